@@ -0,0 +1,182 @@
+//! Engine benchmarks over a corpus of positions from real games.
+//!
+//! The corpus is every decision point of the game logs in `benches/corpus/`,
+//! rebuilt with `GameReplay`. The logs are `colori-runner simulate` games
+//! between 2000-iteration AIs. To widen the corpus, save another log there
+//! and add it to `LOGS`; logs have to be re-recorded when the rules change.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use colori_core::colori_game::{
+    apply_choice_to_state, apply_heuristic_rollout_step, apply_rollout_step, determinize_in_place,
+    enumerate_choices_into,
+};
+use colori_core::game_log::StructuredGameLog;
+use colori_core::ismcts::{ismcts, MctsConfig};
+use colori_core::replay::GameReplay;
+use colori_core::scoring::{calculate_score, HeuristicParams};
+use colori_core::types::{Choice, GamePhase, GameState, MAX_PLAYERS};
+use rand::SeedableRng;
+use wyrand::WyRand;
+
+const LOGS: [&str; 3] = [
+    include_str!("corpus/game-2p.json"),
+    include_str!("corpus/game-3p.json"),
+    include_str!("corpus/game-4p.json"),
+];
+
+const ISMCTS_ITERATIONS: u32 = 1_000;
+const ISMCTS_POSITIONS: usize = 4;
+
+struct Position {
+    state: GameState,
+    player: usize,
+    /// The choice that was made in the game.
+    choice: Choice,
+}
+
+fn heuristic_params() -> HeuristicParams {
+    const PARAMS_JSON: &str = include_str!("../../genetic-algorithm/batch-lki08w-gen-32.json");
+    serde_json::from_str(PARAMS_JSON).expect("Failed to parse heuristic params")
+}
+
+fn load_corpus() -> Vec<Position> {
+    let mut positions = Vec::new();
+    for log in LOGS {
+        let raw: serde_json::Value = serde_json::from_str(log).expect("Failed to parse game log");
+        let game: StructuredGameLog = serde_json::from_value(raw.clone()).expect("Failed to parse game log");
+        let mut replay = GameReplay::new(&raw["initialState"], &game.initial_draws);
+        for entry in &game.entries {
+            replay.fix_current_player_for_next(entry);
+            let player = match &replay.state.phase {
+                GamePhase::Draft { draft_state } => Some(draft_state.current_player_index),
+                GamePhase::Action { action_state } => Some(action_state.current_player_index),
+                _ => None,
+            };
+            if let Some(player) = player {
+                positions.push(Position {
+                    state: replay.state.clone(),
+                    player,
+                    choice: entry.choice.clone(),
+                });
+            }
+            replay.apply_entry(entry);
+        }
+        let scores: Vec<u32> = replay.state.players.iter().map(calculate_score).collect();
+        let logged: Vec<u32> = game.final_scores.iter().flatten().map(|s| s.score).collect();
+        assert_eq!(scores, logged, "Replayed scores differ from the log; re-record the corpus");
+    }
+    positions
+}
+
+fn play_out(state: &mut GameState, mut step: impl FnMut(&mut GameState)) {
+    while !matches!(state.phase, GamePhase::GameOver) {
+        step(state);
+    }
+}
+
+fn benchmarks(c: &mut Criterion) {
+    let params = heuristic_params();
+    let corpus = load_corpus();
+    let states: Vec<GameState> = corpus.iter().map(|p| p.state.clone()).collect();
+
+    let mut group = c.benchmark_group("engine");
+    group.throughput(Throughput::Elements(corpus.len() as u64));
+
+    group.bench_function("enumerate_choices_into", |b| {
+        let mut choices = Vec::new();
+        b.iter(|| {
+            let mut total = 0;
+            for position in &corpus {
+                enumerate_choices_into(&position.state, &mut choices);
+                total += choices.len();
+            }
+            total
+        });
+    });
+
+    group.bench_function("apply_choice_to_state", |b| {
+        let mut rng = WyRand::seed_from_u64(42);
+        b.iter_batched(
+            || states.clone(),
+            |mut states| {
+                for (state, position) in states.iter_mut().zip(&corpus) {
+                    apply_choice_to_state(state, &position.choice, &mut rng);
+                }
+                states
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.bench_function("determinize_in_place", |b| {
+        let mut rng = WyRand::seed_from_u64(42);
+        let cached_scores: Vec<[u32; MAX_PLAYERS]> = corpus
+            .iter()
+            .map(|p| {
+                let mut scores = [0u32; MAX_PLAYERS];
+                for (i, player) in p.state.players.iter().enumerate() {
+                    scores[i] = calculate_score(player);
+                }
+                scores
+            })
+            .collect();
+        let mut det = corpus[0].state.clone();
+        b.iter(|| {
+            for (position, scores) in corpus.iter().zip(&cached_scores) {
+                determinize_in_place(&mut det, &position.state, position.player, scores, &mut rng);
+            }
+        });
+    });
+
+    group.bench_function("random_rollout", |b| {
+        let mut rng = WyRand::seed_from_u64(42);
+        b.iter_batched(
+            || states.clone(),
+            |mut states| {
+                for state in states.iter_mut() {
+                    play_out(state, |s| apply_rollout_step(s, false, &params, &mut rng));
+                }
+                states
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.bench_function("heuristic_rollout", |b| {
+        let mut rng = WyRand::seed_from_u64(42);
+        b.iter_batched(
+            || states.clone(),
+            |mut states| {
+                for state in states.iter_mut() {
+                    play_out(state, |s| apply_heuristic_rollout_step(s, false, &params, &mut rng));
+                }
+                states
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+
+    // Searches are slow, so only a few positions spread across the corpus.
+    let config = MctsConfig {
+        iterations: ISMCTS_ITERATIONS,
+        early_termination: false,
+        ..MctsConfig::new(params)
+    };
+    let searched: Vec<&Position> = corpus.iter().step_by(corpus.len().div_ceil(ISMCTS_POSITIONS)).collect();
+    let mut group = c.benchmark_group("engine");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(ISMCTS_ITERATIONS as u64 * searched.len() as u64));
+    group.bench_function("ismcts", |b| {
+        let mut rng = WyRand::seed_from_u64(42);
+        b.iter(|| {
+            for position in &searched {
+                ismcts(&position.state, position.player, &config, None, None, &mut rng);
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,78 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use colori_core::colori_game::{apply_choice_to_state, enumerate_choices};
+use colori_core::draw_phase::execute_draw_phase;
+use colori_core::ismcts::{ismcts, MctsConfig};
+use colori_core::scoring::HeuristicParams;
+use colori_core::setup::create_initial_game_state;
+use colori_core::types::{GamePhase, GameState};
+use rand::{RngExt, SeedableRng};
+use wyrand::WyRand;
+
+const ITERATIONS: u32 = 2_000;
+
+fn heuristic_params() -> HeuristicParams {
+    const PARAMS_JSON: &str = include_str!("../../genetic-algorithm/batch-lki08w-gen-32.json");
+    serde_json::from_str(PARAMS_JSON).expect("Failed to parse heuristic params")
+}
+
+/// Plays random moves from a fresh game until `round` is reached and it is
+/// the first action-phase decision of that round.
+fn position(num_players: usize, round: u32, seed: u64) -> GameState {
+    let mut rng = WyRand::seed_from_u64(seed);
+    let mut state = create_initial_game_state(num_players, &vec![true; num_players], &mut rng);
+    execute_draw_phase(&mut state, &mut rng);
+    while state.round < round || !matches!(state.phase, GamePhase::Action { .. }) {
+        let choices = enumerate_choices(&state);
+        let choice = &choices[rng.random_range(0..choices.len())];
+        apply_choice_to_state(&mut state, choice, &mut rng);
+        if matches!(state.phase, GamePhase::Draw) {
+            execute_draw_phase(&mut state, &mut rng);
+        }
+    }
+    state
+}
+
+fn current_player(state: &GameState) -> usize {
+    match &state.phase {
+        GamePhase::Draft { draft_state } => draft_state.current_player_index,
+        GamePhase::Action { action_state } => action_state.current_player_index,
+        _ => unreachable!(),
+    }
+}
+
+fn benchmarks(c: &mut Criterion) {
+    let config = MctsConfig {
+        iterations: ITERATIONS,
+        early_termination: false,
+        ..MctsConfig::new(heuristic_params())
+    };
+
+    let mut rng = WyRand::seed_from_u64(1);
+    let mut opening = create_initial_game_state(3, &[true, true, true], &mut rng);
+    execute_draw_phase(&mut opening, &mut rng);
+    let positions = [("draft_opening_3p", opening), ("action_round_4_3p", position(3, 4, 2))];
+
+    let mut group = c.benchmark_group("ismcts");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(ITERATIONS as u64));
+    for (name, state) in &positions {
+        let player = current_player(state);
+        group.bench_function(*name, |b| {
+            let mut rng = WyRand::seed_from_u64(42);
+            b.iter(|| ismcts(state, player, &config, None, None, &mut rng).choice);
+        });
+    }
+
+    // Same search with the tree capped well below one node per iteration.
+    let capped = MctsConfig { max_nodes: Some(ITERATIONS as usize / 4), ..config.clone() };
+    let (name, state) = &positions[1];
+    let player = current_player(state);
+    group.bench_function(format!("{name}_max_nodes"), |b| {
+        let mut rng = WyRand::seed_from_u64(42);
+        b.iter(|| ismcts(state, player, &capped, None, None, &mut rng).choice);
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmarks);
+criterion_main!(benches);
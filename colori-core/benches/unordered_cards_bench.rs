@@ -1,21 +1,31 @@
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
-use colori_core::unordered_cards::UnorderedCards;
+use colori_core::types::CardId;
+use colori_core::unordered_cards::{BitSet, CardMarker, UnorderedCards, CARD_LIMBS};
 use rand::SeedableRng;
 use wyrand::WyRand;
 
 fn make_set_50() -> UnorderedCards {
     let mut set = UnorderedCards::new();
-    for i in 0..50u8 {
+    for i in 0..50 {
         set.insert(i);
     }
     set
 }
 
+/// 50 cards spread over the first two limbs.
+fn make_spread_set<const LIMBS: usize>() -> BitSet<CardMarker, LIMBS> {
+    let mut set = BitSet::new();
+    for i in 0..50 {
+        set.insert(i * 5);
+    }
+    set
+}
+
 fn benchmarks(c: &mut Criterion) {
     c.bench_function("bench_insert", |b| {
         b.iter(|| {
             let mut set = UnorderedCards::new();
-            for i in 0..50u8 {
+            for i in 0..50 {
                 set.insert(black_box(i));
             }
             set
@@ -25,7 +35,7 @@ fn benchmarks(c: &mut Criterion) {
     c.bench_function("bench_contains", |b| {
         let set = make_set_50();
         b.iter(|| {
-            for i in 0..50u8 {
+            for i in 0..50 {
                 black_box(set.contains(black_box(i)));
             }
         });
@@ -39,7 +49,7 @@ fn benchmarks(c: &mut Criterion) {
     c.bench_function("bench_union", |b| {
         let a = make_set_50();
         let mut b_set = UnorderedCards::new();
-        for i in 50..100u8 {
+        for i in 50..100 {
             b_set.insert(i);
         }
         b.iter(|| black_box(black_box(a).union(black_box(b_set))));
@@ -48,7 +58,7 @@ fn benchmarks(c: &mut Criterion) {
     c.bench_function("bench_intersection", |b| {
         let a = make_set_50();
         let mut b_set = UnorderedCards::new();
-        for i in 25..75u8 {
+        for i in 25..75 {
             b_set.insert(i);
         }
         b.iter(|| black_box(black_box(a).intersection(black_box(b_set))));
@@ -57,7 +67,7 @@ fn benchmarks(c: &mut Criterion) {
     c.bench_function("bench_difference", |b| {
         let a = make_set_50();
         let mut b_set = UnorderedCards::new();
-        for i in 25..75u8 {
+        for i in 25..75 {
             b_set.insert(i);
         }
         b.iter(|| black_box(black_box(a).difference(black_box(b_set))));
@@ -66,7 +76,7 @@ fn benchmarks(c: &mut Criterion) {
     c.bench_function("bench_iter_collect", |b| {
         let set = make_set_50();
         b.iter(|| {
-            let v: Vec<u8> = black_box(set).iter().collect();
+            let v: Vec<CardId> = black_box(set).iter().collect();
             black_box(v)
         });
     });
@@ -122,5 +132,46 @@ fn benchmarks(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmarks);
+fn width_benchmarks(c: &mut Criterion) {
+    fn bench_width<const LIMBS: usize>(c: &mut Criterion, label: &str) {
+        c.bench_function(&format!("bench_{label}_pick_random_spread"), |b| {
+            let set = make_spread_set::<LIMBS>();
+            let mut rng = WyRand::seed_from_u64(42);
+            b.iter(|| black_box(set.pick_random(&mut rng)));
+        });
+
+        c.bench_function(&format!("bench_{label}_draw_spread"), |b| {
+            let mut rng = WyRand::seed_from_u64(42);
+            b.iter_batched(
+                make_spread_set::<LIMBS>,
+                |mut set| black_box(set.draw(&mut rng)),
+                BatchSize::SmallInput,
+            );
+        });
+
+        c.bench_function(&format!("bench_{label}_draw_multiple_5_spread"), |b| {
+            let mut rng = WyRand::seed_from_u64(42);
+            b.iter_batched(
+                make_spread_set::<LIMBS>,
+                |mut set| black_box(set.draw_multiple(black_box(5), &mut rng)),
+                BatchSize::SmallInput,
+            );
+        });
+
+        c.bench_function(&format!("bench_{label}_iter_collect_spread"), |b| {
+            let set = make_spread_set::<LIMBS>();
+            b.iter(|| {
+                let v: Vec<CardId> = black_box(set).iter().collect();
+                black_box(v)
+            });
+        });
+    }
+
+    // The double-width set only ever holds low ids here, so any gap between
+    // the two is the cost of scanning the extra limbs.
+    bench_width::<CARD_LIMBS>(c, "default_width");
+    bench_width::<{ CARD_LIMBS * 2 }>(c, "double_width");
+}
+
+criterion_group!(benches, benchmarks, width_benchmarks);
 criterion_main!(benches);
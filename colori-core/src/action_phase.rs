@@ -3,8 +3,8 @@ use crate::deck_utils::draw_from_deck;
 use crate::draw_log_helpers::{is_replaying, record_player_deck_draw, replay_player_deck_draw, replay_sell_card_reveal};
 use crate::game_log::{DrawEvent, DrawLog};
 use crate::types::{
-    Ability, AbilityStack, ActionState, Card, Color, GamePhase, GameState,
-    PlayerState, SellCard, SellCardInstance,
+    Ability, AbilityStack, ActionState, Card, CardId, Color, GamePhase, GameState,
+    PlayerState, SellCard, SellCardInstance, MAX_CARD_INSTANCES,
 };
 use crate::unordered_cards::UnorderedCards;
 use rand::Rng;
@@ -13,7 +13,7 @@ use smallvec::SmallVec;
 /// Iterate over unique card types in a card set, deduplicating by card variant.
 pub(crate) fn for_each_unique_card_type(
     cards: &UnorderedCards,
-    card_lookup: &[Card; 256],
+    card_lookup: &[Card; MAX_CARD_INSTANCES],
     mut f: impl FnMut(Card),
 ) {
     let mut seen: u64 = 0;
@@ -31,7 +31,7 @@ pub(crate) fn for_each_unique_card_type(
 /// — deduplicating by card variant.
 pub(crate) fn for_each_unique_card_type_in_workshop_area(
     player: &PlayerState,
-    card_lookup: &[Card; 256],
+    card_lookup: &[Card; MAX_CARD_INSTANCES],
     f: impl FnMut(Card),
 ) {
     let union = player.workshop_cards.union(player.workshopped_cards);
@@ -41,7 +41,7 @@ pub(crate) fn for_each_unique_card_type_in_workshop_area(
 /// Remove `id` from whichever of the player's workshop-area buckets
 /// (`workshop_cards` or `workshopped_cards`) contains it. Returns `true` on
 /// success.
-pub(crate) fn remove_from_workshop_area(player: &mut PlayerState, id: u8) -> bool {
+pub(crate) fn remove_from_workshop_area(player: &mut PlayerState, id: CardId) -> bool {
     if player.workshop_cards.contains(id) {
         player.workshop_cards.remove(id);
         true
@@ -57,11 +57,11 @@ pub(crate) fn remove_from_workshop_area(player: &mut PlayerState, id: u8) -> boo
 /// Returns `(action_ids, action_count, non_action_ids, non_action_count)`.
 fn partition_action_cards(
     selected_cards: &UnorderedCards,
-    card_lookup: &[Card; 256],
-) -> ([u8; 16], usize, [u8; 16], usize) {
-    let mut action_ids = [0u8; 16];
+    card_lookup: &[Card; MAX_CARD_INSTANCES],
+) -> ([CardId; 16], usize, [CardId; 16], usize) {
+    let mut action_ids: [CardId; 16] = [0; 16];
     let mut action_count = 0usize;
-    let mut non_action_ids = [0u8; 16];
+    let mut non_action_ids: [CardId; 16] = [0; 16];
     let mut non_action_count = 0usize;
     for id in selected_cards.iter() {
         let card = card_lookup[id as usize];
@@ -79,8 +79,8 @@ fn partition_action_cards(
 /// Processes non-action cards: extracts materials/colors, moves from workshop to workshopped.
 fn process_non_action_cards(
     player: &mut PlayerState,
-    card_lookup: &[Card; 256],
-    non_action_ids: &[u8; 16],
+    card_lookup: &[Card; MAX_CARD_INSTANCES],
+    non_action_ids: &[CardId; 16],
     non_action_count: usize,
 ) {
     for i in 0..non_action_count {
@@ -168,8 +168,8 @@ impl CollectedAbilities {
 /// Processes action cards: removes from workshop, moves to workshopped, and collects abilities.
 fn collect_abilities_from_action_cards(
     player: &mut PlayerState,
-    card_lookup: &[Card; 256],
-    action_ids: &[u8; 16],
+    card_lookup: &[Card; MAX_CARD_INSTANCES],
+    action_ids: &[CardId; 16],
     action_count: usize,
 ) -> CollectedAbilities {
     let mut collected = CollectedAbilities::new();
@@ -243,7 +243,7 @@ pub fn initialize_action_phase(state: &mut GameState) {
 }
 
 pub fn destroy_drafted_card<R: Rng>(state: &mut GameState, card_instance_id: u32, rng: &mut R) {
-    let id = card_instance_id as u8;
+    let id = card_instance_id as CardId;
     let player_index = get_action_state(state).current_player_index;
     let player = &mut state.players[player_index];
 
@@ -274,7 +274,7 @@ pub fn destroy_workshop_card_and_trigger<R: Rng>(
     card_instance_id: u32,
     rng: &mut R,
 ) {
-    let id = card_instance_id as u8;
+    let id = card_instance_id as CardId;
     let player_index = get_action_state(state).current_player_index;
     let player = &mut state.players[player_index];
 
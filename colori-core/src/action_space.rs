@@ -0,0 +1,466 @@
+//! Fixed integer action space covering every enumerable `Choice`.
+//!
+//! Indices are laid out as contiguous sections, one per `Choice` variant, in
+//! the order of the `*_BASE` constants below. Within a section:
+//!
+//! - cards and sell cards are indexed by discriminant;
+//! - primaries, secondaries and tertiaries by position in `PRIMARIES`,
+//!   `SECONDARIES` and `TERTIARIES`;
+//! - `SwapTertiary` is `lose * 6 + gain` (the diagonal is never legal);
+//! - a sequence of `n` mixes is `(9^n - 1) / 8` (the number of shorter
+//!   sequences) plus its pairs read as a base-9 number, each pair by its
+//!   index in `VALID_MIX_PAIRS`: `0` for no mixes, `1 + p` for one, `10 +
+//!   p * 9 + q` for two and so on, up to `MAX_MIXES` mixes;
+//! - workshop multisets (up to 4 cards) use the combinatorial number system
+//!   over 46 symbols, with symbol 45 standing for an empty slot;
+//! - the destroy combos are indexed by the destroyed card's rank among cards
+//!   sharing its ability, times the size of the follow-up sub-space.
+//!
+//! The UI-only deferred choices have no index.
+
+use crate::cards::generate_all_sell_cards;
+use crate::colori_game::enumerate_choices;
+use crate::colors::{PRIMARIES, SECONDARIES, TERTIARIES, VALID_MIX_PAIRS};
+use crate::types::*;
+use smallvec::SmallVec;
+
+const NUM_CARDS: u32 = 45;
+const NUM_SELL_CARDS: u32 = 54;
+const MAX_WORKSHOP_CARDS: usize = 4;
+const WORKSHOP_SUBSETS: u32 = 211_876; // C(49, 4)
+/// Longest mix sequence with an index. The base game mixes at most twice;
+/// the rest of the room is for cards that mix more.
+pub const MAX_MIXES: usize = 4;
+const MIX_SEQUENCES: u32 = mix_sequence_offset(MAX_MIXES + 1);
+const DESTROY_TARGETS: u32 = NUM_CARDS + 1;
+
+const MIX_ABILITY_CARDS: u32 = 6;
+const SELL_ABILITY_CARDS: u32 = 13;
+const WORKSHOP_ABILITY_CARDS: u32 = 18;
+const DESTROY_ABILITY_CARDS: u32 = 7;
+
+pub const DRAFT_PICK_BASE: u32 = 0;
+pub const DESTROY_DRAFTED_CARD_BASE: u32 = DRAFT_PICK_BASE + NUM_CARDS;
+pub const END_TURN_BASE: u32 = DESTROY_DRAFTED_CARD_BASE + NUM_CARDS;
+pub const WORKSHOP_BASE: u32 = END_TURN_BASE + 1;
+pub const SKIP_WORKSHOP_BASE: u32 = WORKSHOP_BASE + WORKSHOP_SUBSETS;
+pub const DESTROY_DRAWN_CARDS_BASE: u32 = SKIP_WORKSHOP_BASE + 1;
+pub const SELECT_SELL_CARD_BASE: u32 = DESTROY_DRAWN_CARDS_BASE + DESTROY_TARGETS;
+pub const GAIN_SECONDARY_BASE: u32 = SELECT_SELL_CARD_BASE + NUM_SELL_CARDS;
+pub const GAIN_PRIMARY_BASE: u32 = GAIN_SECONDARY_BASE + 3;
+pub const MIX_ALL_BASE: u32 = GAIN_PRIMARY_BASE + 3;
+pub const SWAP_TERTIARY_BASE: u32 = MIX_ALL_BASE + MIX_SEQUENCES;
+pub const DESTROY_AND_MIX_BASE: u32 = SWAP_TERTIARY_BASE + 6 * 6;
+pub const DESTROY_AND_SELL_BASE: u32 = DESTROY_AND_MIX_BASE + MIX_ABILITY_CARDS * MIX_SEQUENCES;
+pub const DESTROY_AND_WORKSHOP_BASE: u32 = DESTROY_AND_SELL_BASE + SELL_ABILITY_CARDS * NUM_SELL_CARDS;
+pub const DESTROY_AND_DESTROY_CARDS_BASE: u32 =
+    DESTROY_AND_WORKSHOP_BASE + WORKSHOP_ABILITY_CARDS * WORKSHOP_SUBSETS;
+pub const SELECT_MOVE_TO_DRAFTED_BASE: u32 =
+    DESTROY_AND_DESTROY_CARDS_BASE + DESTROY_ABILITY_CARDS * DESTROY_TARGETS;
+pub const SKIP_MOVE_TO_DRAFTED_BASE: u32 = SELECT_MOVE_TO_DRAFTED_BASE + NUM_CARDS;
+pub const SELECT_MOVE_TO_WORKSHOP_BASE: u32 = SKIP_MOVE_TO_DRAFTED_BASE + 1;
+pub const SKIP_MOVE_TO_WORKSHOP_BASE: u32 = SELECT_MOVE_TO_WORKSHOP_BASE + NUM_CARDS;
+
+/// Total number of action indices, 4,078,661. The workshop sections take
+/// `WORKSHOP_SUBSETS` indices for `Workshop` and again for each workshop
+/// ability card, 99% of the total, so a dense mask or policy head costs
+/// about 4 MB per decision as bytes (510 KB as bits, as in
+/// `legal_action_mask`). Use `legal_action_indices` and score only the
+/// legal actions.
+pub const ACTION_SPACE_SIZE: u32 = SKIP_MOVE_TO_WORKSHOP_BASE + 1;
+
+// ── Destroyed-card classes ──
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AbilityClass {
+    Mix,
+    Sell,
+    Workshop,
+    DestroyCards,
+}
+
+fn in_class(card: Card, class: AbilityClass) -> bool {
+    match class {
+        AbilityClass::Mix => matches!(card.ability(), Ability::MixColors { .. }),
+        AbilityClass::Sell => matches!(card.ability(), Ability::Sell),
+        AbilityClass::Workshop => matches!(card.ability(), Ability::Workshop { .. }),
+        AbilityClass::DestroyCards => matches!(card.ability(), Ability::DestroyCards),
+    }
+}
+
+fn class_rank(card: Card, class: AbilityClass) -> Option<u32> {
+    if !in_class(card, class) {
+        return None;
+    }
+    Some(ALL_CARDS[..card as usize].iter().filter(|&&c| in_class(c, class)).count() as u32)
+}
+
+fn class_card(rank: u32, class: AbilityClass) -> Option<Card> {
+    ALL_CARDS.iter().copied().filter(|&c| in_class(c, class)).nth(rank as usize)
+}
+
+// ── Sub-space ranking ──
+
+fn binomial(n: u32, k: u32) -> u32 {
+    if k > n {
+        return 0;
+    }
+    let mut result = 1u64;
+    for i in 0..k as u64 {
+        result = result * (n as u64 - i) / (i + 1);
+    }
+    result as u32
+}
+
+fn workshop_rank(cards: &[Card]) -> Option<u32> {
+    if cards.len() > MAX_WORKSHOP_CARDS {
+        return None;
+    }
+    let mut symbols = [NUM_CARDS; MAX_WORKSHOP_CARDS];
+    for (i, &card) in cards.iter().enumerate() {
+        symbols[i] = card as u32;
+    }
+    symbols.sort_unstable();
+    let mut rank = 0;
+    for (i, &s) in symbols.iter().enumerate() {
+        rank += binomial(s + i as u32, i as u32 + 1);
+    }
+    Some(rank)
+}
+
+fn workshop_unrank(mut rank: u32) -> SmallVec<[Card; 4]> {
+    let mut symbols = [0u32; MAX_WORKSHOP_CARDS];
+    for i in (0..MAX_WORKSHOP_CARDS as u32).rev() {
+        let mut b = i;
+        while binomial(b + 1, i + 1) <= rank {
+            b += 1;
+        }
+        rank -= binomial(b, i + 1);
+        symbols[i as usize] = b - i;
+    }
+    symbols
+        .iter()
+        .filter(|&&s| s < NUM_CARDS)
+        .map(|&s| ALL_CARDS[s as usize])
+        .collect()
+}
+
+fn mix_pair_index(pair: (Color, Color)) -> Option<u32> {
+    VALID_MIX_PAIRS
+        .iter()
+        .position(|&(a, b)| (a, b) == pair || (b, a) == pair)
+        .map(|p| p as u32)
+}
+
+/// Number of mix sequences shorter than `len`, where the sequences of
+/// length `len` start.
+const fn mix_sequence_offset(len: usize) -> u32 {
+    (9u32.pow(len as u32) - 1) / 8
+}
+
+fn mix_sequence_rank(mixes: &[(Color, Color)]) -> Option<u32> {
+    if mixes.len() > MAX_MIXES {
+        return None;
+    }
+    let mut digits = 0;
+    for &pair in mixes {
+        digits = digits * 9 + mix_pair_index(pair)?;
+    }
+    Some(mix_sequence_offset(mixes.len()) + digits)
+}
+
+fn mix_sequence_unrank(rank: u32) -> SmallVec<[(Color, Color); 2]> {
+    let len = (0..=MAX_MIXES).rev().find(|&len| mix_sequence_offset(len) <= rank).unwrap();
+    let mut digits = rank - mix_sequence_offset(len);
+    let mut mixes: SmallVec<[(Color, Color); 2]> = (0..len)
+        .map(|_| {
+            let pair = VALID_MIX_PAIRS[(digits % 9) as usize];
+            digits /= 9;
+            pair
+        })
+        .collect();
+    mixes.reverse();
+    mixes
+}
+
+fn destroy_target_rank(target: Option<Card>) -> u32 {
+    target.map_or(0, |card| card as u32 + 1)
+}
+
+fn destroy_target_unrank(rank: u32) -> Option<Card> {
+    if rank == 0 {
+        None
+    } else {
+        Some(ALL_CARDS[rank as usize - 1])
+    }
+}
+
+fn color_position(colors: &[Color], color: Color) -> Option<u32> {
+    colors.iter().position(|&c| c == color).map(|p| p as u32)
+}
+
+// ── Public API ──
+
+/// Maps a choice to its index in the fixed action space. Returns `None` for
+/// choices outside the space: the UI-only deferred variants, and malformed
+/// choices such as workshopping more than four cards.
+pub fn choice_to_index(choice: &Choice) -> Option<u32> {
+    let index = match choice {
+        Choice::DraftPick { card } => DRAFT_PICK_BASE + *card as u32,
+        Choice::DestroyDraftedCard { card } => DESTROY_DRAFTED_CARD_BASE + *card as u32,
+        Choice::EndTurn => END_TURN_BASE,
+        Choice::Workshop { card_types } => WORKSHOP_BASE + workshop_rank(card_types)?,
+        Choice::SkipWorkshop => SKIP_WORKSHOP_BASE,
+        Choice::DestroyDrawnCards { card } => DESTROY_DRAWN_CARDS_BASE + destroy_target_rank(*card),
+        Choice::SelectSellCard { sell_card } => SELECT_SELL_CARD_BASE + *sell_card as u32,
+        Choice::GainSecondary { color } => GAIN_SECONDARY_BASE + color_position(&SECONDARIES, *color)?,
+        Choice::GainPrimary { color } => GAIN_PRIMARY_BASE + color_position(&PRIMARIES, *color)?,
+        Choice::MixAll { mixes } => MIX_ALL_BASE + mix_sequence_rank(mixes)?,
+        Choice::SwapTertiary { lose, gain } => {
+            SWAP_TERTIARY_BASE
+                + color_position(&TERTIARIES, *lose)? * 6
+                + color_position(&TERTIARIES, *gain)?
+        }
+        Choice::DestroyAndMix { card, mixes } => {
+            DESTROY_AND_MIX_BASE
+                + class_rank(*card, AbilityClass::Mix)? * MIX_SEQUENCES
+                + mix_sequence_rank(mixes)?
+        }
+        Choice::DestroyAndSell { card, sell_card } => {
+            DESTROY_AND_SELL_BASE
+                + class_rank(*card, AbilityClass::Sell)? * NUM_SELL_CARDS
+                + *sell_card as u32
+        }
+        Choice::DestroyAndWorkshop { card, workshop_cards } => {
+            DESTROY_AND_WORKSHOP_BASE
+                + class_rank(*card, AbilityClass::Workshop)? * WORKSHOP_SUBSETS
+                + workshop_rank(workshop_cards)?
+        }
+        Choice::DestroyAndDestroyCards { card, target } => {
+            DESTROY_AND_DESTROY_CARDS_BASE
+                + class_rank(*card, AbilityClass::DestroyCards)? * DESTROY_TARGETS
+                + destroy_target_rank(*target)
+        }
+        Choice::SelectMoveToDrafted { card } => SELECT_MOVE_TO_DRAFTED_BASE + *card as u32,
+        Choice::SkipMoveToDrafted => SKIP_MOVE_TO_DRAFTED_BASE,
+        Choice::SelectMoveToWorkshop { card } => SELECT_MOVE_TO_WORKSHOP_BASE + *card as u32,
+        Choice::SkipMoveToWorkshop => SKIP_MOVE_TO_WORKSHOP_BASE,
+        Choice::DeferredMoveToDraft { .. } | Choice::DestroyWorkshopCardDeferred { .. } => return None,
+    };
+    Some(index)
+}
+
+/// Inverse of `choice_to_index`. Returns `None` when `index` is out of range.
+/// Card lists and mix pairs come back in the canonical order produced by
+/// `enumerate_choices`, so enumerated choices round-trip exactly.
+pub fn index_to_choice(index: u32) -> Option<Choice> {
+    let choice = if index < DESTROY_DRAFTED_CARD_BASE {
+        Choice::DraftPick { card: ALL_CARDS[(index - DRAFT_PICK_BASE) as usize] }
+    } else if index < END_TURN_BASE {
+        Choice::DestroyDraftedCard { card: ALL_CARDS[(index - DESTROY_DRAFTED_CARD_BASE) as usize] }
+    } else if index < WORKSHOP_BASE {
+        Choice::EndTurn
+    } else if index < SKIP_WORKSHOP_BASE {
+        Choice::Workshop { card_types: workshop_unrank(index - WORKSHOP_BASE) }
+    } else if index < DESTROY_DRAWN_CARDS_BASE {
+        Choice::SkipWorkshop
+    } else if index < SELECT_SELL_CARD_BASE {
+        Choice::DestroyDrawnCards { card: destroy_target_unrank(index - DESTROY_DRAWN_CARDS_BASE) }
+    } else if index < GAIN_SECONDARY_BASE {
+        Choice::SelectSellCard {
+            sell_card: generate_all_sell_cards()[(index - SELECT_SELL_CARD_BASE) as usize],
+        }
+    } else if index < GAIN_PRIMARY_BASE {
+        Choice::GainSecondary { color: SECONDARIES[(index - GAIN_SECONDARY_BASE) as usize] }
+    } else if index < MIX_ALL_BASE {
+        Choice::GainPrimary { color: PRIMARIES[(index - GAIN_PRIMARY_BASE) as usize] }
+    } else if index < SWAP_TERTIARY_BASE {
+        Choice::MixAll { mixes: mix_sequence_unrank(index - MIX_ALL_BASE) }
+    } else if index < DESTROY_AND_MIX_BASE {
+        let offset = (index - SWAP_TERTIARY_BASE) as usize;
+        Choice::SwapTertiary { lose: TERTIARIES[offset / 6], gain: TERTIARIES[offset % 6] }
+    } else if index < DESTROY_AND_SELL_BASE {
+        let offset = index - DESTROY_AND_MIX_BASE;
+        Choice::DestroyAndMix {
+            card: class_card(offset / MIX_SEQUENCES, AbilityClass::Mix)?,
+            mixes: mix_sequence_unrank(offset % MIX_SEQUENCES),
+        }
+    } else if index < DESTROY_AND_WORKSHOP_BASE {
+        let offset = index - DESTROY_AND_SELL_BASE;
+        Choice::DestroyAndSell {
+            card: class_card(offset / NUM_SELL_CARDS, AbilityClass::Sell)?,
+            sell_card: generate_all_sell_cards()[(offset % NUM_SELL_CARDS) as usize],
+        }
+    } else if index < DESTROY_AND_DESTROY_CARDS_BASE {
+        let offset = index - DESTROY_AND_WORKSHOP_BASE;
+        Choice::DestroyAndWorkshop {
+            card: class_card(offset / WORKSHOP_SUBSETS, AbilityClass::Workshop)?,
+            workshop_cards: workshop_unrank(offset % WORKSHOP_SUBSETS),
+        }
+    } else if index < SELECT_MOVE_TO_DRAFTED_BASE {
+        let offset = index - DESTROY_AND_DESTROY_CARDS_BASE;
+        Choice::DestroyAndDestroyCards {
+            card: class_card(offset / DESTROY_TARGETS, AbilityClass::DestroyCards)?,
+            target: destroy_target_unrank(offset % DESTROY_TARGETS),
+        }
+    } else if index < SKIP_MOVE_TO_DRAFTED_BASE {
+        Choice::SelectMoveToDrafted { card: ALL_CARDS[(index - SELECT_MOVE_TO_DRAFTED_BASE) as usize] }
+    } else if index < SELECT_MOVE_TO_WORKSHOP_BASE {
+        Choice::SkipMoveToDrafted
+    } else if index < SKIP_MOVE_TO_WORKSHOP_BASE {
+        Choice::SelectMoveToWorkshop { card: ALL_CARDS[(index - SELECT_MOVE_TO_WORKSHOP_BASE) as usize] }
+    } else if index < ACTION_SPACE_SIZE {
+        Choice::SkipMoveToWorkshop
+    } else {
+        return None;
+    };
+    Some(choice)
+}
+
+/// Indices of all legal choices, in `enumerate_choices` order.
+///
+/// Panics if a card mixes more than `MAX_MIXES` times, which no card does.
+pub fn legal_action_indices(state: &GameState) -> Vec<u32> {
+    enumerate_choices(state)
+        .iter()
+        .map(|choice| choice_to_index(choice).expect("Enumerated choice outside the action space"))
+        .collect()
+}
+
+/// Legal-action mask packed 64 actions to a word: action `i` is legal if bit
+/// `i % 64` of word `i / 64` is set. Prefer `legal_action_indices` when the
+/// consumer can take indices; see `ACTION_SPACE_SIZE` for the size.
+pub fn legal_action_mask(state: &GameState) -> Vec<u64> {
+    let mut mask = vec![0u64; ACTION_SPACE_SIZE.div_ceil(64) as usize];
+    for index in legal_action_indices(state) {
+        mask[index as usize / 64] |= 1 << (index % 64);
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colori_game::apply_choice_to_state;
+    use crate::draw_phase::execute_draw_phase;
+    use crate::setup::create_initial_game_state;
+    use rand::RngExt;
+    use rand::SeedableRng;
+    use wyrand::WyRand;
+
+    #[test]
+    fn test_ability_class_sizes() {
+        let count = |class| ALL_CARDS.iter().filter(|&&c| in_class(c, class)).count() as u32;
+        assert_eq!(count(AbilityClass::Mix), MIX_ABILITY_CARDS);
+        assert_eq!(count(AbilityClass::Sell), SELL_ABILITY_CARDS);
+        assert_eq!(count(AbilityClass::Workshop), WORKSHOP_ABILITY_CARDS);
+        assert_eq!(count(AbilityClass::DestroyCards), DESTROY_ABILITY_CARDS);
+        assert_eq!(binomial(49, 4), WORKSHOP_SUBSETS);
+    }
+
+    #[test]
+    fn test_card_tables_in_discriminant_order() {
+        for (i, card) in ALL_CARDS.iter().enumerate() {
+            assert_eq!(*card as usize, i);
+        }
+        for (i, sell_card) in generate_all_sell_cards().iter().enumerate() {
+            assert_eq!(*sell_card as usize, i);
+        }
+    }
+
+    #[test]
+    fn test_workshop_rank_is_bijective() {
+        for rank in 0..WORKSHOP_SUBSETS {
+            let cards = workshop_unrank(rank);
+            assert!(cards.windows(2).all(|w| w[0] as usize <= w[1] as usize));
+            assert_eq!(workshop_rank(&cards), Some(rank));
+        }
+    }
+
+    #[test]
+    fn test_every_index_round_trips() {
+        // The workshop sections are covered by test_workshop_rank_is_bijective;
+        // sample them sparsely to keep this test fast.
+        let mut index = 0;
+        while index < ACTION_SPACE_SIZE {
+            let choice = index_to_choice(index).unwrap();
+            assert_eq!(choice_to_index(&choice), Some(index), "{:?}", choice);
+            let in_workshop = (WORKSHOP_BASE..SKIP_WORKSHOP_BASE).contains(&index)
+                || (DESTROY_AND_WORKSHOP_BASE..DESTROY_AND_DESTROY_CARDS_BASE).contains(&index);
+            index += if in_workshop { 97 } else { 1 };
+        }
+        assert_eq!(index_to_choice(ACTION_SPACE_SIZE), None);
+        assert_eq!(ACTION_SPACE_SIZE, 4_078_661);
+    }
+
+    #[test]
+    fn test_enumerated_choices_round_trip() {
+        for seed in 0..20u64 {
+            let num_players = 2 + (seed as usize % 3);
+            let mut rng = WyRand::seed_from_u64(seed);
+            let ai_players = vec![true; num_players];
+            let mut state = create_initial_game_state(num_players, &ai_players, &mut rng);
+            execute_draw_phase(&mut state, &mut rng);
+            while !matches!(state.phase, GamePhase::GameOver) {
+                let choices = enumerate_choices(&state);
+                let indices = legal_action_indices(&state);
+                for (choice, &index) in choices.iter().zip(indices.iter()) {
+                    assert_eq!(index_to_choice(index).as_ref(), Some(choice));
+                }
+                let mut unique = indices.clone();
+                unique.sort_unstable();
+                unique.dedup();
+                assert_eq!(unique.len(), indices.len(), "Duplicate action indices (seed={})", seed);
+
+                let choice = &choices[rng.random_range(0..choices.len())];
+                apply_choice_to_state(&mut state, choice, &mut rng);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mix_sequence_rank_is_bijective() {
+        for rank in 0..MIX_SEQUENCES {
+            let mixes = mix_sequence_unrank(rank);
+            assert!(mixes.len() <= MAX_MIXES);
+            assert_eq!(mix_sequence_rank(&mixes), Some(rank));
+        }
+        let too_long = [VALID_MIX_PAIRS[0]; MAX_MIXES + 1];
+        assert_eq!(mix_sequence_rank(&too_long), None);
+    }
+
+    #[test]
+    fn test_legal_action_indices_with_three_mixes() {
+        let mut rng = WyRand::seed_from_u64(3);
+        let mut state = create_initial_game_state(2, &[true, true], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        while !matches!(state.phase, GamePhase::Action { .. }) {
+            let choices = enumerate_choices(&state);
+            apply_choice_to_state(&mut state, &choices[0], &mut rng);
+        }
+        let GamePhase::Action { action_state } = &mut state.phase else { unreachable!() };
+        let player = action_state.current_player_index;
+        action_state.ability_stack.push(Ability::MixColors { count: 3 });
+        for &color in &[Color::Red, Color::Yellow, Color::Blue] {
+            state.players[player].color_wheel.set(color, 2);
+        }
+
+        let choices = enumerate_choices(&state);
+        assert!(choices.iter().any(|c| matches!(c, Choice::MixAll { mixes } if mixes.len() == 3)));
+        let indices = legal_action_indices(&state);
+        for (choice, &index) in choices.iter().zip(indices.iter()) {
+            assert_eq!(index_to_choice(index).as_ref(), Some(choice));
+        }
+
+        let mask = legal_action_mask(&state);
+        assert_eq!(mask.len(), ACTION_SPACE_SIZE.div_ceil(64) as usize);
+        assert_eq!(mask.iter().map(|w| w.count_ones() as usize).sum::<usize>(), indices.len());
+        assert!(indices.iter().all(|&i| mask[i as usize / 64] >> (i % 64) & 1 == 1));
+    }
+
+    #[test]
+    fn test_deferred_choices_have_no_index() {
+        assert_eq!(choice_to_index(&Choice::DeferredMoveToDraft { card: Card::Lye }), None);
+        assert_eq!(choice_to_index(&Choice::DestroyWorkshopCardDeferred { card: Card::Lye }), None);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::action_phase::*;
 use crate::draft_phase::player_pick;
-use crate::types::{Ability, Card, Choice, GamePhase, GameState, SellCard};
+use crate::types::{Ability, Card, CardId, Choice, GamePhase, GameState, SellCard, MAX_CARD_INSTANCES};
 use crate::unordered_cards::UnorderedCards;
 use rand::Rng;
 
@@ -11,7 +11,7 @@ fn find_card_instance(state: &GameState, card: &Card, cards: &UnorderedCards) ->
             return id as u32;
         }
     }
-    let contents: Vec<(u8, Card)> = cards.iter().map(|id| (id, state.card_lookup[id as usize])).collect();
+    let contents: Vec<(CardId, Card)> = cards.iter().map(|id| (id, state.card_lookup[id as usize])).collect();
     panic!(
         "Card type {:?} not found in card set (round {}, phase {:?}, set contents: {:?})",
         card, state.round, phase_name(&state.phase), contents
@@ -53,7 +53,7 @@ fn find_sell_card_instance(state: &GameState, sell_card: &SellCard) -> u32 {
 pub(crate) fn resolve_card_types_to_ids(
     card_types: &[Card],
     available: &UnorderedCards,
-    card_lookup: &[Card; 256],
+    card_lookup: &[Card; MAX_CARD_INSTANCES],
 ) -> Option<UnorderedCards> {
     let mut ids = UnorderedCards::new();
     let mut used = UnorderedCards::new();
@@ -214,9 +214,9 @@ pub fn apply_choice<R: Rng>(state: &mut GameState, choice: &Choice, rng: &mut R)
                 .workshop_cards
                 .union(state.players[player_index].workshopped_cards);
             let id = find_card_instance(state, card, &area);
-            let removed = remove_from_workshop_area(&mut state.players[player_index], id as u8);
+            let removed = remove_from_workshop_area(&mut state.players[player_index], id as CardId);
             assert!(removed, "Card not found in workshop area for SelectMoveToDrafted");
-            state.players[player_index].drafted_cards.insert(id as u8);
+            state.players[player_index].drafted_cards.insert(id as CardId);
             process_ability_stack(state, rng);
         }
         Choice::SkipMoveToDrafted => {
@@ -227,8 +227,8 @@ pub fn apply_choice<R: Rng>(state: &mut GameState, choice: &Choice, rng: &mut R)
             get_action_state_mut(state).ability_stack.pop();
             let player_index = get_action_state(state).current_player_index;
             let id = find_card_instance(state, card, &state.players[player_index].drafted_cards);
-            state.players[player_index].drafted_cards.remove(id as u8);
-            state.players[player_index].workshop_cards.insert(id as u8);
+            state.players[player_index].drafted_cards.remove(id as CardId);
+            state.players[player_index].workshop_cards.insert(id as CardId);
             process_ability_stack(state, rng);
         }
         Choice::SkipMoveToWorkshop => {
@@ -1,7 +1,14 @@
 use crate::colors::{perform_mix_unchecked, VALID_MIX_PAIRS};
 use crate::types::*;
+use rustc_hash::FxHashSet;
 use smallvec::SmallVec;
 
+/// Pushes one choice per distinct wheel reachable with up to
+/// `remaining_mixes` mixes, starting with the empty (skip) sequence.
+///
+/// Every mix removes one color from the wheel, so sequences of different
+/// lengths never collide; among sequences of the same length that reach the
+/// same wheel, only the first in depth-first order is kept.
 pub(super) fn enumerate_mix_sequences<F>(
     wheel: &ColorWheel,
     remaining_mixes: u32,
@@ -13,24 +20,120 @@ pub(super) fn enumerate_mix_sequences<F>(
     // Always include skip-all (empty mixes)
     choices.push(make_choice(SmallVec::new()));
 
+    let mut seen = FxHashSet::default();
+    let mut mixes = SmallVec::new();
+    push_mix_extensions(wheel, remaining_mixes, &mut mixes, &mut seen, choices, &make_choice);
+}
+
+fn push_mix_extensions<F>(
+    wheel: &ColorWheel,
+    remaining_mixes: u32,
+    mixes: &mut SmallVec<[(Color, Color); 2]>,
+    seen: &mut FxHashSet<[u32; NUM_COLORS]>,
+    choices: &mut Vec<Choice>,
+    make_choice: &F,
+) where
+    F: Fn(SmallVec<[(Color, Color); 2]>) -> Choice,
+{
+    if remaining_mixes == 0 {
+        return;
+    }
     for &(a, b) in &VALID_MIX_PAIRS {
         if wheel.get(a) > 0 && wheel.get(b) > 0 {
-            let mut mixes1 = SmallVec::new();
-            mixes1.push((a, b));
-            choices.push(make_choice(mixes1));
-
-            if remaining_mixes > 1 {
-                let mut wheel2 = wheel.clone();
-                perform_mix_unchecked(&mut wheel2, a, b);
-                for &(c, d) in &VALID_MIX_PAIRS {
-                    if wheel2.get(c) > 0 && wheel2.get(d) > 0 {
-                        let mut mixes2 = SmallVec::new();
-                        mixes2.push((a, b));
-                        mixes2.push((c, d));
-                        choices.push(make_choice(mixes2));
-                    }
+            let mut next = wheel.clone();
+            perform_mix_unchecked(&mut next, a, b);
+            // A wheel seen before has already had all of its extensions pushed.
+            if !seen.insert(next.counts) {
+                continue;
+            }
+            mixes.push((a, b));
+            choices.push(make_choice(mixes.clone()));
+            push_mix_extensions(&next, remaining_mixes - 1, mixes, seen, choices, make_choice);
+            mixes.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::can_mix;
+    use rand::{RngExt, SeedableRng};
+    use wyrand::WyRand;
+
+    /// Every wheel reachable with up to `remaining` mixes, by trying all
+    /// ordered pairs of colors at every step.
+    fn brute_force_wheels(wheel: &ColorWheel, remaining: u32, out: &mut FxHashSet<[u32; NUM_COLORS]>) {
+        out.insert(wheel.counts);
+        if remaining == 0 {
+            return;
+        }
+        for &a in &ALL_COLORS {
+            for &b in &ALL_COLORS {
+                if wheel.get(a) > 0 && wheel.get(b) > 0 && can_mix(a, b) {
+                    let mut next = wheel.clone();
+                    perform_mix_unchecked(&mut next, a, b);
+                    brute_force_wheels(&next, remaining - 1, out);
                 }
             }
         }
     }
+
+    fn apply_mixes(wheel: &ColorWheel, mixes: &[(Color, Color)]) -> [u32; NUM_COLORS] {
+        let mut result = wheel.clone();
+        for &(a, b) in mixes {
+            assert!(result.get(a) > 0 && result.get(b) > 0 && can_mix(a, b));
+            perform_mix_unchecked(&mut result, a, b);
+        }
+        result.counts
+    }
+
+    #[test]
+    fn test_mix_sequences_match_brute_force() {
+        let mut rng = WyRand::seed_from_u64(7);
+        for _ in 0..200 {
+            let mut wheel = ColorWheel::new();
+            for &color in &ALL_COLORS {
+                wheel.set(color, rng.random_range(0..3));
+            }
+            for remaining in 0..=4 {
+                let mut choices = Vec::new();
+                enumerate_mix_sequences(&wheel, remaining, &mut choices, |mixes| Choice::MixAll { mixes });
+
+                let mut expected = FxHashSet::default();
+                brute_force_wheels(&wheel, remaining, &mut expected);
+
+                let mut reached = FxHashSet::default();
+                for choice in &choices {
+                    let Choice::MixAll { mixes } = choice else { unreachable!() };
+                    assert!(mixes.len() <= remaining as usize);
+                    assert!(
+                        reached.insert(apply_mixes(&wheel, mixes)),
+                        "duplicate resulting wheel for {:?}",
+                        mixes
+                    );
+                }
+                assert_eq!(reached, expected, "wheel={:?}, remaining={}", wheel.counts, remaining);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mix_sequences_beyond_two_mixes() {
+        let mut wheel = ColorWheel::new();
+        wheel.set(Color::Red, 2);
+        wheel.set(Color::Yellow, 2);
+        wheel.set(Color::Blue, 2);
+
+        let mut choices = Vec::new();
+        enumerate_mix_sequences(&wheel, 3, &mut choices, |mixes| Choice::MixAll { mixes });
+        let longest = choices
+            .iter()
+            .map(|c| match c {
+                Choice::MixAll { mixes } => mixes.len(),
+                _ => unreachable!(),
+            })
+            .max();
+        assert_eq!(longest, Some(3));
+    }
 }
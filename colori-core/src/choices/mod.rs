@@ -188,6 +188,22 @@ fn check_destroy_preconditions(state: &GameState, card: &Card) -> bool {
     }
 }
 
+/// Whether `mixes` can be performed in order on `wheel` by an ability that
+/// allows `count` mixes.
+fn check_mixes(wheel: &ColorWheel, mixes: &[(Color, Color)], count: u32) -> bool {
+    if mixes.len() > count as usize {
+        return false;
+    }
+    let mut wheel = wheel.clone();
+    for &(a, b) in mixes {
+        if wheel.get(a) == 0 || wheel.get(b) == 0 || !can_mix(a, b) {
+            return false;
+        }
+        perform_mix_unchecked(&mut wheel, a, b);
+    }
+    true
+}
+
 pub fn check_choice_available(state: &GameState, choice: &Choice) -> bool {
     match choice {
         Choice::DraftPick { card } => {
@@ -220,8 +236,8 @@ pub fn check_choice_available(state: &GameState, choice: &Choice) -> bool {
         Choice::Workshop { card_types } => {
             if let GamePhase::Action { ref action_state } = state.phase {
                 match action_state.ability_stack.last() {
-                    Some(Ability::Workshop { .. }) => {
-                        if card_types.is_empty() {
+                    Some(&Ability::Workshop { count }) => {
+                        if card_types.is_empty() || card_types.len() > count as usize {
                             return false;
                         }
                         let player = &state.players[action_state.current_player_index];
@@ -296,15 +312,9 @@ pub fn check_choice_available(state: &GameState, choice: &Choice) -> bool {
         Choice::MixAll { mixes } => {
             if let GamePhase::Action { ref action_state } = state.phase {
                 match action_state.ability_stack.last() {
-                    Some(Ability::MixColors { .. }) => {
-                        if mixes.is_empty() {
-                            return true;
-                        }
+                    Some(&Ability::MixColors { count }) => {
                         let player = &state.players[action_state.current_player_index];
-                        let (a, b) = mixes[0];
-                        player.color_wheel.get(a) > 0
-                            && player.color_wheel.get(b) > 0
-                            && can_mix(a, b)
+                        check_mixes(&player.color_wheel, mixes, count)
                     }
                     _ => false,
                 }
@@ -329,32 +339,20 @@ pub fn check_choice_available(state: &GameState, choice: &Choice) -> bool {
             }
         }
         Choice::DestroyAndMix { card, mixes } => {
+            let Ability::MixColors { count } = card.ability() else {
+                return false;
+            };
             if !check_destroy_preconditions(state, card) {
                 return false;
             }
-            if mixes.is_empty() {
-                return true;
-            }
             let player = &state.players[match &state.phase {
                 GamePhase::Action { action_state } => action_state.current_player_index,
                 _ => return false,
             }];
-            let (a, b) = mixes[0];
-            if player.color_wheel.get(a) == 0 || player.color_wheel.get(b) == 0 || !can_mix(a, b) {
-                return false;
-            }
-            if mixes.len() > 1 {
-                let mut wheel = player.color_wheel.clone();
-                perform_mix_unchecked(&mut wheel, a, b);
-                let (c, d) = mixes[1];
-                if wheel.get(c) == 0 || wheel.get(d) == 0 || !can_mix(c, d) {
-                    return false;
-                }
-            }
-            true
+            check_mixes(&player.color_wheel, mixes, count)
         }
         Choice::DestroyAndSell { card, sell_card } => {
-            if !check_destroy_preconditions(state, card) {
+            if card.ability() != Ability::Sell || !check_destroy_preconditions(state, card) {
                 return false;
             }
             let player = &state.players[match &state.phase {
@@ -364,7 +362,10 @@ pub fn check_choice_available(state: &GameState, choice: &Choice) -> bool {
             state.sell_card_display.iter().any(|b| b.sell_card == *sell_card && can_afford_sell_card(player, &b.sell_card))
         }
         Choice::DestroyAndWorkshop { card, workshop_cards } => {
-            if !check_destroy_preconditions(state, card) {
+            let Ability::Workshop { count } = card.ability() else {
+                return false;
+            };
+            if workshop_cards.len() > count as usize || !check_destroy_preconditions(state, card) {
                 return false;
             }
             if workshop_cards.is_empty() {
@@ -377,7 +378,7 @@ pub fn check_choice_available(state: &GameState, choice: &Choice) -> bool {
             resolve_card_types_to_ids(workshop_cards, &player.workshop_cards, &state.card_lookup).is_some()
         }
         Choice::DestroyAndDestroyCards { card, target } => {
-            if !check_destroy_preconditions(state, card) {
+            if card.ability() != Ability::DestroyCards || !check_destroy_preconditions(state, card) {
                 return false;
             }
             match target {
@@ -6,7 +6,7 @@ use smallvec::SmallVec;
 /// returning a sorted array of (Card, count) pairs and the number of distinct types.
 pub(super) fn count_card_types(
     mask: UnorderedCards,
-    card_lookup: &[Card; 256],
+    card_lookup: &[Card; MAX_CARD_INSTANCES],
 ) -> ([Card; 46], [u8; 46], usize) {
     let mut card_types = [Card::BasicRed; 46];
     let mut type_counts = [0u8; 46];
@@ -21,6 +21,17 @@ pub fn apply_choice_to_state<R: Rng>(state: &mut GameState, choice: &Choice, rng
     }
 }
 
+/// Applies `choice` if `check_choice_available` accepts it and returns whether
+/// it was applied. A rejected choice leaves `state` untouched, so this is safe
+/// to call with choices from untrusted input.
+pub fn try_apply_choice_to_state<R: Rng>(state: &mut GameState, choice: &Choice, rng: &mut R) -> bool {
+    if !check_choice_available(state, choice) {
+        return false;
+    }
+    apply_choice_to_state(state, choice, rng);
+    true
+}
+
 // ── Game status ──
 
 #[derive(Debug)]
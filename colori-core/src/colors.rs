@@ -1,4 +1,6 @@
 use crate::types::{Color, ColorWheel, NUM_COLORS};
+use serde::Serialize;
+use smallvec::SmallVec;
 
 /// Indices of primary colors on the 12-color wheel: Red(0), Yellow(4), Blue(8).
 const PRIMARY_INDICES: [usize; 3] = [0, 4, 8];
@@ -157,6 +159,131 @@ pub fn pay_cost(wheel: &mut ColorWheel, cost: &[Color]) -> bool {
     true
 }
 
+/// The cheapest way to pay a color cost: the mixes to perform, in order,
+/// after gaining `missing_primaries`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MixPlan {
+    pub mixes: Vec<(Color, Color)>,
+    pub missing_primaries: Vec<Color>,
+}
+
+/// How many mixes it takes to make `color` from primaries.
+fn max_recipe_depth(color: Color) -> u8 {
+    if is_primary(color) {
+        0
+    } else if is_tertiary(color) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Adds the colors consumed by making `color` with `depth` mixes.
+fn add_recipe(color: Color, depth: u8, used: &mut [u32; NUM_COLORS]) {
+    match depth {
+        0 => used[color.index()] += 1,
+        1 => {
+            let (a, b) = unmix_components(color);
+            used[a.index()] += 1;
+            used[b.index()] += 1;
+        }
+        _ => {
+            let (primary, secondary) = unmix_components(color);
+            let (a, b) = unmix_components(secondary);
+            used[primary.index()] += 1;
+            used[a.index()] += 1;
+            used[b.index()] += 1;
+        }
+    }
+}
+
+/// Picks, for each cost color, whether to take it from the wheel or mix it,
+/// minimizing missing primaries and then mixes. Every secondary comes from
+/// two primaries and every tertiary from a primary and an adjacent
+/// secondary, so trying each depth per cost color is exhaustive.
+/// Returns (missing primaries, mixes, depth per cost color).
+fn best_recipes(wheel: &ColorWheel, cost: &[Color]) -> (u32, u32, SmallVec<[u8; 4]>) {
+    let mut depths: SmallVec<[u8; 4]> = SmallVec::from_elem(0, cost.len());
+    let mut best: Option<(u32, u32, SmallVec<[u8; 4]>)> = None;
+    loop {
+        let mut used = [0u32; NUM_COLORS];
+        let mut mixes = 0;
+        for (&color, &depth) in cost.iter().zip(depths.iter()) {
+            add_recipe(color, depth, &mut used);
+            mixes += depth as u32;
+        }
+        let mut missing = 0;
+        let mut feasible = true;
+        for (idx, &needed) in used.iter().enumerate() {
+            let have = wheel.counts[idx];
+            if needed > have {
+                if is_primary_idx(idx) {
+                    missing += needed - have;
+                } else {
+                    feasible = false;
+                    break;
+                }
+            }
+        }
+        if feasible && best.as_ref().is_none_or(|(m, x, _)| (missing, mixes) < (*m, *x)) {
+            best = Some((missing, mixes, depths.clone()));
+        }
+
+        // Advance to the next combination of depths.
+        let mut i = 0;
+        while i < cost.len() && depths[i] == max_recipe_depth(cost[i]) {
+            depths[i] = 0;
+            i += 1;
+        }
+        if i == cost.len() {
+            break;
+        }
+        depths[i] += 1;
+    }
+    best.expect("Mixing everything from primaries is always feasible")
+}
+
+/// Minimum (missing primaries, mixes) needed to pay `cost` from `wheel`.
+pub fn mix_distance(wheel: &ColorWheel, cost: &[Color]) -> (u32, u32) {
+    let (missing, mixes, _) = best_recipes(wheel, cost);
+    (missing, mixes)
+}
+
+/// The plan behind `mix_distance`: which primaries to gain and which mixes
+/// to perform, secondaries first, so that `cost` can then be paid.
+pub fn cheapest_mix_plan(wheel: &ColorWheel, cost: &[Color]) -> MixPlan {
+    let (_, _, depths) = best_recipes(wheel, cost);
+    let mut used = [0u32; NUM_COLORS];
+    let mut secondary_mixes = Vec::new();
+    let mut tertiary_mixes = Vec::new();
+    for (&color, &depth) in cost.iter().zip(depths.iter()) {
+        add_recipe(color, depth, &mut used);
+        match depth {
+            0 => {}
+            1 if is_tertiary(color) => tertiary_mixes.push(unmix_components(color)),
+            1 => secondary_mixes.push(unmix_components(color)),
+            _ => {
+                let (primary, secondary) = unmix_components(color);
+                secondary_mixes.push(unmix_components(secondary));
+                tertiary_mixes.push((primary, secondary));
+            }
+        }
+    }
+    let mut missing_primaries = Vec::new();
+    for &primary in &PRIMARIES {
+        let have = wheel.get(primary);
+        for _ in have..used[primary.index()] {
+            missing_primaries.push(primary);
+        }
+    }
+    secondary_mixes.extend(tertiary_mixes);
+    MixPlan {
+        mixes: secondary_mixes,
+        missing_primaries,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,4 +350,48 @@ mod tests {
             }
         }
     }
+
+    fn wheel_with(colors: &[Color]) -> ColorWheel {
+        let mut wheel = ColorWheel::new();
+        for &c in colors {
+            wheel.increment(c);
+        }
+        wheel
+    }
+
+    #[test]
+    fn test_mix_plan_prefers_owned_colors() {
+        let wheel = wheel_with(&[Color::Teal, Color::Blue, Color::Yellow]);
+        assert_eq!(mix_distance(&wheel, &[Color::Teal]), (0, 0));
+        // Yellow + Blue = Green, then Blue + Green = Teal, but Blue is gone.
+        assert_eq!(mix_distance(&wheel, &[Color::Teal, Color::Teal]), (1, 2));
+        let plan = cheapest_mix_plan(&wheel, &[Color::Teal, Color::Teal]);
+        assert_eq!(plan.missing_primaries, vec![Color::Blue]);
+        assert_eq!(plan.mixes, vec![(Color::Yellow, Color::Blue), (Color::Blue, Color::Green)]);
+    }
+
+    #[test]
+    fn test_mix_plan_pays_cost_when_followed() {
+        use crate::types::ALL_COLORS;
+
+        let wheel = wheel_with(&[Color::Red, Color::Red, Color::Yellow, Color::Blue, Color::Orange]);
+        for &a in &ALL_COLORS {
+            for &b in &ALL_COLORS {
+                let cost = [a, b];
+                let plan = cheapest_mix_plan(&wheel, &cost);
+                let mut after = wheel.clone();
+                for &p in &plan.missing_primaries {
+                    after.increment(p);
+                }
+                for &(x, y) in &plan.mixes {
+                    assert!(perform_mix(&mut after, x, y), "{:?}: cannot mix {:?} {:?}", cost, x, y);
+                }
+                assert!(can_pay_cost(&after, &cost), "{:?}: plan {:?} does not pay", cost, plan);
+                assert_eq!(
+                    mix_distance(&wheel, &cost),
+                    (plan.missing_primaries.len() as u32, plan.mixes.len() as u32)
+                );
+            }
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use crate::action_phase::initialize_action_phase;
-use crate::types::{DraftState, GamePhase, GameState, MAX_PLAYERS};
+use crate::types::{CardId, DraftState, GamePhase, GameState, MAX_PLAYERS};
 use crate::unordered_cards::UnorderedCards;
 use rand::Rng;
 
@@ -53,7 +53,7 @@ pub fn initialize_draft<R: Rng>(state: &mut GameState, rng: &mut R) {
 pub fn player_pick<R: Rng>(state: &mut GameState, card_instance_id: u32, rng: &mut R) {
     let num_players = state.players.len();
     let starting_player = ((state.round - 1) as usize) % num_players;
-    let id = card_instance_id as u8;
+    let id = card_instance_id as CardId;
 
     let (pi, should_advance) = match &mut state.phase {
         GamePhase::Draft { draft_state } => {
@@ -110,7 +110,7 @@ pub fn player_pick<R: Rng>(state: &mut GameState, card_instance_id: u32, rng: &m
 }
 
 /// Remove a card from a phantom hand, recording/replaying via draw log.
-fn phantom_draft_removal<R: Rng>(state: &mut GameState, hand_idx: usize, rng: &mut R) -> u8 {
+fn phantom_draft_removal<R: Rng>(state: &mut GameState, hand_idx: usize, rng: &mut R) -> CardId {
     use crate::game_log::{DrawEvent, DrawLog};
     use crate::types::CardInstance;
 
@@ -118,7 +118,7 @@ fn phantom_draft_removal<R: Rng>(state: &mut GameState, hand_idx: usize, rng: &m
     if let Some(DrawLog::Replaying(events)) = &mut state.draw_log {
         if let Some(pos) = events.iter().position(|e| matches!(e, DrawEvent::PhantomDraftRemoval { hand_index, .. } if *hand_index == hand_idx)) {
             if let DrawEvent::PhantomDraftRemoval { card, .. } = events.remove(pos).unwrap() {
-                return card.instance_id as u8;
+                return card.instance_id as CardId;
             }
         }
     }
@@ -231,6 +231,7 @@ mod tests {
         get_sell_card_registry, get_card_registry, set_sell_card_registry, set_card_registry,
     };
     use rand::SeedableRng;
+    use std::sync::Arc;
     use wyrand::WyRand;
 
     fn test_serialize(state: &GameState) -> String {
@@ -241,8 +242,8 @@ mod tests {
 
     fn test_deserialize(json: &str) -> GameState {
         let mut state: GameState = serde_json::from_str(json).unwrap();
-        state.card_lookup = get_card_registry();
-        state.sell_card_lookup = get_sell_card_registry();
+        state.card_lookup = Arc::new(get_card_registry());
+        state.sell_card_lookup = Arc::new(get_sell_card_registry());
         for p in state.players.iter_mut() {
             p.cached_score = calculate_score(p);
         }
@@ -1,5 +1,5 @@
 use crate::game_log::{DrawEvent, DrawLog};
-use crate::types::{CardInstance, GameState};
+use crate::types::{CardId, CardInstance, GameState};
 use crate::unordered_cards::UnorderedCards;
 
 pub fn record_player_deck_draw(state: &mut GameState, player_index: usize, before: UnorderedCards) {
@@ -25,18 +25,27 @@ pub fn record_player_deck_draw(state: &mut GameState, player_index: usize, befor
 /// Moves the specified cards from the player's deck (or discard) to workshop.
 pub fn replay_player_deck_draw(state: &mut GameState, player_index: usize) {
     let event = match &mut state.draw_log {
-        Some(DrawLog::Replaying(queue)) => queue.pop_front(),
+        Some(DrawLog::Replaying(queue)) => {
+            // Empty draws are not recorded, so the next event may belong to
+            // another player.
+            if !matches!(queue.front(), Some(DrawEvent::PlayerDeckDraw { player_index: p, .. }) if *p == player_index) {
+                return;
+            }
+            queue.pop_front()
+        }
         _ => return,
     };
     if let Some(DrawEvent::PlayerDeckDraw { cards, .. }) = event {
         let player = &mut state.players[player_index];
+        // A draw that reaches into the discard emptied the deck first, and
+        // `draw_from_deck` then shuffled the whole discard into the deck.
+        if cards.iter().any(|card| player.discard.contains(card.instance_id as CardId)) {
+            player.deck = player.deck.union(player.discard);
+            player.discard = UnorderedCards::new();
+        }
         for card in &cards {
-            let id = card.instance_id as u8;
-            if player.deck.contains(id) {
-                player.deck.remove(id);
-            } else if player.discard.contains(id) {
-                player.discard.remove(id);
-            }
+            let id = card.instance_id as CardId;
+            player.deck.remove(id);
             player.workshop_cards.insert(id);
         }
     }
@@ -56,7 +65,7 @@ pub fn replay_sell_card_reveal(state: &mut GameState) -> bool {
         _ => return false,
     };
     if let Some(DrawEvent::SellCardReveal { sell_card }) = event {
-        let id = sell_card.instance_id as u8;
+        let id = sell_card.instance_id as CardId;
         state.sell_card_deck.remove(id);
         state.sell_card_display.push(sell_card);
         return true;
@@ -2,7 +2,7 @@ use crate::deck_utils::draw_from_deck;
 use crate::draw_log_helpers::{is_replaying, record_player_deck_draw, replay_player_deck_draw};
 use crate::draft_phase::initialize_draft;
 use crate::game_log::{DrawEvent, DrawLog};
-use crate::types::{CardInstance, DraftState, GamePhase, GameState, MAX_PLAYERS};
+use crate::types::{CardId, CardInstance, DraftState, GamePhase, GameState, MAX_PLAYERS};
 use crate::unordered_cards::UnorderedCards;
 use rand::Rng;
 
@@ -68,8 +68,14 @@ fn replay_draft_deals(state: &mut GameState) {
             _ => break,
         };
         if let Some(DrawEvent::DraftDeal { player_index, cards }) = event {
+            // Like `initialize_draft`, refill the emptied draft deck from the
+            // destroyed pile once a deal reaches into it.
+            if cards.iter().any(|card| state.destroyed_pile.contains(card.instance_id as CardId)) {
+                state.draft_deck = state.draft_deck.union(state.destroyed_pile);
+                state.destroyed_pile = UnorderedCards::new();
+            }
             for card in &cards {
-                let id = card.instance_id as u8;
+                let id = card.instance_id as CardId;
                 state.draft_deck.remove(id);
                 hands[player_index].insert(id);
             }
@@ -59,6 +59,48 @@ pub struct StructuredGameLog {
     pub player_variants: Option<Vec<PlayerVariant>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    /// Seed of every chance outcome of the game, for logs written by
+    /// `colori-runner`.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_seed",
+        deserialize_with = "deserialize_optional_seed"
+    )]
+    pub deal_seed: Option<u64>,
+}
+
+/// Writes a seed as a decimal string, since JavaScript would round a `u64`
+/// above 2^53 when parsing the log.
+pub fn serialize_seed<S: serde::Serializer>(seed: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(seed)
+}
+
+fn serialize_optional_seed<S: serde::Serializer>(
+    seed: &Option<u64>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match seed {
+        Some(seed) => serialize_seed(seed, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Reads a seed written as a string, or as a plain number by older logs.
+fn deserialize_optional_seed<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Seed {
+        Number(u64),
+        Text(String),
+    }
+    match Option::<Seed>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Seed::Number(seed)) => Ok(Some(seed)),
+        Some(Seed::Text(text)) => text.parse().map(Some).map_err(serde::de::Error::custom),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
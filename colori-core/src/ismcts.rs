@@ -1,10 +1,9 @@
-use crate::colori_game::{
-    apply_choice_to_state, apply_rollout_step, apply_heuristic_rollout_step,
-    determinize_in_place, enumerate_choices_into,
-};
+use crate::colori_game::{apply_choice_to_state, determinize_in_place, enumerate_choices_into};
 use crate::draft_phase::player_pick;
+use crate::rollout_policy::{GreedyRollout, HeuristicRollout, RandomRollout, RolloutPolicyKind};
 use crate::scoring::{calculate_score, CardHeuristicTable, compute_heuristic_rewards, compute_terminal_rewards, heuristic_score, HeuristicParams};
 use crate::types::*;
+use crate::unordered_cards::UnorderedCards;
 use rand::Rng;
 use rand::RngExt;
 use serde::Deserialize;
@@ -20,18 +19,36 @@ pub struct MctsConfig {
     pub progressive_bias_weight: f64,
     pub heuristic_params: HeuristicParams,
     pub no_rollout: bool,
-    pub heuristic_rollout: bool,
-    pub heuristic_draft: bool,
+    pub rollout_policy: RolloutPolicyKind,
     pub early_termination: bool,
     pub time_limit_ms: Option<u64>,
     pub random_first_pick: bool,
     pub force_max_workshop: bool,
+    /// Blend all-moves-as-first (AMAF) statistics into child selection.
+    pub rave: bool,
+    /// RAVE equivalence parameter k. The AMAF weight for a child with n visits
+    /// is beta = sqrt(k / (3n + k)), so larger values trust AMAF for longer.
+    pub rave_equivalence: f64,
+    /// Multiple-observer ISMCTS: keep one tree per player so opponents select
+    /// moves from their own information sets instead of the perspective
+    /// player's tree and the decoupled draft bandit.
+    pub multi_observer: bool,
+    /// Stop adding nodes to a tree once it holds this many. Simulations that
+    /// reach the edge of a full tree roll out from there instead. The root's
+    /// children are always added.
+    pub max_nodes: Option<usize>,
+    /// How many rounds past the current one rollouts may play, though never
+    /// stopping before round 8. See `max_rollout_round`.
+    pub rollout_round_horizon: u32,
 }
 
 pub struct MctsResult {
     pub choice: Choice,
     pub iterations_used: u32,
-    pub tree: Option<MctsNode>,
+    pub tree: Option<MctsTree>,
+    /// Each opponent's tree, indexed by player, when searching with
+    /// `multi_observer`. The perspective player's slot holds an empty tree.
+    pub opponent_trees: Vec<MctsTree>,
 }
 
 pub struct TreeStats {
@@ -50,14 +67,24 @@ impl MctsConfig {
             progressive_bias_weight: 0.0,
             heuristic_params,
             no_rollout: false,
-            heuristic_rollout: true,
-            heuristic_draft: false,
+            rollout_policy: RolloutPolicyKind::default(),
             early_termination: true,
             time_limit_ms: None,
             random_first_pick: false,
             force_max_workshop: true,
+            rave: false,
+            rave_equivalence: 250.0,
+            multi_observer: false,
+            max_nodes: None,
+            rollout_round_horizon: 2,
         }
     }
+
+    /// The round after which rollouts from a state in `round` stop, for
+    /// the `max_rollout_round` argument of `ismcts`.
+    pub fn max_rollout_round(&self, round: u32) -> u32 {
+        std::cmp::max(8, round + self.rollout_round_horizon)
+    }
 }
 
 impl<'de> Deserialize<'de> for MctsConfig {
@@ -79,16 +106,28 @@ impl<'de> Deserialize<'de> for MctsConfig {
             #[serde(default = "default_progressive_bias_weight")]
             progressive_bias_weight: f64,
             heuristic_params: HeuristicParams,
-            #[serde(default = "default_heuristic_rollout")]
-            heuristic_rollout: bool,
             #[serde(default)]
-            heuristic_draft: bool,
+            heuristic_rollout: Option<bool>,
+            #[serde(default)]
+            heuristic_draft: Option<bool>,
+            #[serde(default)]
+            rollout_policy: Option<RolloutPolicyKind>,
             #[serde(default)]
             early_termination: bool,
             #[serde(default)]
             time_limit_ms: Option<u64>,
             #[serde(default)]
             random_first_pick: bool,
+            #[serde(default)]
+            rave: bool,
+            #[serde(default = "default_rave_equivalence")]
+            rave_equivalence: f64,
+            #[serde(default)]
+            multi_observer: bool,
+            #[serde(default)]
+            max_nodes: Option<usize>,
+            #[serde(default = "default_rollout_round_horizon")]
+            rollout_round_horizon: u32,
         }
 
         fn default_iterations() -> u32 { 100 }
@@ -96,9 +135,12 @@ impl<'de> Deserialize<'de> for MctsConfig {
         fn default_max_rollout_steps() -> u32 { 1000 }
         fn default_use_heuristic_eval() -> bool { true }
         fn default_progressive_bias_weight() -> f64 { 0.0 }
-        fn default_heuristic_rollout() -> bool { true }
+        fn default_rave_equivalence() -> f64 { 250.0 }
+        fn default_rollout_round_horizon() -> u32 { 2 }
 
         let helper = MctsConfigHelper::deserialize(deserializer)?;
+        let rollout_policy = RolloutPolicyKind::resolve(helper.rollout_policy, helper.heuristic_rollout, helper.heuristic_draft)
+            .map_err(serde::de::Error::custom)?;
         Ok(MctsConfig {
             iterations: helper.iterations,
             exploration_constant: helper.exploration_constant,
@@ -107,17 +149,27 @@ impl<'de> Deserialize<'de> for MctsConfig {
             progressive_bias_weight: helper.progressive_bias_weight,
             heuristic_params: helper.heuristic_params,
             no_rollout: false,
-            heuristic_rollout: helper.heuristic_rollout,
-            heuristic_draft: helper.heuristic_draft,
+            rollout_policy,
             early_termination: helper.early_termination,
             time_limit_ms: helper.time_limit_ms,
             random_first_pick: helper.random_first_pick,
             force_max_workshop: true,
+            rave: helper.rave,
+            rave_equivalence: helper.rave_equivalence,
+            multi_observer: helper.multi_observer,
+            max_nodes: helper.max_nodes,
+            rollout_round_horizon: helper.rollout_round_horizon,
         })
     }
 }
 
-pub struct MctsNode {
+/// Index of a node within its `MctsTree`.
+type NodeId = u32;
+
+const ROOT: NodeId = 0;
+const NO_NODE: NodeId = NodeId::MAX;
+
+struct NodeData {
     visit_count: u32,
     cumulative_reward: f64,
     player_index: usize,
@@ -125,12 +177,21 @@ pub struct MctsNode {
     availability_count: u32,
     ln_availability: f64,
     heuristic_bias: f64,
-    children: Vec<MctsNode>,
+    /// Stands for a move the tree's owner could not see (an opponent's
+    /// face-down draft pick) in multiple-observer search.
+    hidden: bool,
+    amaf_visit_count: u32,
+    amaf_cumulative_reward: f64,
+    // Children form a singly linked list in insertion order.
+    first_child: NodeId,
+    last_child: NodeId,
+    next_sibling: NodeId,
+    child_count: u32,
 }
 
-impl MctsNode {
+impl NodeData {
     fn new(player_index: usize, choice: Option<Choice>) -> Self {
-        MctsNode {
+        NodeData {
             visit_count: 0,
             cumulative_reward: 0.0,
             player_index,
@@ -138,94 +199,261 @@ impl MctsNode {
             availability_count: 0,
             ln_availability: 0.0,
             heuristic_bias: 0.0,
-            children: Vec::new(),
+            hidden: false,
+            amaf_visit_count: 0,
+            amaf_cumulative_reward: 0.0,
+            first_child: NO_NODE,
+            last_child: NO_NODE,
+            next_sibling: NO_NODE,
+            child_count: 0,
+        }
+    }
+}
+
+/// A search tree. Nodes live in a single arena so expansion does not
+/// allocate per node, and the root is always the first node.
+pub struct MctsTree {
+    nodes: Vec<NodeData>,
+}
+
+impl MctsTree {
+    fn new(player_index: usize) -> Self {
+        MctsTree { nodes: vec![NodeData::new(player_index, None)] }
+    }
+
+    pub fn root(&self) -> MctsNode<'_> {
+        MctsNode { tree: self, id: ROOT }
+    }
+
+    /// Number of nodes in the tree, including the root.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn is_full(&self, max_nodes: Option<usize>) -> bool {
+        max_nodes.is_some_and(|max| self.nodes.len() >= max)
+    }
+
+    #[inline]
+    fn node(&self, id: NodeId) -> &NodeData {
+        &self.nodes[id as usize]
+    }
+
+    #[inline]
+    fn node_mut(&mut self, id: NodeId) -> &mut NodeData {
+        &mut self.nodes[id as usize]
+    }
+
+    fn child_ids(&self, id: NodeId) -> ChildIds<'_> {
+        let node = self.node(id);
+        ChildIds { tree: self, next: node.first_child, remaining: node.child_count as usize }
+    }
+
+    fn add_child(&mut self, parent: NodeId, data: NodeData) -> NodeId {
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(data);
+        let parent_node = self.node_mut(parent);
+        let last = parent_node.last_child;
+        parent_node.last_child = id;
+        parent_node.child_count += 1;
+        if last == NO_NODE {
+            parent_node.first_child = id;
+        } else {
+            self.node_mut(last).next_sibling = id;
         }
+        id
     }
 
-    fn is_root(&self) -> bool {
-        self.choice.is_none()
+    /// Returns the child reached when the owner of this tree observes
+    /// `actor` making `choice`, creating it if needed. None when the child
+    /// is missing and the tree is full.
+    fn observe(&mut self, id: NodeId, choice: &Choice, actor: usize, hidden: bool, max_nodes: Option<usize>) -> Option<NodeId> {
+        let existing = self.child_ids(id).find(|&c| {
+            let child = self.node(c);
+            if hidden { child.hidden } else { child.choice.as_ref() == Some(choice) }
+        });
+        if existing.is_some() {
+            return existing;
+        }
+        if self.is_full(max_nodes) {
+            return None;
+        }
+        let mut node = if hidden {
+            NodeData::new(actor, None)
+        } else {
+            NodeData::new(actor, Some(choice.clone()))
+        };
+        node.hidden = hidden;
+        Some(self.add_child(id, node))
+    }
+
+    /// The subtree below the root's child for `choice`, as a tree of its own.
+    /// Nodes outside that subtree are dropped.
+    pub fn into_subtree(mut self, choice: &Choice) -> Option<MctsTree> {
+        let start = self.child_ids(ROOT).find(|&c| self.node(c).choice.as_ref() == Some(choice))?;
+        let mut subtree = MctsTree { nodes: Vec::new() };
+        subtree.nodes.push(self.detach(start));
+        subtree.nodes[0].choice = None;
+        // Breadth-first copy, which keeps every child list in its original order.
+        let mut queue: Vec<(NodeId, NodeId)> = vec![(start, ROOT)];
+        let mut head = 0;
+        while head < queue.len() {
+            let (old_id, new_id) = queue[head];
+            head += 1;
+            let mut child = self.node(old_id).first_child;
+            while child != NO_NODE {
+                let next = self.node(child).next_sibling;
+                let data = self.detach(child);
+                let new_child = subtree.add_child(new_id, data);
+                queue.push((child, new_child));
+                child = next;
+            }
+        }
+        Some(subtree)
     }
 
-    pub fn into_subtree(mut self, choice: &Choice) -> Option<MctsNode> {
-        let idx = self.children.iter().position(|c| c.choice.as_ref() == Some(choice))?;
-        let mut child = self.children.swap_remove(idx);
-        child.choice = None;
-        Some(child)
+    /// Moves a node's statistics out of the arena, leaving its links intact.
+    fn detach(&mut self, id: NodeId) -> NodeData {
+        let node = self.node_mut(id);
+        NodeData {
+            choice: node.choice.take(),
+            first_child: NO_NODE,
+            last_child: NO_NODE,
+            next_sibling: NO_NODE,
+            child_count: 0,
+            ..*node
+        }
     }
 
     fn expand<R: Rng>(
         &mut self,
+        id: NodeId,
         choices: &[Choice],
         active_player: usize,
         available: &mut Vec<bool>,
+        max_nodes: Option<usize>,
         rng: &mut R,
     ) {
         available.clear();
-        available.resize(self.children.len(), false);
+        available.resize(self.node(id).child_count as usize, false);
 
         // Match choices against existing children, collect unseen indices
         let mut unseen_indices: SmallVec<[usize; 16]> = SmallVec::new();
         for (i, choice) in choices.iter().enumerate() {
-            if let Some(idx) = self
-                .children
-                .iter()
-                .position(|c| c.choice.as_ref() == Some(choice))
-            {
-                if !available[idx] {
-                    self.children[idx].availability_count += 1;
-                    self.children[idx].ln_availability = (self.children[idx].availability_count as f64).ln();
-                    available[idx] = true;
+            let mut found = None;
+            let mut child = self.node(id).first_child;
+            let mut pos = 0;
+            while child != NO_NODE {
+                if self.node(child).choice.as_ref() == Some(choice) {
+                    found = Some((pos, child));
+                    break;
+                }
+                child = self.node(child).next_sibling;
+                pos += 1;
+            }
+            if let Some((pos, child)) = found {
+                if !available[pos] {
+                    let node = self.node_mut(child);
+                    node.availability_count += 1;
+                    node.ln_availability = (node.availability_count as f64).ln();
+                    available[pos] = true;
                 }
             } else {
                 unseen_indices.push(i);
             }
         }
 
-        // Add new nodes: root adds all unseen, non-root adds one at random
-        if self.is_root() {
+        // Add new nodes: root adds all unseen (even when full, so the search
+        // can always pick a move), non-root adds one at random
+        if id == ROOT {
             for &i in &unseen_indices {
-                let mut new_node = MctsNode::new(active_player, Some(choices[i].clone()));
+                let mut new_node = NodeData::new(active_player, Some(choices[i].clone()));
                 new_node.availability_count = 1;
                 available.push(true);
-                self.children.push(new_node);
+                self.add_child(id, new_node);
             }
-        } else if !unseen_indices.is_empty() {
+        } else if !unseen_indices.is_empty() && !self.is_full(max_nodes) {
             let pick = rng.random_range(0..unseen_indices.len());
             let i = unseen_indices[pick];
-            let mut new_node = MctsNode::new(active_player, Some(choices[i].clone()));
+            let mut new_node = NodeData::new(active_player, Some(choices[i].clone()));
             new_node.availability_count = 1;
             available.push(true);
-            self.children.push(new_node);
+            self.add_child(id, new_node);
+        }
+    }
+}
+
+struct ChildIds<'a> {
+    tree: &'a MctsTree,
+    next: NodeId,
+    remaining: usize,
+}
+
+impl Iterator for ChildIds<'_> {
+    type Item = NodeId;
+
+    #[inline]
+    fn next(&mut self) -> Option<NodeId> {
+        if self.next == NO_NODE {
+            return None;
         }
+        let id = self.next;
+        self.next = self.tree.node(id).next_sibling;
+        self.remaining -= 1;
+        Some(id)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for ChildIds<'_> {}
+
+/// A read-only view of one node of an `MctsTree`.
+#[derive(Clone, Copy)]
+pub struct MctsNode<'a> {
+    tree: &'a MctsTree,
+    id: NodeId,
+}
+
+impl<'a> MctsNode<'a> {
+    fn data(&self) -> &'a NodeData {
+        self.tree.node(self.id)
     }
 
     pub fn visit_count(&self) -> u32 {
-        self.visit_count
+        self.data().visit_count
     }
 
     pub fn average_reward(&self) -> f64 {
-        if self.visit_count == 0 {
+        let data = self.data();
+        if data.visit_count == 0 {
             0.0
         } else {
-            self.cumulative_reward / self.visit_count as f64
+            data.cumulative_reward / data.visit_count as f64
         }
     }
 
-    pub fn choice(&self) -> Option<&Choice> {
-        self.choice.as_ref()
+    pub fn choice(&self) -> Option<&'a Choice> {
+        self.data().choice.as_ref()
+    }
+
+    /// The node's children, in the order they were added.
+    pub fn children(&self) -> impl ExactSizeIterator<Item = MctsNode<'a>> + 'a {
+        let tree = self.tree;
+        tree.child_ids(self.id).map(move |id| MctsNode { tree, id })
     }
 
-    pub fn children(&self) -> &[MctsNode] {
-        &self.children
+    /// True for a node standing in for a move its tree's owner could not see.
+    pub fn is_hidden(&self) -> bool {
+        self.data().hidden
     }
 
     /// Maximum depth from this node to any leaf.
     pub fn max_depth(&self) -> usize {
-        if self.children.is_empty() {
-            0
-        } else {
-            1 + self.children.iter().map(|c| c.max_depth()).max().unwrap_or(0)
-        }
+        self.children().map(|c| 1 + c.max_depth()).max().unwrap_or(0)
     }
 
     /// Compute aggregate tree statistics.
@@ -248,8 +476,8 @@ impl MctsNode {
         if depth > acc.max_depth {
             acc.max_depth = depth;
         }
-        let visited_children: Vec<&MctsNode> = self.children.iter()
-            .filter(|c| c.visit_count > 0)
+        let visited_children: Vec<MctsNode> = self.children()
+            .filter(|c| c.visit_count() > 0)
             .collect();
         if !visited_children.is_empty() {
             acc.internal_nodes += 1;
@@ -424,18 +652,18 @@ pub fn ismcts<R: Rng>(
     player_index: usize,
     config: &MctsConfig,
     max_rollout_round: Option<u32>,
-    previous_tree: Option<MctsNode>,
+    previous_tree: Option<MctsTree>,
     rng: &mut R,
 ) -> MctsResult {
     // If there's only one legal choice, return it immediately without searching
-    let mut choices_buf: Vec<Choice> = Vec::new();
-    enumerate_choices_into(state, &mut choices_buf);
-    if choices_buf.len() == 1 {
-        return MctsResult { choice: choices_buf.swap_remove(0), iterations_used: 0, tree: None };
+    let mut buffers = SearchBuffers::default();
+    enumerate_choices_into(state, &mut buffers.choices);
+    if buffers.choices.len() == 1 {
+        return MctsResult { choice: buffers.choices.swap_remove(0), iterations_used: 0, tree: None, opponent_trees: Vec::new() };
     }
 
-    let mut root = previous_tree.unwrap_or_else(|| MctsNode::new(player_index, None));
-    let reused_iterations = root.visit_count;
+    let mut root = previous_tree.unwrap_or_else(|| MctsTree::new(player_index));
+    let reused_iterations = root.node(ROOT).visit_count;
     let mut det_state = state.clone();
     det_state.force_max_workshop = config.force_max_workshop;
 
@@ -444,11 +672,14 @@ pub fn ismcts<R: Rng>(
         cached_scores[i] = calculate_score(p);
     }
 
-    let mut availability_buf: Vec<bool> = Vec::new();
     let card_table = CardHeuristicTable::new(&config.heuristic_params);
 
     let mut opponent_stats = OpponentDraftStats::new();
-    let mut pick_log: Vec<(u32, usize, Card)> = Vec::new();
+    let mut opponent_trees: Vec<MctsTree> = if config.multi_observer {
+        (0..state.players.len()).map(MctsTree::new).collect()
+    } else {
+        Vec::new()
+    };
 
     let (effective_max_rollout_round, use_heuristic) = if config.use_heuristic_eval {
         let should_use = state.round <= config.heuristic_params.heuristic_round_threshold;
@@ -464,24 +695,36 @@ pub fn ismcts<R: Rng>(
         (max_rollout_round, false)
     };
 
+    let search = SearchSettings {
+        perspective_player: player_index,
+        max_rollout_round: effective_max_rollout_round,
+        use_heuristic,
+        config,
+        card_table: &card_table,
+    };
+
     let mut iterations_used = 0u32;
     if let Some(time_limit_ms) = config.time_limit_ms {
         let deadline = Instant::now() + Duration::from_millis(time_limit_ms);
         while Instant::now() < deadline {
             iterations_used += 1;
-            pick_log.clear();
+            buffers.pick_log.clear();
+            buffers.amaf_log.clear();
             determinize_in_place(&mut det_state, state, player_index, &cached_scores, rng);
             det_state.force_max_workshop = config.force_max_workshop;
-            advance_past_opponent_draft_picks(
-                &mut det_state, player_index, &mut opponent_stats,
-                &mut pick_log, config.exploration_constant, rng,
-            );
-            let scores = iteration_simultaneous(
-                &mut root, &mut det_state, player_index,
-                &mut opponent_stats, &mut pick_log,
-                effective_max_rollout_round, use_heuristic, config, &mut choices_buf, &mut availability_buf, &card_table, rng,
-            );
-            for &(pick_round, player, card) in &pick_log {
+            let scores = if config.multi_observer {
+                iteration_multi_observer(
+                    &mut observer_trees(&mut root, &mut opponent_trees, player_index), &[ROOT; MAX_PLAYERS],
+                    &mut det_state, &search, &mut buffers, rng,
+                )
+            } else {
+                advance_past_opponent_draft_picks(
+                    &mut det_state, player_index, &mut opponent_stats,
+                    &mut buffers.pick_log, config.exploration_constant, rng,
+                );
+                iteration_simultaneous(&mut root, ROOT, &mut det_state, &mut opponent_stats, &search, &mut buffers, rng)
+            };
+            for &(pick_round, player, card) in &buffers.pick_log {
                 let reward = scores[player];
                 opponent_stats.record_outcome(pick_round as usize, player, card, reward);
             }
@@ -490,19 +733,23 @@ pub fn ismcts<R: Rng>(
         let new_iterations = config.iterations.saturating_sub(reused_iterations);
         for i in 0..new_iterations {
             iterations_used = i + 1;
-            pick_log.clear();
+            buffers.pick_log.clear();
+            buffers.amaf_log.clear();
             determinize_in_place(&mut det_state, state, player_index, &cached_scores, rng);
             det_state.force_max_workshop = config.force_max_workshop;
-            advance_past_opponent_draft_picks(
-                &mut det_state, player_index, &mut opponent_stats,
-                &mut pick_log, config.exploration_constant, rng,
-            );
-            let scores = iteration_simultaneous(
-                &mut root, &mut det_state, player_index,
-                &mut opponent_stats, &mut pick_log,
-                effective_max_rollout_round, use_heuristic, config, &mut choices_buf, &mut availability_buf, &card_table, rng,
-            );
-            for &(pick_round, player, card) in &pick_log {
+            let scores = if config.multi_observer {
+                iteration_multi_observer(
+                    &mut observer_trees(&mut root, &mut opponent_trees, player_index), &[ROOT; MAX_PLAYERS],
+                    &mut det_state, &search, &mut buffers, rng,
+                )
+            } else {
+                advance_past_opponent_draft_picks(
+                    &mut det_state, player_index, &mut opponent_stats,
+                    &mut buffers.pick_log, config.exploration_constant, rng,
+                );
+                iteration_simultaneous(&mut root, ROOT, &mut det_state, &mut opponent_stats, &search, &mut buffers, rng)
+            };
+            for &(pick_round, player, card) in &buffers.pick_log {
                 let reward = scores[player];
                 opponent_stats.record_outcome(pick_round as usize, player, card, reward);
             }
@@ -510,9 +757,9 @@ pub fn ismcts<R: Rng>(
             // Early termination: stop if the leader can't be overtaken
             if config.early_termination {
                 let remaining = new_iterations - iterations_used;
-                if remaining > 0 && root.children.len() >= 2 {
+                if remaining > 0 && root.node(ROOT).child_count >= 2 {
                     // Cheap check: gap exceeds remaining iterations
-                    let (best, second) = top_two_visit_counts(&root.children);
+                    let (best, second) = top_two_visit_counts(&root);
                     if best - second > remaining {
                         break;
                     }
@@ -521,12 +768,13 @@ pub fn ismcts<R: Rng>(
                     // can any challenger overtake the leader by visit count?
                     // Only run when the leader has a meaningful lead (gap > remaining/4)
                     // to avoid wasting time on simulations that won't pass.
+                    // The simulation models plain UCB, so it is skipped under RAVE.
                     let gap = best - second;
-                    if iterations_used % 1024 == 0
+                    if !config.rave
+                        && iterations_used % 1024 == 0
                         && gap > remaining / 4
                         && !can_challenger_overtake(
-                            &root.children,
-                            root.visit_count,
+                            &root,
                             remaining,
                             config.exploration_constant,
                             config.progressive_bias_weight,
@@ -539,28 +787,29 @@ pub fn ismcts<R: Rng>(
         }
     }
 
-    if root.children.is_empty() {
-        enumerate_choices_into(state, &mut choices_buf);
-        let idx = rng.random_range(0..choices_buf.len());
-        return MctsResult { choice: choices_buf[idx].clone(), iterations_used, tree: None };
+    if root.node(ROOT).child_count == 0 {
+        enumerate_choices_into(state, &mut buffers.choices);
+        let idx = rng.random_range(0..buffers.choices.len());
+        return MctsResult { choice: buffers.choices[idx].clone(), iterations_used, tree: None, opponent_trees };
     }
 
-    let best_choice = root.children.iter()
-        .max_by_key(|c| c.visit_count)
+    let best_choice = root.root().children()
+        .max_by_key(|c| c.visit_count())
         .unwrap()
-        .choice.clone().unwrap();
+        .choice().unwrap().clone();
 
     MctsResult {
         choice: best_choice,
         iterations_used,
         tree: Some(root),
+        opponent_trees,
     }
 }
 
-fn top_two_visit_counts(children: &[MctsNode]) -> (u32, u32) {
+fn top_two_visit_counts(tree: &MctsTree) -> (u32, u32) {
     let mut best = 0u32;
     let mut second = 0u32;
-    for child in children {
+    for child in tree.child_ids(ROOT).map(|id| tree.node(id)) {
         if child.visit_count > best {
             second = best;
             best = child.visit_count;
@@ -575,12 +824,13 @@ fn top_two_visit_counts(children: &[MctsNode]) -> (u32, u32) {
 /// Assumes all non-leader children receive reward 1.0 and the leader receives reward 0.0.
 /// Returns true if any challenger could possibly end up with >= the leader's visit count.
 fn can_challenger_overtake(
-    children: &[MctsNode],
-    root_visit_count: u32,
+    tree: &MctsTree,
     remaining: u32,
     exploration_constant: f64,
     progressive_bias_weight: f64,
 ) -> bool {
+    let children: SmallVec<[&NodeData; 16]> = tree.child_ids(ROOT).map(|id| tree.node(id)).collect();
+    let root_visit_count = tree.node(ROOT).visit_count;
     let k = children.len();
     if k < 2 || remaining == 0 {
         return false;
@@ -657,26 +907,22 @@ fn eval_scores(
 }
 
 fn iteration_simultaneous<R: Rng>(
-    node: &mut MctsNode,
+    tree: &mut MctsTree,
+    node: NodeId,
     state: &mut GameState,
-    perspective_player: usize,
     opponent_stats: &mut OpponentDraftStats,
-    pick_log: &mut Vec<(u32, usize, Card)>,
-    max_rollout_round: Option<u32>,
-    use_heuristic: bool,
-    config: &MctsConfig,
-    choices_buf: &mut Vec<Choice>,
-    availability_buf: &mut Vec<bool>,
-    card_table: &CardHeuristicTable,
+    search: &SearchSettings,
+    buffers: &mut SearchBuffers,
     rng: &mut R,
 ) -> [f64; MAX_PLAYERS] {
+    let SearchSettings { perspective_player, max_rollout_round, use_heuristic, config, card_table } = *search;
     let active_player = if matches!(state.phase, GamePhase::GameOver) {
         let scores = compute_terminal_rewards(&state.players);
-        record_outcome(node, &scores);
+        record_outcome(tree.node_mut(node), &scores);
         return scores;
     } else if max_rollout_round.is_some_and(|mr| state.round > mr) {
         let scores = eval_scores(state, use_heuristic, &config.heuristic_params, card_table);
-        record_outcome(node, &scores);
+        record_outcome(tree.node_mut(node), &scores);
         return scores;
     } else {
         match &state.phase {
@@ -688,37 +934,51 @@ fn iteration_simultaneous<R: Rng>(
     };
 
     // Enumerate choices (needed for both expand and select)
-    enumerate_choices_into(state, choices_buf);
+    enumerate_choices_into(state, &mut buffers.choices);
 
-    // Expand (also populates availability_buf)
-    node.expand(choices_buf, active_player, availability_buf, rng);
+    // Expand (also populates the availability buffer)
+    tree.expand(node, &buffers.choices, active_player, &mut buffers.availability, config.max_nodes, rng);
 
     // Select
-    let best_idx =
-        match select(node, availability_buf, config.exploration_constant, config.progressive_bias_weight)
-        {
-            Some(idx) => idx,
-            None => {
-                let empty_scores = [0.0; MAX_PLAYERS];
-                record_outcome(node, &empty_scores);
-                return empty_scores;
-            }
-        };
+    let rave_equivalence = if config.rave { Some(config.rave_equivalence) } else { None };
+    let best = match select(tree, node, &buffers.availability, config.exploration_constant, config.progressive_bias_weight, rave_equivalence) {
+        Some(id) => id,
+        None => {
+            // Nothing to descend into: either the tree is full or no move is legal.
+            let scores = if tree.is_full(config.max_nodes) {
+                rollout(state, search, None, rng)
+            } else {
+                [0.0; MAX_PLAYERS]
+            };
+            record_outcome(tree.node_mut(node), &scores);
+            return scores;
+        }
+    };
 
     // Apply selected child's choice
-    let choice = node.children[best_idx].choice.as_ref().unwrap();
+    let choice = tree.node(best).choice.as_ref().unwrap();
+    let amaf_start = buffers.amaf_log.len();
+    if config.rave {
+        buffers.amaf_log.push((active_player, choice.clone()));
+    }
     apply_choice_to_state(state, choice, rng);
 
     // After applying the perspective player's draft pick, advance past opponents
+    let picks_before = buffers.pick_log.len();
     advance_past_opponent_draft_picks(
         state, perspective_player, opponent_stats,
-        pick_log, config.exploration_constant, rng,
+        &mut buffers.pick_log, config.exploration_constant, rng,
     );
+    if config.rave {
+        for &(_, player, card) in &buffers.pick_log[picks_before..] {
+            buffers.amaf_log.push((player, Choice::DraftPick { card }));
+        }
+    }
 
-    let should_rollout = node.children[best_idx].visit_count == 0;
+    let should_rollout = tree.node(best).visit_count == 0;
 
     if should_rollout && config.progressive_bias_weight != 0.0 {
-        node.children[best_idx].heuristic_bias = heuristic_score(
+        tree.node_mut(best).heuristic_bias = heuristic_score(
             &state.players[perspective_player],
             &state.sell_card_display,
             &state.card_lookup,
@@ -731,45 +991,204 @@ fn iteration_simultaneous<R: Rng>(
         let scores = if config.no_rollout {
             eval_scores(state, true, &config.heuristic_params, card_table)
         } else {
-            rollout(state, max_rollout_round, config.max_rollout_steps, use_heuristic, config.heuristic_rollout, config.heuristic_draft, &config.heuristic_params, card_table, rng)
+            let amaf_log = if config.rave { Some(&mut buffers.amaf_log) } else { None };
+            rollout(state, search, amaf_log, rng)
         };
-        record_outcome(&mut node.children[best_idx], &scores);
+        record_outcome(tree.node_mut(best), &scores);
         scores
     } else {
-        let child = &mut node.children[best_idx];
-        iteration_simultaneous(
-            child, state, perspective_player,
-            opponent_stats, pick_log,
-            max_rollout_round, use_heuristic, config, choices_buf, availability_buf, card_table, rng,
-        )
+        iteration_simultaneous(tree, best, state, opponent_stats, search, buffers, rng)
     };
 
-    record_outcome(node, &scores);
+    if config.rave {
+        record_amaf_outcome(tree, node, &buffers.amaf_log[amaf_start..], &scores);
+    }
+    record_outcome(tree.node_mut(node), &scores);
+    scores
+}
+
+/// Settings that stay fixed for every iteration of one search.
+#[derive(Clone, Copy)]
+struct SearchSettings<'a> {
+    perspective_player: usize,
+    max_rollout_round: Option<u32>,
+    use_heuristic: bool,
+    config: &'a MctsConfig,
+    card_table: &'a CardHeuristicTable,
+}
+
+/// Scratch space reused by every iteration of one search.
+#[derive(Default)]
+struct SearchBuffers {
+    choices: Vec<Choice>,
+    availability: Vec<bool>,
+    /// Opponent draft picks sampled in the current iteration.
+    pick_log: Vec<(u32, usize, Card)>,
+    /// Moves made so far in the current iteration, for RAVE.
+    amaf_log: Vec<(usize, Choice)>,
+}
+
+/// Borrows every player's tree for a multiple-observer iteration, with the
+/// perspective player's slot pointing at `root`.
+fn observer_trees<'a>(
+    root: &'a mut MctsTree,
+    opponent_trees: &'a mut [MctsTree],
+    perspective_player: usize,
+) -> SmallVec<[&'a mut MctsTree; MAX_PLAYERS]> {
+    let mut root = Some(root);
+    opponent_trees
+        .iter_mut()
+        .enumerate()
+        .map(|(i, tree)| if i == perspective_player { root.take().unwrap() } else { tree })
+        .collect()
+}
+
+/// One MO-ISMCTS iteration. `nodes[p]` is player p's current node in
+/// `trees[p]`; the player to move selects in their own tree and every other
+/// tree follows the move as its owner observes it. Draft picks are
+/// face-down, so opponents see them only as a single hidden move. Under RAVE
+/// each tree credits the moves its node owners went on to make, hidden or
+/// not; hidden children themselves get no AMAF credit.
+fn iteration_multi_observer<R: Rng>(
+    trees: &mut [&mut MctsTree],
+    nodes: &[NodeId],
+    state: &mut GameState,
+    search: &SearchSettings,
+    buffers: &mut SearchBuffers,
+    rng: &mut R,
+) -> [f64; MAX_PLAYERS] {
+    let SearchSettings { max_rollout_round, use_heuristic, config, card_table, .. } = *search;
+    let scores = if matches!(state.phase, GamePhase::GameOver) {
+        Some(compute_terminal_rewards(&state.players))
+    } else if max_rollout_round.is_some_and(|mr| state.round > mr) {
+        Some(eval_scores(state, use_heuristic, &config.heuristic_params, card_table))
+    } else {
+        None
+    };
+    if let Some(scores) = scores {
+        record_outcome_all(trees, nodes, &scores);
+        return scores;
+    }
+
+    let (active_player, is_draft) = match &state.phase {
+        GamePhase::Draft { draft_state } => (draft_state.current_player_index, true),
+        GamePhase::Action { action_state } => (action_state.current_player_index, false),
+        GamePhase::Draw => (0, false),
+        _ => unreachable!(),
+    };
+
+    enumerate_choices_into(state, &mut buffers.choices);
+    trees[active_player].expand(
+        nodes[active_player], &buffers.choices, active_player, &mut buffers.availability, config.max_nodes, rng,
+    );
+
+    let rave_equivalence = if config.rave { Some(config.rave_equivalence) } else { None };
+    let best = select(
+        trees[active_player], nodes[active_player], &buffers.availability,
+        config.exploration_constant, config.progressive_bias_weight, rave_equivalence,
+    );
+    let choice = best.map(|id| trees[active_player].node(id).choice.clone().unwrap());
+
+    // Every observer must be able to follow the move; a full tree cannot.
+    let mut children: SmallVec<[NodeId; MAX_PLAYERS]> = SmallVec::new();
+    if let (Some(best), Some(choice)) = (best, choice.as_ref()) {
+        for (observer, tree) in trees.iter_mut().enumerate() {
+            let child = if observer == active_player {
+                Some(best)
+            } else {
+                tree.observe(nodes[observer], choice, active_player, is_draft, config.max_nodes)
+            };
+            match child {
+                Some(child) => children.push(child),
+                None => break,
+            }
+        }
+    }
+
+    if children.len() != trees.len() {
+        let scores = if best.is_some() || trees.iter().any(|t| t.is_full(config.max_nodes)) {
+            rollout(state, search, None, rng)
+        } else {
+            [0.0; MAX_PLAYERS]
+        };
+        record_outcome_all(trees, nodes, &scores);
+        return scores;
+    }
+    let best = children[active_player];
+    let should_rollout = trees[active_player].node(best).visit_count == 0;
+
+    let choice = choice.unwrap();
+    let amaf_start = buffers.amaf_log.len();
+    if config.rave {
+        buffers.amaf_log.push((active_player, choice.clone()));
+    }
+    apply_choice_to_state(state, &choice, rng);
+
+    if should_rollout && config.progressive_bias_weight != 0.0 {
+        trees[active_player].node_mut(best).heuristic_bias = heuristic_score(
+            &state.players[active_player],
+            &state.sell_card_display,
+            &state.card_lookup,
+            &config.heuristic_params,
+            card_table,
+        );
+    }
+
+    let scores = if should_rollout {
+        let scores = if config.no_rollout {
+            eval_scores(state, true, &config.heuristic_params, card_table)
+        } else {
+            let amaf_log = if config.rave { Some(&mut buffers.amaf_log) } else { None };
+            rollout(state, search, amaf_log, rng)
+        };
+        record_outcome_all(trees, &children, &scores);
+        scores
+    } else {
+        iteration_multi_observer(trees, &children, state, search, buffers, rng)
+    };
+
+    if config.rave {
+        for (tree, &node) in trees.iter_mut().zip(nodes) {
+            record_amaf_outcome(tree, node, &buffers.amaf_log[amaf_start..], &scores);
+        }
+    }
+    record_outcome_all(trees, nodes, &scores);
     scores
 }
 
 fn select(
-    node: &MctsNode,
+    tree: &MctsTree,
+    node: NodeId,
     available: &[bool],
     c: f64,
     progressive_bias_weight: f64,
-) -> Option<usize> {
-    let mut best_idx: Option<usize> = None;
+    rave_equivalence: Option<f64>,
+) -> Option<NodeId> {
+    let mut best_id: Option<NodeId> = None;
     let mut best_value = f64::NEG_INFINITY;
 
-    let root_ln = if node.is_root() { (node.visit_count as f64).ln() } else { 0.0 };
+    let is_root = node == ROOT;
+    let root_ln = if is_root { (tree.node(node).visit_count as f64).ln() } else { 0.0 };
 
-    for (idx, child) in node.children.iter().enumerate() {
+    for (idx, id) in tree.child_ids(node).enumerate() {
         if !available[idx] {
             continue;
         }
+        let child = tree.node(id);
 
         let value = if child.visit_count == 0 {
             f64::INFINITY
         } else {
-            let ln_total = if node.is_root() { root_ln } else { child.ln_availability };
+            let ln_total = if is_root { root_ln } else { child.ln_availability };
             let visit_count_f = child.visit_count as f64;
-            let win_rate = child.cumulative_reward / visit_count_f;
+            let mut win_rate = child.cumulative_reward / visit_count_f;
+            if let Some(k) = rave_equivalence {
+                if child.amaf_visit_count > 0 {
+                    let beta = (k / (3.0 * visit_count_f + k)).sqrt();
+                    let amaf_rate = child.amaf_cumulative_reward / child.amaf_visit_count as f64;
+                    win_rate = (1.0 - beta) * win_rate + beta * amaf_rate;
+                }
+            }
             let exploration = c * (ln_total / visit_count_f).sqrt();
 
             win_rate + exploration
@@ -778,39 +1197,154 @@ fn select(
 
         if value > best_value {
             best_value = value;
-            best_idx = Some(idx);
+            best_id = Some(id);
+        }
+    }
+
+    best_id
+}
+
+/// Plays out the rest of a simulation with the configured policy. With an
+/// `amaf_log`, also logs the draft picks and drafted-card destroys the
+/// rollout makes, so RAVE can credit them.
+fn rollout<R: Rng>(state: &mut GameState, search: &SearchSettings, amaf_log: Option<&mut Vec<(usize, Choice)>>, rng: &mut R) -> [f64; MAX_PLAYERS] {
+    let SearchSettings { config, card_table, .. } = *search;
+    let params = &config.heuristic_params;
+    match (&config.rollout_policy, amaf_log) {
+        (RolloutPolicyKind::Random { heuristic_draft }, None) => {
+            let policy = RandomRollout { heuristic_draft: *heuristic_draft };
+            run_rollout(|s, r| policy.play(s, params, r), state, search, rng)
+        }
+        (RolloutPolicyKind::Heuristic { heuristic_draft }, None) => {
+            let policy = HeuristicRollout { heuristic_draft: *heuristic_draft };
+            run_rollout(|s, r| policy.play(s, params, r), state, search, rng)
         }
+        (RolloutPolicyKind::Greedy { epsilon }, None) => {
+            let policy = GreedyRollout { epsilon: *epsilon };
+            run_rollout(|s, r| policy.play(s, params, card_table, r), state, search, rng)
+        }
+        (RolloutPolicyKind::Custom(custom), None) => {
+            run_rollout(|s, r| custom.0.step(s, params, card_table, r), state, search, rng)
+        }
+        (policy, Some(amaf_log)) => run_rollout(
+            |s, r| logged_rollout_step(policy, s, params, card_table, amaf_log, r),
+            state,
+            search,
+            rng,
+        ),
     }
+}
 
-    best_idx
+/// One step of `policy` that logs the draft picks and drafted-card destroys
+/// it made to `amaf_log`.
+fn logged_rollout_step<R: Rng>(
+    policy: &RolloutPolicyKind,
+    state: &mut GameState,
+    params: &HeuristicParams,
+    card_table: &CardHeuristicTable,
+    amaf_log: &mut Vec<(usize, Choice)>,
+    rng: &mut R,
+) {
+    let before: SmallVec<[UnorderedCards; MAX_PLAYERS]> = state.players.iter().map(|p| p.drafted_cards).collect();
+    let in_action = matches!(state.phase, GamePhase::Action { .. });
+    let round = state.round;
+    policy.play(state, params, card_table, rng);
+    // A step that stays within one action phase can only add drafted cards
+    // by MoveToDrafted. A step that ends the round also drafts the next one.
+    let drafted = !in_action || state.round != round;
+    log_rollout_picks(state, &before, drafted, amaf_log);
 }
 
-fn rollout<R: Rng>(state: &mut GameState, max_rollout_round: Option<u32>, max_rollout_steps: u32, use_heuristic: bool, heuristic_rollout: bool, heuristic_draft: bool, params: &HeuristicParams, card_table: &CardHeuristicTable, rng: &mut R) -> [f64; MAX_PLAYERS] {
-    for _ in 0..max_rollout_steps {
+/// Generic over `step` so each built-in policy gets its own loop with a
+/// concrete `rng`; only custom policies pay for dynamic dispatch.
+fn run_rollout<R: Rng>(mut step: impl FnMut(&mut GameState, &mut R), state: &mut GameState, search: &SearchSettings, rng: &mut R) -> [f64; MAX_PLAYERS] {
+    let SearchSettings { max_rollout_round, use_heuristic, config, card_table, .. } = *search;
+    let params = &config.heuristic_params;
+    for _ in 0..config.max_rollout_steps {
         if matches!(state.phase, GamePhase::GameOver) {
             return compute_terminal_rewards(&state.players);
         }
         if max_rollout_round.is_some_and(|mr| state.round > mr) {
             return eval_scores(state, use_heuristic, params, card_table);
         }
-        if heuristic_rollout {
-            apply_heuristic_rollout_step(state, heuristic_draft, params, rng);
-        } else {
-            apply_rollout_step(state, heuristic_draft, params, rng);
-        }
+        step(state, rng);
     }
 
     eval_scores(state, use_heuristic, params, card_table)
 }
 
-fn record_outcome(node: &mut MctsNode, scores: &[f64; MAX_PLAYERS]) {
+/// Rollout steps apply moves without building a `Choice`, so recover the
+/// draft picks and drafted-card destroys from how each player's drafted
+/// cards changed. New drafted cards are picks only if `drafted`. Cards that
+/// left for anywhere but the destroyed pile, such as the discard at the end
+/// of a turn, are not moves.
+fn log_rollout_picks(state: &GameState, drafted_before: &[UnorderedCards], drafted: bool, amaf_log: &mut Vec<(usize, Choice)>) {
+    for (player_index, (player, &before)) in state.players.iter().zip(drafted_before).enumerate() {
+        let after = player.drafted_cards;
+        if drafted {
+            for id in after.difference(before).iter() {
+                amaf_log.push((player_index, Choice::DraftPick { card: state.card_lookup[id as usize] }));
+            }
+        }
+        for id in before.difference(after).iter() {
+            if state.destroyed_pile.contains(id) {
+                amaf_log.push((player_index, Choice::DestroyDraftedCard { card: state.card_lookup[id as usize] }));
+            }
+        }
+    }
+}
+
+fn record_outcome(node: &mut NodeData, scores: &[f64; MAX_PLAYERS]) {
     node.cumulative_reward += scores[node.player_index];
     node.visit_count += 1;
 }
 
+fn record_outcome_all(trees: &mut [&mut MctsTree], nodes: &[NodeId], scores: &[f64; MAX_PLAYERS]) {
+    for (tree, &node) in trees.iter_mut().zip(nodes) {
+        record_outcome(tree.node_mut(node), scores);
+    }
+}
+
+/// Credits every child whose choice its player made anywhere in the rest of
+/// this simulation. A logged `DestroyDraftedCard`, which is all a rollout
+/// reveals about a destroy, credits every way of destroying that card.
+fn record_amaf_outcome(tree: &mut MctsTree, node: NodeId, played: &[(usize, Choice)], scores: &[f64; MAX_PLAYERS]) {
+    let mut id = tree.node(node).first_child;
+    while id != NO_NODE {
+        let child = tree.node_mut(id);
+        let credited = child.choice.as_ref().is_some_and(|child_choice| {
+            played.iter().any(|(player, choice)| *player == child.player_index && amaf_matches(choice, child_choice))
+        });
+        if credited {
+            child.amaf_visit_count += 1;
+            child.amaf_cumulative_reward += scores[child.player_index];
+        }
+        id = child.next_sibling;
+    }
+}
+
+fn amaf_matches(played: &Choice, child: &Choice) -> bool {
+    match played {
+        Choice::DestroyDraftedCard { card } => destroyed_drafted_card(child) == Some(*card),
+        _ => played == child,
+    }
+}
+
+fn destroyed_drafted_card(choice: &Choice) -> Option<Card> {
+    match choice {
+        Choice::DestroyDraftedCard { card }
+        | Choice::DestroyAndMix { card, .. }
+        | Choice::DestroyAndSell { card, .. }
+        | Choice::DestroyAndWorkshop { card, .. }
+        | Choice::DestroyAndDestroyCards { card, .. } => Some(*card),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rollout_policy::{CustomRollout, RolloutPolicy};
     use crate::colori_game::{
         apply_choice_to_state, check_choice_available, enumerate_choices_into,
         get_game_status, GameStatus,
@@ -941,17 +1475,400 @@ mod tests {
         panic!("seed={seed}, players={num_players}: game did not finish within {max_steps} steps");
     }
 
+    /// Decision points from one random game: the first draft pick, the first
+    /// action-phase decision, and the first decision of round 5.
+    fn fixed_positions(num_players: usize, seed: u64) -> Vec<GameState> {
+        let mut rng = WyRand::seed_from_u64(seed);
+        let mut state = create_initial_game_state(num_players, &vec![true; num_players], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        let first_draft = state.clone();
+        let mut choices = Vec::new();
+        while !matches!(state.phase, GamePhase::Action { .. }) {
+            enumerate_choices_into(&state, &mut choices);
+            let choice = choices[rng.random_range(0..choices.len())].clone();
+            apply_choice_to_state(&mut state, &choice, &mut rng);
+        }
+        let first_action = state.clone();
+        while state.round < 5 || matches!(state.phase, GamePhase::Draw) {
+            if matches!(state.phase, GamePhase::Draw) {
+                execute_draw_phase(&mut state, &mut rng);
+                continue;
+            }
+            enumerate_choices_into(&state, &mut choices);
+            let choice = choices[rng.random_range(0..choices.len())].clone();
+            apply_choice_to_state(&mut state, &choice, &mut rng);
+        }
+        assert!(!matches!(state.phase, GamePhase::GameOver));
+        vec![first_draft, first_action, state]
+    }
+
+    /// Searches each of `fixed_positions` as the player to move, checking
+    /// that every search runs all its iterations and picks a legal choice.
+    fn search_fixed_positions(num_players: usize, config: &MctsConfig) -> Vec<(GameState, MctsResult)> {
+        assert!(!config.early_termination);
+        let mut rng = WyRand::seed_from_u64(0);
+        let mut choices = Vec::new();
+        fixed_positions(num_players, 0)
+            .into_iter()
+            .map(|state| {
+                let GameStatus::AwaitingAction { player_index } = get_game_status(&state, None) else {
+                    unreachable!()
+                };
+                let result = ismcts(&state, player_index, config, None, None, &mut rng);
+                enumerate_choices_into(&state, &mut choices);
+                assert!(choices.contains(&result.choice), "{:?} not in {choices:?}", result.choice);
+                assert_eq!(result.tree.as_ref().unwrap().root().visit_count(), config.iterations);
+                (state, result)
+            })
+            .collect()
+    }
+
+    /// Mean final score per player when `policy` plays whole two-player
+    /// games from the same deal.
+    fn mean_rollout_score(policy: &RolloutPolicyKind) -> f64 {
+        let params = test_heuristic_params();
+        let card_table = CardHeuristicTable::new(&params);
+        let mut rng = WyRand::seed_from_u64(1);
+        let mut start = create_initial_game_state(2, &[true, true], &mut rng);
+        execute_draw_phase(&mut start, &mut rng);
+        let mut total = 0;
+        for seed in 0..10 {
+            let mut rng = WyRand::seed_from_u64(seed);
+            let mut state = start.clone();
+            while !matches!(state.phase, GamePhase::GameOver) {
+                policy.play(&mut state, &params, &card_table, &mut rng);
+            }
+            total += state.players.iter().map(calculate_score).sum::<u32>();
+        }
+        total as f64 / 20.0
+    }
+
     #[test]
-    fn test_ismcts_with_heuristic_rollout() {
+    fn test_ismcts_with_rave() {
         let config = MctsConfig {
-            iterations: 10,
+            iterations: 100,
+            rave: true,
+            early_termination: false,
+            ..MctsConfig::new(test_heuristic_params())
+        };
+        for num_players in 2..=4 {
+            for (state, result) in search_fixed_positions(num_players, &config) {
+                let tree = result.tree.unwrap();
+                let children: Vec<&NodeData> = tree.child_ids(ROOT).map(|id| tree.node(id)).collect();
+                // Every visit credits the child's own move, and later matching
+                // moves credit it again.
+                assert!(children.iter().all(|c| c.amaf_visit_count >= c.visit_count));
+                assert!(
+                    children.iter().any(|c| c.amaf_visit_count > c.visit_count),
+                    "players={num_players}, round={}: no AMAF credit beyond visits",
+                    state.round
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rave_collects_amaf_stats_for_draft_picks() {
+        let mut rng = WyRand::seed_from_u64(3);
+        let mut state = create_initial_game_state(3, &[true, true, true], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        let config = MctsConfig {
+            iterations: 200,
+            rave: true,
+            early_termination: false,
+            ..MctsConfig::new(test_heuristic_params())
+        };
+        let tree = ismcts(&state, 0, &config, None, None, &mut rng).tree.unwrap();
+        let children: Vec<&NodeData> = tree.child_ids(ROOT).map(|id| tree.node(id)).collect();
+        let amaf_total: u32 = children.iter().map(|c| c.amaf_visit_count).sum();
+        let visit_total: u32 = children.iter().map(|c| c.visit_count).sum();
+        // Each simulation credits its own root pick plus any later matching picks.
+        assert!(amaf_total >= visit_total);
+        for child in children {
+            assert!(child.amaf_visit_count >= child.visit_count);
+        }
+    }
+
+    #[test]
+    fn test_rollout_logs_draft_picks_and_destroys() {
+        let mut rng = WyRand::seed_from_u64(2);
+        let mut state = create_initial_game_state(3, &[true, true, true], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        let config = MctsConfig::new(test_heuristic_params());
+        let card_table = CardHeuristicTable::new(&config.heuristic_params);
+        let search = SearchSettings {
+            perspective_player: 0,
+            max_rollout_round: Some(state.round),
+            use_heuristic: true,
+            config: &config,
+            card_table: &card_table,
+        };
+        let mut amaf_log = Vec::new();
+        rollout(&mut state, &search, Some(&mut amaf_log), &mut rng);
+
+        for player in 0..3 {
+            let picks = amaf_log.iter().filter(|(p, c)| *p == player && matches!(c, Choice::DraftPick { .. })).count();
+            // The step that ends a round also drafts the next one.
+            assert!(picks >= 4 && picks % 4 == 0, "player {player} drafted {picks} cards");
+            assert!(amaf_log.iter().any(|(p, c)| *p == player && matches!(c, Choice::DestroyDraftedCard { .. })));
+        }
+        let (_, Choice::DestroyDraftedCard { card }) = amaf_log.iter().find(|(_, c)| matches!(c, Choice::DestroyDraftedCard { .. })).unwrap() else {
+            unreachable!()
+        };
+        assert!(amaf_matches(&Choice::DestroyDraftedCard { card: *card }, &Choice::DestroyAndMix { card: *card, mixes: SmallVec::new() }));
+        assert!(!amaf_matches(&Choice::DraftPick { card: *card }, &Choice::DestroyDraftedCard { card: *card }));
+    }
+
+    #[test]
+    fn test_rollout_move_to_drafted_is_not_a_draft_pick() {
+        let mut rng = WyRand::seed_from_u64(3);
+        let mut state = create_initial_game_state(3, &[true, true, true], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        let mut choices = Vec::new();
+        while !matches!(state.phase, GamePhase::Action { .. }) {
+            enumerate_choices_into(&state, &mut choices);
+            let choice = choices[rng.random_range(0..choices.len())].clone();
+            apply_choice_to_state(&mut state, &choice, &mut rng);
+        }
+        let GamePhase::Action { ref mut action_state } = state.phase else { unreachable!() };
+        let player = action_state.current_player_index;
+        action_state.ability_stack.push(Ability::MoveToDrafted);
+        assert!(!state.players[player].workshop_cards.is_empty());
+
+        let config = MctsConfig::new(test_heuristic_params());
+        let card_table = CardHeuristicTable::new(&config.heuristic_params);
+        let policy = RolloutPolicyKind::Random { heuristic_draft: false };
+        let mut moved = 0;
+        for seed in 0..20 {
+            let mut s = state.clone();
+            let mut amaf_log = Vec::new();
+            logged_rollout_step(&policy, &mut s, &config.heuristic_params, &card_table, &mut amaf_log, &mut WyRand::seed_from_u64(seed));
+            if s.players[player].drafted_cards.len() > state.players[player].drafted_cards.len() {
+                moved += 1;
+            }
+            assert!(!amaf_log.iter().any(|(_, c)| matches!(c, Choice::DraftPick { .. })), "seed {seed} logged {amaf_log:?}");
+        }
+        assert!(moved > 0, "no seed moved a workshop card to drafted");
+    }
+
+    #[test]
+    fn test_ismcts_multi_observer() {
+        let config = MctsConfig {
+            iterations: 100,
+            multi_observer: true,
+            early_termination: false,
             ..MctsConfig::new(test_heuristic_params())
         };
         for num_players in 2..=4 {
-            for seed in 0..3 {
-                run_full_game_with_config(num_players, seed, &config);
+            for (state, result) in search_fixed_positions(num_players, &config) {
+                let GameStatus::AwaitingAction { player_index } = get_game_status(&state, None) else {
+                    unreachable!()
+                };
+                let tree = result.tree.unwrap();
+                assert_eq!(result.opponent_trees.len(), num_players);
+                for (player, opponent_tree) in result.opponent_trees.iter().enumerate() {
+                    if player == player_index {
+                        assert_eq!(opponent_tree.node_count(), 1);
+                        continue;
+                    }
+                    assert_eq!(opponent_tree.root().visit_count(), config.iterations);
+                    // Action-phase moves are public, so opponents see the same
+                    // root moves as the player searching.
+                    if matches!(state.phase, GamePhase::Action { .. }) {
+                        assert_eq!(opponent_tree.root().children().len(), tree.root().children().len());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_observer_hides_perspective_draft_pick() {
+        let mut rng = WyRand::seed_from_u64(5);
+        let mut state = create_initial_game_state(3, &[true, true, true], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        let perspective = match &state.phase {
+            GamePhase::Draft { draft_state } => draft_state.current_player_index,
+            _ => unreachable!(),
+        };
+        let config = MctsConfig {
+            iterations: 100,
+            multi_observer: true,
+            early_termination: false,
+            ..MctsConfig::new(test_heuristic_params())
+        };
+        let result = ismcts(&state, perspective, &config, None, None, &mut rng);
+        assert!(result.tree.unwrap().root().children().len() > 1);
+        assert_eq!(result.opponent_trees.len(), 3);
+        for (player, tree) in result.opponent_trees.iter().enumerate() {
+            if player == perspective {
+                continue;
+            }
+            // Opponents cannot tell which card was picked, so every
+            // iteration passes through the same hidden child.
+            let children: Vec<MctsNode> = tree.root().children().collect();
+            assert_eq!(children.len(), 1);
+            assert!(children[0].is_hidden());
+            assert_eq!(children[0].visit_count(), tree.root().visit_count());
+        }
+    }
+
+    #[test]
+    fn test_multi_observer_with_rave() {
+        let mut rng = WyRand::seed_from_u64(6);
+        let mut state = create_initial_game_state(3, &[true, true, true], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        let config = MctsConfig {
+            iterations: 200,
+            rave: true,
+            multi_observer: true,
+            early_termination: false,
+            ..MctsConfig::new(test_heuristic_params())
+        };
+        let result = ismcts(&state, 0, &config, None, None, &mut rng);
+        let tree = result.tree.unwrap();
+        for child in tree.child_ids(ROOT).map(|id| tree.node(id)) {
+            assert!(child.amaf_visit_count >= child.visit_count);
+        }
+        for opponent_tree in &result.opponent_trees[1..] {
+            let root = opponent_tree.node(ROOT);
+            assert!(opponent_tree.child_ids(ROOT).all(|id| opponent_tree.node(id).amaf_visit_count == 0));
+            assert_eq!(root.visit_count, 200);
+        }
+    }
+
+    #[test]
+    fn test_max_nodes_caps_tree_size() {
+        let mut rng = WyRand::seed_from_u64(8);
+        let mut state = create_initial_game_state(3, &[true, true, true], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        for multi_observer in [false, true] {
+            let config = MctsConfig {
+                iterations: 500,
+                early_termination: false,
+                multi_observer,
+                max_nodes: Some(40),
+                ..MctsConfig::new(test_heuristic_params())
+            };
+            let result = ismcts(&state, 0, &config, None, None, &mut rng);
+            let tree = result.tree.unwrap();
+            assert!(tree.node_count() <= 40);
+            assert_eq!(tree.root().visit_count(), 500);
+            for opponent_tree in &result.opponent_trees {
+                assert!(opponent_tree.node_count() <= 40);
             }
         }
+
+        let config = MctsConfig {
+            iterations: 50,
+            max_nodes: Some(16),
+            ..MctsConfig::new(test_heuristic_params())
+        };
+        run_full_game_with_config(2, 0, &config);
     }
 
+    #[test]
+    fn test_into_subtree_keeps_child_statistics() {
+        let mut rng = WyRand::seed_from_u64(4);
+        let mut state = create_initial_game_state(2, &[true, true], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        let config = MctsConfig {
+            iterations: 300,
+            early_termination: false,
+            ..MctsConfig::new(test_heuristic_params())
+        };
+        let result = ismcts(&state, 0, &config, None, None, &mut rng);
+        let tree = result.tree.unwrap();
+        let child = tree.root().children().find(|c| c.choice() == Some(&result.choice)).unwrap();
+        let (visits, nodes, depth) = (child.visit_count(), child.tree_stats().total_nodes, child.max_depth());
+
+        let subtree = tree.into_subtree(&result.choice).unwrap();
+        assert_eq!(subtree.root().choice(), None);
+        assert_eq!(subtree.root().visit_count(), visits);
+        assert_eq!(subtree.root().tree_stats().total_nodes, nodes);
+        assert_eq!(subtree.root().max_depth(), depth);
+    }
+
+    #[test]
+    fn test_ismcts_with_greedy_rollout() {
+        let policy = RolloutPolicyKind::Greedy { epsilon: 0.2 };
+        let config = MctsConfig {
+            iterations: 50,
+            rollout_policy: policy.clone(),
+            early_termination: false,
+            ..MctsConfig::new(test_heuristic_params())
+        };
+        search_fixed_positions(3, &config);
+        let random = mean_rollout_score(&RolloutPolicyKind::Random { heuristic_draft: false });
+        let greedy = mean_rollout_score(&policy);
+        assert!(greedy > random, "greedy {greedy} <= random {random}");
+    }
+
+    struct CountingRollout(std::sync::atomic::AtomicUsize);
+
+    impl RolloutPolicy for CountingRollout {
+        fn step(&self, state: &mut GameState, params: &HeuristicParams, card_table: &CardHeuristicTable, rng: &mut dyn Rng) {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            RolloutPolicyKind::default().step(state, params, card_table, rng);
+        }
+    }
+
+    #[test]
+    fn test_ismcts_with_custom_rollout() {
+        let policy = std::sync::Arc::new(CountingRollout(Default::default()));
+        let config = MctsConfig {
+            iterations: 20,
+            rollout_policy: RolloutPolicyKind::Custom(CustomRollout(policy.clone())),
+            ..MctsConfig::new(test_heuristic_params())
+        };
+        let mut rng = WyRand::seed_from_u64(0);
+        let mut state = create_initial_game_state(2, &[true, true], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        ismcts(&state, 0, &config, None, None, &mut rng);
+        assert!(policy.0.load(std::sync::atomic::Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn test_config_rollout_flags_select_policy() {
+        let params = include_str!("../../genetic-algorithm/batch-lki08w-gen-32.json");
+        let json = format!(r#"{{"heuristicParams":{params},"heuristicRollout":false,"heuristicDraft":true}}"#);
+        let config: MctsConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(config.rollout_policy, RolloutPolicyKind::Random { heuristic_draft: true });
+        let json = format!(r#"{{"heuristicParams":{params},"rolloutPolicy":{{"type":"greedy","epsilon":0.5}}}}"#);
+        let config: MctsConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(config.rollout_policy, RolloutPolicyKind::Greedy { epsilon: 0.5 });
+        let json = format!(r#"{{"heuristicParams":{params},"rolloutPolicy":{{"type":"greedy"}},"heuristicDraft":true}}"#);
+        assert!(serde_json::from_str::<MctsConfig>(&json).is_err());
+    }
+
+    #[test]
+    fn test_rollout_round_horizon() {
+        let params = include_str!("../../genetic-algorithm/batch-lki08w-gen-32.json");
+        let json = format!(r#"{{"heuristicParams":{params}}}"#);
+        let config: MctsConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(config.max_rollout_round(1), 8);
+        assert_eq!(config.max_rollout_round(7), 9);
+        let json = format!(r#"{{"heuristicParams":{params},"rolloutRoundHorizon":4}}"#);
+        let config: MctsConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(config.max_rollout_round(3), 8);
+        assert_eq!(config.max_rollout_round(6), 10);
+    }
+
+    #[test]
+    fn test_ismcts_with_heuristic_rollout() {
+        let config = MctsConfig {
+            iterations: 50,
+            early_termination: false,
+            ..MctsConfig::new(test_heuristic_params())
+        };
+        assert_eq!(config.rollout_policy, RolloutPolicyKind::Heuristic { heuristic_draft: false });
+        for num_players in 2..=4 {
+            search_fixed_positions(num_players, &config);
+        }
+        let random = mean_rollout_score(&RolloutPolicyKind::Random { heuristic_draft: false });
+        let heuristic = mean_rollout_score(&config.rollout_policy);
+        assert!(heuristic > random, "heuristic {heuristic} <= random {random}");
+    }
+
+
 }
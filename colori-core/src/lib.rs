@@ -1,4 +1,5 @@
 pub mod action_phase;
+pub mod action_space;
 pub mod apply_choice;
 pub mod cards;
 pub mod choices;
@@ -11,9 +12,13 @@ pub mod draw_phase;
 pub mod fixed_vec;
 pub mod game_log;
 pub mod ismcts;
+pub mod observation;
+pub mod perft;
 pub mod replay;
 pub(crate) mod rollout;
+pub mod rollout_policy;
 pub mod scoring;
 pub mod setup;
+pub mod turn_planner;
 pub mod types;
 pub mod unordered_cards;
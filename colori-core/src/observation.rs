@@ -0,0 +1,138 @@
+use crate::types::*;
+use crate::unordered_cards::UnorderedCards;
+
+const NUM_CARD_TYPES: usize = 45;
+const NUM_SELL_CARD_TYPES: usize = 54;
+const NUM_ABILITY_KINDS: usize = 11;
+const PLAYER_FEATURES: usize = 1 + 5 * NUM_CARD_TYPES + NUM_COLORS + 3 + 2;
+const GLOBAL_FEATURES: usize = 5;
+
+/// Length of the vector produced by `encode_observation`, identical for
+/// every player count.
+pub const OBSERVATION_LEN: usize =
+    GLOBAL_FEATURES + MAX_PLAYERS * PLAYER_FEATURES + NUM_CARD_TYPES + NUM_SELL_CARD_TYPES + NUM_ABILITY_KINDS;
+
+fn card_type_counts(cards: UnorderedCards, card_lookup: &[Card; MAX_CARD_INSTANCES], out: &mut Vec<f32>) {
+    let start = out.len();
+    out.resize(start + NUM_CARD_TYPES, 0.0);
+    for id in cards.iter() {
+        out[start + card_lookup[id as usize] as usize] += 1.0;
+    }
+}
+
+fn ability_kind(ability: &Ability) -> usize {
+    match ability {
+        Ability::Workshop { .. } => 0,
+        Ability::DrawCards { .. } => 1,
+        Ability::MixColors { .. } => 2,
+        Ability::DestroyCards => 3,
+        Ability::Sell => 4,
+        Ability::GainDucats { .. } => 5,
+        Ability::GainSecondary => 6,
+        Ability::GainPrimary => 7,
+        Ability::ChangeTertiary => 8,
+        Ability::MoveToDrafted => 9,
+        Ability::MoveToWorkshop => 10,
+    }
+}
+
+/// Encodes everything `perspective` can observe as a flat feature vector.
+/// Player blocks are rotated so the perspective player always comes first.
+/// Opponents' draft hands are the only hidden information and are omitted.
+pub fn encode_observation(state: &GameState, perspective: usize) -> Vec<f32> {
+    let mut out = Vec::with_capacity(OBSERVATION_LEN);
+    let num_players = state.players.len();
+
+    let (is_draft, is_action, pick_number) = match &state.phase {
+        GamePhase::Draft { draft_state } => (1.0, 0.0, draft_state.pick_number as f32),
+        GamePhase::Action { .. } => (0.0, 1.0, 0.0),
+        _ => (0.0, 0.0, 0.0),
+    };
+    out.push(is_draft);
+    out.push(is_action);
+    out.push(state.round as f32);
+    out.push(pick_number);
+    out.push(num_players as f32);
+
+    for slot in 0..MAX_PLAYERS {
+        if slot >= num_players {
+            out.resize(out.len() + PLAYER_FEATURES, 0.0);
+            continue;
+        }
+        let player = &state.players[(perspective + slot) % num_players];
+        out.push(1.0);
+        card_type_counts(player.deck, &state.card_lookup, &mut out);
+        card_type_counts(player.discard, &state.card_lookup, &mut out);
+        card_type_counts(player.workshop_cards, &state.card_lookup, &mut out);
+        card_type_counts(player.workshopped_cards, &state.card_lookup, &mut out);
+        card_type_counts(player.drafted_cards, &state.card_lookup, &mut out);
+        out.extend(player.color_wheel.counts.iter().map(|&c| c as f32));
+        out.extend(player.materials.counts.iter().map(|&c| c as f32));
+        out.push(player.ducats as f32);
+        out.push(player.completed_sell_cards.len() as f32);
+    }
+
+    let own_hand = match &state.phase {
+        GamePhase::Draft { draft_state } => draft_state.hands[perspective],
+        _ => UnorderedCards::new(),
+    };
+    card_type_counts(own_hand, &state.card_lookup, &mut out);
+
+    let start = out.len();
+    out.resize(start + NUM_SELL_CARD_TYPES, 0.0);
+    for sci in state.sell_card_display.iter() {
+        out[start + sci.sell_card as usize] += 1.0;
+    }
+
+    let start = out.len();
+    out.resize(start + NUM_ABILITY_KINDS, 0.0);
+    if let GamePhase::Action { action_state } = &state.phase {
+        if let Some(top) = action_state.ability_stack.last() {
+            out[start + ability_kind(top)] = 1.0;
+        }
+    }
+
+    debug_assert_eq!(out.len(), OBSERVATION_LEN);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colori_game::{apply_choice_to_state, enumerate_choices};
+    use crate::draw_phase::execute_draw_phase;
+    use crate::setup::create_initial_game_state;
+    use rand::RngExt;
+    use rand::SeedableRng;
+    use wyrand::WyRand;
+
+    #[test]
+    fn test_observation_length_is_fixed() {
+        for num_players in 1..=MAX_PLAYERS {
+            let mut rng = WyRand::seed_from_u64(num_players as u64);
+            let ai_players = vec![true; num_players];
+            let mut state = create_initial_game_state(num_players, &ai_players, &mut rng);
+            execute_draw_phase(&mut state, &mut rng);
+            while !matches!(state.phase, GamePhase::GameOver) {
+                for perspective in 0..num_players {
+                    assert_eq!(encode_observation(&state, perspective).len(), OBSERVATION_LEN);
+                }
+                let choices = enumerate_choices(&state);
+                let choice = &choices[rng.random_range(0..choices.len())];
+                apply_choice_to_state(&mut state, choice, &mut rng);
+            }
+        }
+    }
+
+    #[test]
+    fn test_observation_hides_opponent_draft_hands() {
+        let mut rng = WyRand::seed_from_u64(7);
+        let mut state = create_initial_game_state(3, &[true, true, true], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        let before = encode_observation(&state, 0);
+        if let GamePhase::Draft { ref mut draft_state } = state.phase {
+            draft_state.hands.swap(1, 2);
+        }
+        assert_eq!(encode_observation(&state, 0), before);
+    }
+}
@@ -0,0 +1,121 @@
+//! Perft-style move generation counts.
+//!
+//! `perft` counts the distinct choice sequences of a fixed length from a
+//! position, using `enumerate_choices` and `apply_choice_to_state`. Any change
+//! to the rules or to choice enumeration shows up as a change in these
+//! counts; a moved reference count in the tests is a prompt to check that
+//! the change was intended. `colori-runner perft` prints the same counts.
+//!
+//! Every choice is applied with a generator freshly seeded from `seed`, so the
+//! random draws behind a choice depend only on the position and the seed. That
+//! makes counts composable: the count for one entry of `perft_divide` equals
+//! `perft` of that child at one less depth.
+
+use crate::colori_game::{apply_choice_to_state, enumerate_choices};
+use crate::draw_phase::execute_draw_phase;
+use crate::setup::create_initial_game_state;
+use crate::types::*;
+use rand::{RngExt, SeedableRng};
+use wyrand::WyRand;
+
+/// Number of choice sequences of exactly `depth` choices from `state`.
+/// Sequences that end the game early are not counted.
+pub fn perft(state: &GameState, depth: u32, seed: u64) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if matches!(state.phase, GamePhase::GameOver) {
+        return 0;
+    }
+    let choices = enumerate_choices(state);
+    // Every enumerated choice can be applied, so the last ply needs no apply.
+    if depth == 1 {
+        return choices.len() as u64;
+    }
+    choices
+        .iter()
+        .map(|choice| perft(&child(state, choice, seed), depth - 1, seed))
+        .sum()
+}
+
+/// `perft` broken down by the first choice, in enumeration order.
+pub fn perft_divide(state: &GameState, depth: u32, seed: u64) -> Vec<(Choice, u64)> {
+    assert!(depth > 0, "perft_divide needs a depth of at least 1");
+    if matches!(state.phase, GamePhase::GameOver) {
+        return Vec::new();
+    }
+    enumerate_choices(state)
+        .into_iter()
+        .map(|choice| {
+            let count = perft(&child(state, &choice, seed), depth - 1, seed);
+            (choice, count)
+        })
+        .collect()
+}
+
+fn child(state: &GameState, choice: &Choice, seed: u64) -> GameState {
+    let mut next = state.clone();
+    let mut rng = WyRand::seed_from_u64(seed);
+    apply_choice_to_state(&mut next, choice, &mut rng);
+    next
+}
+
+/// A reproducible starting point for `perft`: a fresh `num_players` game
+/// dealt from `seed`, advanced by `plies` uniformly random choices (fewer if
+/// the game ends first).
+pub fn seed_position(num_players: usize, seed: u64, plies: u32) -> GameState {
+    let mut rng = WyRand::seed_from_u64(seed);
+    let mut state = create_initial_game_state(num_players, &vec![true; num_players], &mut rng);
+    execute_draw_phase(&mut state, &mut rng);
+    for _ in 0..plies {
+        if matches!(state.phase, GamePhase::GameOver) {
+            break;
+        }
+        let choices = enumerate_choices(&state);
+        let choice = &choices[rng.random_range(0..choices.len())];
+        apply_choice_to_state(&mut state, choice, &mut rng);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (players, seed, plies, depth, count). A rules change that moves one of
+    /// these should be explained in the commit that updates it.
+    const REFERENCE_COUNTS: &[(usize, u64, u32, u32, u64)] = &[
+        (1, 5, 0, 4, 60),
+        (1, 6, 25, 4, 137),
+        (2, 0, 0, 4, 400),
+        (2, 3, 40, 4, 2707),
+        (3, 1, 12, 4, 61306),
+        (3, 4, 80, 4, 28193),
+        (4, 2, 30, 4, 318),
+        (4, 7, 150, 4, 400),
+    ];
+
+    #[test]
+    fn test_perft_reference_counts() {
+        for &(num_players, seed, plies, depth, expected) in REFERENCE_COUNTS {
+            let state = seed_position(num_players, seed, plies);
+            assert_eq!(
+                perft(&state, depth, seed),
+                expected,
+                "players={num_players}, seed={seed}, plies={plies}, depth={depth}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_divide_sums_to_perft() {
+        let state = seed_position(3, 5, 20);
+        let divide = perft_divide(&state, 2, 5);
+        assert_eq!(divide.len(), enumerate_choices(&state).len());
+        let total: u64 = divide.iter().map(|(_, count)| count).sum();
+        assert_eq!(total, perft(&state, 2, 5));
+        for (choice, count) in &divide {
+            assert_eq!(*count, perft(&child(&state, choice, 5), 1, 5));
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::game_log::{DrawEvent, DrawLog, StructuredLogEntry};
 use crate::scoring::calculate_score;
 use crate::types::{Choice, GamePhase, GameState};
 use std::collections::VecDeque;
+use std::sync::Arc;
 use wyrand::WyRand;
 use rand::SeedableRng;
 
@@ -14,8 +15,8 @@ pub fn reconstruct_initial_state(initial_state_json: &serde_json::Value) -> Game
     let mut state: GameState = serde_json::from_value(initial_state_json.clone())
         .expect("Failed to deserialize initial state");
 
-    state.card_lookup = crate::unordered_cards::get_card_registry();
-    state.sell_card_lookup = crate::unordered_cards::get_sell_card_registry();
+    state.card_lookup = Arc::new(crate::unordered_cards::get_card_registry());
+    state.sell_card_lookup = Arc::new(crate::unordered_cards::get_sell_card_registry());
 
     for player in state.players.iter_mut() {
         player.cached_score = calculate_score(player);
@@ -87,23 +87,15 @@ fn rollout_draw_and_draft<R: Rng>(state: &mut GameState, rng: &mut R) {
 
 // ── Fused rollout step ──
 
+/// Performs up to `remaining` uniformly random mixes on `wheel`, stopping
+/// early when the skip option is drawn.
 #[inline(always)]
-fn random_mix_seq<R: Rng>(
-    wheel: &ColorWheel,
-    remaining: u32,
-    rng: &mut R,
-) -> ([(Color, Color); 2], usize) {
-    let mut mixes = [(Color::Red, Color::Red); 2];
-    let mut count = 0usize;
-    let mut sim_wheel = wheel.clone();
+fn random_mixes<R: Rng>(wheel: &mut ColorWheel, remaining: u32, rng: &mut R) {
     for _ in 0..remaining {
-        if count >= 2 {
-            break;
-        }
         let mut pairs: [(Color, Color); 9] = [(Color::Red, Color::Red); 9];
         let mut pair_count = 0usize;
         for &(a, b) in &VALID_MIX_PAIRS {
-            if sim_wheel.get(a) > 0 && sim_wheel.get(b) > 0 {
+            if wheel.get(a) > 0 && wheel.get(b) > 0 {
                 pairs[pair_count] = (a, b);
                 pair_count += 1;
             }
@@ -116,11 +108,8 @@ fn random_mix_seq<R: Rng>(
             break;
         }
         let (a, b) = pairs[target];
-        mixes[count] = (a, b);
-        count += 1;
-        perform_mix_unchecked(&mut sim_wheel, a, b);
+        perform_mix_unchecked(wheel, a, b);
     }
-    (mixes, count)
 }
 
 #[inline(always)]
@@ -168,14 +157,9 @@ fn handle_action_no_pending(state: &mut GameState, player_index: usize, heuristi
     let card = state.card_lookup[card_id as usize];
     match card.ability() {
         Ability::MixColors { count } => {
-            let (mixes, mix_count) =
-                random_mix_seq(&state.players[player_index].color_wheel, count, rng);
             state.players[player_index].drafted_cards.remove(card_id);
             state.destroyed_pile.insert(card_id);
-            for i in 0..mix_count {
-                let (a, b) = mixes[i];
-                perform_mix_unchecked(&mut state.players[player_index].color_wheel, a, b);
-            }
+            random_mixes(&mut state.players[player_index].color_wheel, count, rng);
         }
         Ability::Sell => {
             if let Some(sell_card_id) = pick_random_affordable_sell_card(
@@ -199,7 +183,7 @@ fn handle_action_no_pending(state: &mut GameState, player_index: usize, heuristi
 fn fused_buy<R: Rng>(
     state: &mut GameState,
     player_index: usize,
-    card_id: u8,
+    card_id: CardId,
     sell_card_id: u32,
     rng: &mut R,
 ) {
@@ -274,16 +258,7 @@ pub fn apply_rollout_step<R: Rng>(state: &mut GameState, heuristic_draft: bool,
                 }
                 Some(Ability::MixColors { count }) => {
                     let remaining_mixes = *count;
-                    let (mixes, mix_count) =
-                        random_mix_seq(&state.players[player_index].color_wheel, remaining_mixes, rng);
-                    for i in 0..mix_count {
-                        let (a, b) = mixes[i];
-                        perform_mix_unchecked(
-                            &mut state.players[player_index].color_wheel,
-                            a,
-                            b,
-                        );
-                    }
+                    random_mixes(&mut state.players[player_index].color_wheel, remaining_mixes, rng);
                     if let GamePhase::Action { ref mut action_state } = state.phase {
                         action_state.ability_stack.pop();
                     }
@@ -412,17 +387,17 @@ fn ability_category(ability: Ability) -> (u8, u32) {
 #[inline(always)]
 fn pick_card_to_drop<R: Rng>(
     hand: &UnorderedCards,
-    card_lookup: &[Card; 256],
+    card_lookup: &[Card; MAX_CARD_INSTANCES],
     params: &HeuristicParams,
     rng: &mut R,
-) -> u8 {
+) -> CardId {
     // Epsilon: random drop
     if rng.random_bool(params.rollout_epsilon) {
         return hand.pick_random(rng).unwrap();
     }
 
     // Collect card info: (instance_id, category, workshop_count)
-    let mut cards: [(u8, u8, u32); 8] = [(0, 0, 0); 8];
+    let mut cards: [(CardId, u8, u32); 8] = [(0, 0, 0); 8];
     let mut count = 0usize;
     for id in hand.iter() {
         let card = card_lookup[id as usize];
@@ -448,7 +423,7 @@ fn pick_card_to_drop<R: Rng>(
     }
 
     // Among cards with max redundancy, pick the worst to drop
-    let mut best_drop: Option<u8> = None;
+    let mut best_drop: Option<CardId> = None;
     let mut best_drop_wc = u32::MAX; // for Workshop: lower = worse = drop first
     let mut candidates = 0u32;
 
@@ -922,29 +897,24 @@ fn pick_best_color<R: Rng>(
 
 /// Heuristic mix sequence: prefer mixes whose output is useful for sell cards.
 #[inline(always)]
-fn heuristic_mix_seq<R: Rng>(
-    wheel: &ColorWheel,
+fn heuristic_mixes<R: Rng>(
+    wheel: &mut ColorWheel,
     remaining: u32,
     cache: &SellCardCache,
     params: &HeuristicParams,
     rng: &mut R,
-) -> ([(Color, Color); 2], usize) {
+) {
     if rng.random_bool(params.rollout_epsilon) {
-        return random_mix_seq(wheel, remaining, rng);
+        random_mixes(wheel, remaining, rng);
+        return;
     }
 
-    let mut mixes = [(Color::Red, Color::Red); 2];
-    let mut count = 0usize;
-    let mut sim_wheel = wheel.clone();
     for _ in 0..remaining {
-        if count >= 2 {
-            break;
-        }
         let mut best_pair: Option<(Color, Color)> = None;
         let mut best_score = 0u32;
         let mut any_valid = false;
         for &(a, b) in &VALID_MIX_PAIRS {
-            if sim_wheel.get(a) > 0 && sim_wheel.get(b) > 0 {
+            if wheel.get(a) > 0 && wheel.get(b) > 0 {
                 any_valid = true;
                 let output = mix_result(a, b);
                 let score = cache.color_demand(output);
@@ -966,40 +936,38 @@ fn heuristic_mix_seq<R: Rng>(
             let mut pairs: [(Color, Color); 9] = [(Color::Red, Color::Red); 9];
             let mut pair_count = 0usize;
             for &(a, b) in &VALID_MIX_PAIRS {
-                if sim_wheel.get(a) > 0 && sim_wheel.get(b) > 0 {
+                if wheel.get(a) > 0 && wheel.get(b) > 0 {
                     pairs[pair_count] = (a, b);
                     pair_count += 1;
                 }
             }
             let (a, b) = pairs[rng.random_range(0..pair_count)];
-            mixes[count] = (a, b);
-            count += 1;
-            perform_mix_unchecked(&mut sim_wheel, a, b);
+            perform_mix_unchecked(wheel, a, b);
         } else {
             let (a, b) = best_pair.unwrap();
-            mixes[count] = (a, b);
-            count += 1;
-            perform_mix_unchecked(&mut sim_wheel, a, b);
+            perform_mix_unchecked(wheel, a, b);
         }
     }
-    (mixes, count)
 }
 
-/// Two-step mix lookahead: evaluate all pair combinations when 2 mixes are available.
+/// Two-step mix lookahead: evaluate all pair combinations for the first two
+/// mixes, then pick any further mixes greedily.
 #[inline(always)]
-fn two_step_heuristic_mix_seq<R: Rng>(
-    wheel: &ColorWheel,
+fn two_step_heuristic_mixes<R: Rng>(
+    wheel: &mut ColorWheel,
     remaining: u32,
     cache: &SellCardCache,
     params: &HeuristicParams,
     rng: &mut R,
-) -> ([(Color, Color); 2], usize) {
+) {
     if remaining < 2 {
-        return heuristic_mix_seq(wheel, remaining, cache, params, rng);
+        heuristic_mixes(wheel, remaining, cache, params, rng);
+        return;
     }
 
     if rng.random_bool(params.rollout_epsilon) {
-        return random_mix_seq(wheel, remaining, rng);
+        random_mixes(wheel, remaining, rng);
+        return;
     }
 
     // Collect valid first-mix pairs
@@ -1013,7 +981,7 @@ fn two_step_heuristic_mix_seq<R: Rng>(
     }
 
     if pair1_count == 0 {
-        return ([(Color::Red, Color::Red); 2], 0);
+        return;
     }
 
     let mut best_score = 0u32;
@@ -1050,12 +1018,21 @@ fn two_step_heuristic_mix_seq<R: Rng>(
     // If no useful mixes, 50% chance to skip or fall back to random
     if best_score == 0 {
         if rng.random_bool(0.5) {
-            return ([(Color::Red, Color::Red); 2], 0);
+            return;
         }
-        return random_mix_seq(wheel, remaining, rng);
+        random_mixes(wheel, remaining, rng);
+        return;
     }
 
-    best_combo
+    let (mixes, mix_count) = best_combo;
+    for &(a, b) in &mixes[..mix_count] {
+        perform_mix_unchecked(wheel, a, b);
+    }
+    // Stopping after one mix means the second was not worth it; otherwise
+    // continue greedily with whatever mixes are left.
+    if mix_count == 2 && remaining > 2 {
+        heuristic_mixes(wheel, remaining - 2, cache, params, rng);
+    }
 }
 
 #[inline(always)]
@@ -1080,7 +1057,7 @@ fn handle_action_no_pending_heuristic(state: &mut GameState, player_index: usize
     }
 
     // Score each drafted card and pick the best to destroy
-    let mut best_id: Option<u8> = None;
+    let mut best_id: Option<CardId> = None;
     let mut best_priority = 0u32;
     let mut seen: u64 = 0;
     for id in drafted.iter() {
@@ -1114,14 +1091,9 @@ fn handle_action_no_pending_heuristic(state: &mut GameState, player_index: usize
     let card = state.card_lookup[card_id as usize];
     match card.ability() {
         Ability::MixColors { count } => {
-            let player = &state.players[player_index];
-            let (mixes, mix_count) = two_step_heuristic_mix_seq(&player.color_wheel, count, &cache, params, rng);
             state.players[player_index].drafted_cards.remove(card_id);
             state.destroyed_pile.insert(card_id);
-            for i in 0..mix_count {
-                let (a, b) = mixes[i];
-                perform_mix_unchecked(&mut state.players[player_index].color_wheel, a, b);
-            }
+            two_step_heuristic_mixes(&mut state.players[player_index].color_wheel, count, &cache, params, rng);
         }
         Ability::Sell => {
             if let Some(sell_card_id) = cache.best_affordable_id {
@@ -1186,7 +1158,7 @@ pub fn apply_heuristic_rollout_step<R: Rng>(state: &mut GameState, heuristic_dra
 
                     // Score each workshop card and pick the top N
                     let workshop = state.players[player_index].workshop_cards;
-                    let mut scored: [(u8, u32); 16] = [(0, 0); 16];
+                    let mut scored: [(CardId, u32); 16] = [(0, 0); 16];
                     let mut scored_count = 0usize;
                     for id in workshop.iter() {
                         let card = state.card_lookup[id as usize];
@@ -1237,7 +1209,7 @@ pub fn apply_heuristic_rollout_step<R: Rng>(state: &mut GameState, heuristic_dra
 
                     // Pick the workshop-area card whose ability is most useful to activate.
                     // Destroying a workshop-area card activates its ability (see resolve_destroy_cards).
-                    let mut best_id: Option<u8> = None;
+                    let mut best_id: Option<CardId> = None;
                     let mut best_score = 0u32;
                     for id in area.iter() {
                         let card = state.card_lookup[id as usize];
@@ -1256,15 +1228,7 @@ pub fn apply_heuristic_rollout_step<R: Rng>(state: &mut GameState, heuristic_dra
                 }
                 Some(Ability::MixColors { count }) => {
                     let remaining_mixes = *count;
-                    let (mixes, mix_count) = two_step_heuristic_mix_seq(&state.players[player_index].color_wheel, remaining_mixes, &cache, params, rng);
-                    for i in 0..mix_count {
-                        let (a, b) = mixes[i];
-                        perform_mix_unchecked(
-                            &mut state.players[player_index].color_wheel,
-                            a,
-                            b,
-                        );
-                    }
+                    two_step_heuristic_mixes(&mut state.players[player_index].color_wheel, remaining_mixes, &cache, params, rng);
                     if let GamePhase::Action { ref mut action_state } = state.phase {
                         action_state.ability_stack.pop();
                     }
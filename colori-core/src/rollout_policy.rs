@@ -0,0 +1,267 @@
+//! Policies for the simulation phase of ISMCTS.
+//!
+//! A policy advances a determinized state towards the end of the game. The
+//! built-in policies are selected by a [`RolloutPolicyKind`] variant, which
+//! variant files can name. Any other [`RolloutPolicy`] can be plugged into
+//! `MctsConfig` without touching this module by wrapping it in
+//! [`RolloutPolicyKind::Custom`]; such configs cannot be serialized.
+
+use crate::colori_game::{apply_choice_to_state, enumerate_choices};
+use crate::rollout::{apply_heuristic_rollout_step, apply_rollout_step};
+use crate::scoring::{heuristic_score, CardHeuristicTable, HeuristicParams};
+use crate::types::*;
+use rand::Rng;
+use rand::RngExt;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+pub trait RolloutPolicy: Send + Sync {
+    /// Advances `state` by at least one decision. May play out a whole draft
+    /// in one step. Must not be called once the game is over.
+    fn step(&self, state: &mut GameState, params: &HeuristicParams, card_table: &CardHeuristicTable, rng: &mut dyn Rng);
+}
+
+/// Uniformly random actions.
+pub struct RandomRollout {
+    pub heuristic_draft: bool,
+}
+
+impl RandomRollout {
+    pub fn play<R: Rng>(&self, state: &mut GameState, params: &HeuristicParams, rng: &mut R) {
+        apply_rollout_step(state, self.heuristic_draft, params, rng);
+    }
+}
+
+impl RolloutPolicy for RandomRollout {
+    fn step(&self, state: &mut GameState, params: &HeuristicParams, _card_table: &CardHeuristicTable, mut rng: &mut dyn Rng) {
+        self.play(state, params, &mut rng);
+    }
+}
+
+/// The hand-tuned policy driven by the `rollout_*` heuristic params.
+pub struct HeuristicRollout {
+    pub heuristic_draft: bool,
+}
+
+impl HeuristicRollout {
+    pub fn play<R: Rng>(&self, state: &mut GameState, params: &HeuristicParams, rng: &mut R) {
+        apply_heuristic_rollout_step(state, self.heuristic_draft, params, rng);
+    }
+}
+
+impl RolloutPolicy for HeuristicRollout {
+    fn step(&self, state: &mut GameState, params: &HeuristicParams, _card_table: &CardHeuristicTable, mut rng: &mut dyn Rng) {
+        self.play(state, params, &mut rng);
+    }
+}
+
+/// One-ply lookahead: in the action phase, plays the choice that leaves the
+/// acting player with the best `heuristic_score`, or a random choice with
+/// probability `epsilon`. Drafts are played randomly.
+pub struct GreedyRollout {
+    pub epsilon: f64,
+}
+
+impl GreedyRollout {
+    pub fn play<R: Rng>(&self, state: &mut GameState, params: &HeuristicParams, card_table: &CardHeuristicTable, rng: &mut R) {
+        let player_index = match &state.phase {
+            GamePhase::Action { action_state } => action_state.current_player_index,
+            _ => {
+                apply_rollout_step(state, false, params, rng);
+                return;
+            }
+        };
+
+        let choices = enumerate_choices(state);
+        let idx = if choices.len() == 1 || rng.random_bool(self.epsilon) {
+            rng.random_range(0..choices.len())
+        } else {
+            let mut best_idx = 0;
+            let mut best_score = f64::NEG_INFINITY;
+            for (i, choice) in choices.iter().enumerate() {
+                let mut next = state.clone();
+                apply_choice_to_state(&mut next, choice, rng);
+                let score = heuristic_score(
+                    &next.players[player_index],
+                    &next.sell_card_display,
+                    &next.card_lookup,
+                    params,
+                    card_table,
+                );
+                if score > best_score {
+                    best_score = score;
+                    best_idx = i;
+                }
+            }
+            best_idx
+        };
+        apply_choice_to_state(state, &choices[idx], rng);
+    }
+}
+
+impl RolloutPolicy for GreedyRollout {
+    fn step(&self, state: &mut GameState, params: &HeuristicParams, card_table: &CardHeuristicTable, mut rng: &mut dyn Rng) {
+        self.play(state, params, card_table, &mut rng);
+    }
+}
+
+/// A policy defined outside this module. Two of these compare equal only if
+/// they share the same allocation.
+#[derive(Clone)]
+pub struct CustomRollout(pub Arc<dyn RolloutPolicy>);
+
+impl fmt::Debug for CustomRollout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomRollout")
+    }
+}
+
+impl PartialEq for CustomRollout {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Serializable selector for the built-in rollout policies, plus a
+/// non-serializable slot for custom ones.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RolloutPolicyKind {
+    Random {
+        #[serde(default, rename = "heuristicDraft")]
+        heuristic_draft: bool,
+    },
+    Heuristic {
+        #[serde(default, rename = "heuristicDraft")]
+        heuristic_draft: bool,
+    },
+    Greedy {
+        #[serde(default = "default_greedy_epsilon")]
+        epsilon: f64,
+    },
+    #[serde(skip)]
+    Custom(CustomRollout),
+}
+
+fn default_greedy_epsilon() -> f64 {
+    0.1
+}
+
+impl RolloutPolicyKind {
+    /// The policy selected by the older `heuristicRollout` / `heuristicDraft`
+    /// config switches.
+    pub fn from_flags(heuristic_rollout: bool, heuristic_draft: bool) -> Self {
+        if heuristic_rollout {
+            RolloutPolicyKind::Heuristic { heuristic_draft }
+        } else {
+            RolloutPolicyKind::Random { heuristic_draft }
+        }
+    }
+
+    /// Resolves the policy named by a config that may use either the
+    /// `rolloutPolicy` field or the older flags, but not both.
+    pub fn resolve(
+        rollout_policy: Option<Self>,
+        heuristic_rollout: Option<bool>,
+        heuristic_draft: Option<bool>,
+    ) -> Result<Self, &'static str> {
+        match rollout_policy {
+            Some(_) if heuristic_rollout.is_some() || heuristic_draft.is_some() => {
+                Err("rolloutPolicy cannot be combined with heuristicRollout or heuristicDraft")
+            }
+            Some(policy) => Ok(policy),
+            None => Ok(Self::from_flags(heuristic_rollout.unwrap_or(true), heuristic_draft.unwrap_or(false))),
+        }
+    }
+
+    /// Steps the selected policy. The built-in policies keep a concrete
+    /// `rng`; only custom ones go through the object-safe trait.
+    pub fn play<R: Rng>(&self, state: &mut GameState, params: &HeuristicParams, card_table: &CardHeuristicTable, rng: &mut R) {
+        match self {
+            RolloutPolicyKind::Random { heuristic_draft } => {
+                RandomRollout { heuristic_draft: *heuristic_draft }.play(state, params, rng)
+            }
+            RolloutPolicyKind::Heuristic { heuristic_draft } => {
+                HeuristicRollout { heuristic_draft: *heuristic_draft }.play(state, params, rng)
+            }
+            RolloutPolicyKind::Greedy { epsilon } => GreedyRollout { epsilon: *epsilon }.play(state, params, card_table, rng),
+            RolloutPolicyKind::Custom(custom) => custom.0.step(state, params, card_table, rng),
+        }
+    }
+}
+
+impl RolloutPolicy for RolloutPolicyKind {
+    fn step(&self, state: &mut GameState, params: &HeuristicParams, card_table: &CardHeuristicTable, mut rng: &mut dyn Rng) {
+        self.play(state, params, card_table, &mut rng);
+    }
+}
+
+impl Default for RolloutPolicyKind {
+    fn default() -> Self {
+        RolloutPolicyKind::Heuristic { heuristic_draft: false }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw_phase::execute_draw_phase;
+    use crate::setup::create_initial_game_state;
+    use rand::SeedableRng;
+    use wyrand::WyRand;
+
+    fn test_heuristic_params() -> HeuristicParams {
+        const PARAMS_JSON: &str = include_str!("../../genetic-algorithm/batch-lki08w-gen-32.json");
+        serde_json::from_str(PARAMS_JSON).expect("Failed to parse test heuristic params")
+    }
+
+    fn play_out(policy: &dyn RolloutPolicy, params: &HeuristicParams, card_table: &CardHeuristicTable, num_players: usize, seed: u64) {
+        let mut rng = WyRand::seed_from_u64(seed);
+        let mut state = create_initial_game_state(num_players, &vec![true; num_players], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        for _ in 0..5000 {
+            if matches!(state.phase, GamePhase::GameOver) {
+                return;
+            }
+            policy.step(&mut state, params, card_table, &mut rng);
+        }
+        panic!("seed={seed}, players={num_players}: rollout did not finish");
+    }
+
+    #[test]
+    fn test_builtin_policies_finish_games() {
+        let params = test_heuristic_params();
+        let card_table = CardHeuristicTable::new(&params);
+        for num_players in 2..=4 {
+            for seed in 0..2 {
+                play_out(&RandomRollout { heuristic_draft: false }, &params, &card_table, num_players, seed);
+                play_out(&RandomRollout { heuristic_draft: true }, &params, &card_table, num_players, seed);
+                play_out(&HeuristicRollout { heuristic_draft: true }, &params, &card_table, num_players, seed);
+                play_out(&GreedyRollout { epsilon: 0.1 }, &params, &card_table, num_players, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_policy_kind_json() {
+        let kind: RolloutPolicyKind = serde_json::from_str(r#"{"type":"random","heuristicDraft":true}"#).unwrap();
+        assert_eq!(kind, RolloutPolicyKind::Random { heuristic_draft: true });
+        let kind: RolloutPolicyKind = serde_json::from_str(r#"{"type":"greedy"}"#).unwrap();
+        assert_eq!(kind, RolloutPolicyKind::Greedy { epsilon: 0.1 });
+        assert_eq!(RolloutPolicyKind::from_flags(true, false), RolloutPolicyKind::default());
+    }
+
+    #[test]
+    fn test_resolve_rejects_mixed_settings() {
+        let greedy = RolloutPolicyKind::Greedy { epsilon: 0.1 };
+        assert_eq!(RolloutPolicyKind::resolve(Some(greedy.clone()), None, None), Ok(greedy.clone()));
+        assert_eq!(RolloutPolicyKind::resolve(None, None, None), Ok(RolloutPolicyKind::default()));
+        assert_eq!(
+            RolloutPolicyKind::resolve(None, Some(false), Some(true)),
+            Ok(RolloutPolicyKind::Random { heuristic_draft: true })
+        );
+        assert!(RolloutPolicyKind::resolve(Some(greedy.clone()), Some(true), None).is_err());
+        assert!(RolloutPolicyKind::resolve(Some(greedy), None, Some(false)).is_err());
+    }
+}
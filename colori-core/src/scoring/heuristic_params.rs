@@ -19,6 +19,9 @@ pub struct HeuristicParams {
     pub dual_material_quality: f64,
     pub sell_card_material_alignment: f64,
     pub sell_card_color_alignment: f64,
+    /// Weight on the best ducats-per-step ratio over the sell card display,
+    /// where steps are the mixes, primaries and material still needed.
+    pub sell_card_reach_weight: f64,
     pub heuristic_round_threshold: u32,
     pub heuristic_lookahead: u32,
     pub alum_quality: f64,
@@ -92,6 +95,8 @@ struct HeuristicParamsRaw {
     sell_card_material_alignment: f64,
     #[serde(alias = "buyerColorWeight")]
     sell_card_color_alignment: f64,
+    #[serde(default)]
+    sell_card_reach_weight: f64,
     heuristic_round_threshold: u32,
     heuristic_lookahead: u32,
     alum_quality: f64,
@@ -158,6 +163,7 @@ impl From<HeuristicParamsRaw> for HeuristicParams {
             dual_material_quality: raw.dual_material_quality,
             sell_card_material_alignment: raw.sell_card_material_alignment,
             sell_card_color_alignment: raw.sell_card_color_alignment,
+            sell_card_reach_weight: raw.sell_card_reach_weight,
             heuristic_round_threshold: raw.heuristic_round_threshold,
             heuristic_lookahead: raw.heuristic_lookahead,
             alum_quality: raw.alum_quality,
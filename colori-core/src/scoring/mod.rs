@@ -2,27 +2,10 @@ mod heuristic_params;
 
 pub use heuristic_params::HeuristicParams;
 
-use crate::colors::{PRIMARIES, SECONDARIES, TERTIARIES};
+use crate::colors::{cheapest_mix_plan, mix_distance, PRIMARIES, SECONDARIES, TERTIARIES};
 use crate::fixed_vec::FixedVec;
 use crate::types::*;
-
-const ALL_CARDS: [Card; 45] = [
-    Card::BasicRed, Card::BasicYellow, Card::BasicBlue,
-    Card::Lac, Card::Brazilwood, Card::Pomegranate,
-    Card::Sumac, Card::Elderberry, Card::Turnsole,
-    Card::Madder, Card::Turmeric, Card::DyersGreenweed,
-    Card::Verdigris, Card::Orchil, Card::Logwood,
-    Card::VermilionDye, Card::Saffron, Card::PersianBerries,
-    Card::Azurite, Card::IndigoDye, Card::Cochineal,
-    Card::StarterCeramics, Card::StarterPaintings, Card::StarterTextiles,
-    Card::TerraCotta, Card::OchreWare, Card::CobaltWare,
-    Card::CinnabarCanvas, Card::OrpimentCanvas, Card::UltramarineCanvas,
-    Card::AlizarinFabric, Card::FusticFabric, Card::PastelFabric,
-    Card::ClayCanvas, Card::ClayFabric, Card::CanvasFabric,
-    Card::Alum, Card::CreamOfTartar, Card::GumArabic,
-    Card::Potash, Card::Vinegar, Card::Chalk,
-    Card::LinseedOil, Card::Lye, Card::SalAmmoniac,
-];
+use serde::Serialize;
 
 pub struct CardHeuristicTable {
     quality: [f64; 45],
@@ -130,7 +113,7 @@ fn card_quality(card: Card, params: &HeuristicParams) -> f64 {
 pub fn heuristic_score(
     player: &PlayerState,
     sell_card_display: &FixedVec<SellCardInstance, MAX_SELL_CARD_DISPLAY>,
-    card_lookup: &[Card; 256],
+    card_lookup: &[Card; MAX_CARD_INSTANCES],
     params: &HeuristicParams,
     card_table: &CardHeuristicTable,
 ) -> f64 {
@@ -189,7 +172,58 @@ pub fn heuristic_score(
         best_alignment = best_alignment.max(alignment);
     }
 
+    let mut best_reach = 0.0f64;
+    if params.sell_card_reach_weight != 0.0 {
+        for bi in sell_card_display.iter() {
+            let sell_card = bi.sell_card;
+            let (missing_primaries, mixes) = mix_distance(&player.color_wheel, sell_card.color_cost());
+            let missing_material = (player.materials.get(sell_card.required_material()) == 0) as u32;
+            let steps = missing_primaries + mixes + missing_material;
+            best_reach = best_reach.max(sell_card.ducats() as f64 / (steps + 1) as f64);
+        }
+    }
+
     score + color_score + material_score + deck_quality + deck_thinning_bonus + best_alignment
+        + params.sell_card_reach_weight * best_reach
+}
+
+/// How far a player is from buying one displayed sell card.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellCardReach {
+    pub sell_card_instance_id: u32,
+    /// Mixes to perform, in order, once `missing_primaries` have been gained.
+    pub mixes: Vec<(Color, Color)>,
+    pub missing_primaries: Vec<Color>,
+    pub missing_material: Option<MaterialType>,
+}
+
+impl SellCardReach {
+    pub fn is_affordable(&self) -> bool {
+        self.mixes.is_empty() && self.missing_primaries.is_empty() && self.missing_material.is_none()
+    }
+}
+
+/// For each displayed sell card, the fewest mixes and missing primaries or
+/// material between the player and buying it.
+pub fn sell_card_reachability(
+    color_wheel: &ColorWheel,
+    materials: &Materials,
+    sell_card_display: &FixedVec<SellCardInstance, MAX_SELL_CARD_DISPLAY>,
+) -> Vec<SellCardReach> {
+    sell_card_display
+        .iter()
+        .map(|bi| {
+            let plan = cheapest_mix_plan(color_wheel, bi.sell_card.color_cost());
+            let material = bi.sell_card.required_material();
+            SellCardReach {
+                sell_card_instance_id: bi.instance_id,
+                mixes: plan.mixes,
+                missing_primaries: plan.missing_primaries,
+                missing_material: (materials.get(material) == 0).then_some(material),
+            }
+        })
+        .collect()
 }
 
 /// Compute heuristic rewards for truncated early-game rollouts.
@@ -198,7 +232,7 @@ pub fn heuristic_score(
 pub fn compute_heuristic_rewards(
     players: &FixedVec<PlayerState, MAX_PLAYERS>,
     sell_card_display: &FixedVec<SellCardInstance, MAX_SELL_CARD_DISPLAY>,
-    card_lookup: &[Card; 256],
+    card_lookup: &[Card; MAX_CARD_INSTANCES],
     params: &HeuristicParams,
     card_table: &CardHeuristicTable,
 ) -> [f64; MAX_PLAYERS] {
@@ -308,4 +342,28 @@ mod tests {
         assert_eq!(rewards[0], 0.5);
         assert_eq!(rewards[1], 0.5);
     }
+
+    #[test]
+    fn test_sell_card_reachability() {
+        use crate::colors::VALID_MIX_PAIRS;
+
+        let mut player = make_player(0, &[], [2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        player.materials.increment(MaterialType::Ceramics);
+        let mut display = FixedVec::new();
+        display.push(SellCardInstance { instance_id: 7, sell_card: SellCard::Ceramics3VermilionRed });
+        let reach = sell_card_reachability(&player.color_wheel, &player.materials, &display);
+        assert_eq!(reach.len(), 1);
+        assert_eq!(reach[0].sell_card_instance_id, 7);
+        // Red + Yellow = Orange, Red + Orange = Vermilion, and one more Red to pay.
+        assert_eq!(reach[0].mixes, vec![(Color::Red, Color::Yellow), (Color::Red, Color::Orange)]);
+        assert_eq!(reach[0].missing_primaries, vec![Color::Red]);
+        assert_eq!(reach[0].missing_material, None);
+        assert!(reach[0].mixes.iter().all(|m| VALID_MIX_PAIRS.contains(m)));
+
+        player.color_wheel.increment(Color::Red);
+        let reach = sell_card_reachability(&player.color_wheel, &Materials::new(), &display);
+        assert!(reach[0].missing_primaries.is_empty());
+        assert_eq!(reach[0].missing_material, Some(MaterialType::Ceramics));
+        assert!(!reach[0].is_affordable());
+    }
 }
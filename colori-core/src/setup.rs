@@ -5,25 +5,28 @@ use crate::unordered_cards::{set_sell_card_registry, set_card_registry, Unordere
 use rand::Rng;
 use smallvec::SmallVec;
 use std::cell::Cell;
+use std::sync::Arc;
 
 thread_local! {
     static NEXT_CARD_ID: Cell<u32> = const { Cell::new(0) };
     static NEXT_SELL_CARD_ID: Cell<u32> = const { Cell::new(0) };
 }
 
-fn next_card_id() -> u8 {
+fn next_card_id() -> CardId {
     NEXT_CARD_ID.with(|c| {
         let id = c.get();
+        assert!((id as usize) < MAX_CARD_INSTANCES, "more than {} instances in one game", MAX_CARD_INSTANCES);
         c.set(id + 1);
-        id as u8
+        id as CardId
     })
 }
 
-fn next_sell_card_id() -> u8 {
+fn next_sell_card_id() -> CardId {
     NEXT_SELL_CARD_ID.with(|c| {
         let id = c.get();
+        assert!((id as usize) < MAX_CARD_INSTANCES, "more than {} instances in one game", MAX_CARD_INSTANCES);
         c.set(id + 1);
-        id as u8
+        id as CardId
     })
 }
 
@@ -35,8 +38,8 @@ fn reset_id_counters() {
 pub fn create_initial_game_state<R: Rng>(num_players: usize, ai_players: &[bool], rng: &mut R) -> GameState {
     reset_id_counters();
 
-    let mut card_lookup = [Card::BasicRed; 256];
-    let mut sell_card_lookup = [SellCard::Textiles2Vermilion; 256];
+    let mut card_lookup = [Card::BasicRed; MAX_CARD_INSTANCES];
+    let mut sell_card_lookup = [SellCard::Textiles2Vermilion; MAX_CARD_INSTANCES];
 
     // Build each player's starting state
     let players: FixedVec<PlayerState, MAX_PLAYERS> = (0..num_players)
@@ -132,8 +135,8 @@ pub fn create_initial_game_state<R: Rng>(num_players: usize, ai_players: &[bool]
         round: 1,
         max_rounds: 20,
         ai_players: FixedVec::from_slice(ai_players),
-        card_lookup,
-        sell_card_lookup,
+        card_lookup: Arc::new(card_lookup),
+        sell_card_lookup: Arc::new(sell_card_lookup),
         draw_log: None,
         force_max_workshop: true,
     }
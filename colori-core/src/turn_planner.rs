@@ -0,0 +1,556 @@
+//! Exhaustive search over the rest of the current player's action turn.
+//!
+//! Within a turn the only hidden information is which cards a `DrawCards`
+//! ability draws and which sell card is revealed after a sale. The planner
+//! takes the expectation over those outcomes and the maximum over the
+//! player's choices (expectimax), with transpositions shared through a memo.
+//!
+//! Turns with several mixes and a well-stocked color wheel can reach millions
+//! of distinct states, so every search runs under a state budget.
+
+use crate::action_phase::end_player_turn;
+use crate::colori_game::{apply_choice_to_state, enumerate_choices};
+use crate::game_log::{DrawEvent, DrawLog};
+use crate::scoring::{calculate_score, heuristic_score, CardHeuristicTable, HeuristicParams};
+use crate::types::*;
+use crate::unordered_cards::{UnorderedCards, UnorderedSellCards};
+use rand::Rng;
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+
+/// What the planner maximizes, measured once the turn ends.
+pub enum TurnObjective<'a> {
+    /// The player's score (ducats plus completed sell cards).
+    Score,
+    /// The player's `heuristic_score`.
+    Heuristic {
+        params: &'a HeuristicParams,
+        card_table: &'a CardHeuristicTable,
+    },
+}
+
+pub struct TurnPlan {
+    /// Best choices from the given state, up to and including `EndTurn` or
+    /// the first choice with a random outcome, whichever comes first. Plan
+    /// again once a draw has resolved.
+    pub choices: Vec<Choice>,
+    /// Expected objective at the end of the turn under optimal play.
+    pub expected_value: f64,
+    /// Distinct states searched.
+    pub states_searched: usize,
+}
+
+/// Plans the rest of the current player's turn. Returns None outside the
+/// action phase, or if the plan needs more than `max_states` distinct states.
+pub fn plan_turn<R: Rng>(
+    state: &GameState,
+    objective: &TurnObjective,
+    max_states: usize,
+    rng: &mut R,
+) -> Option<TurnPlan> {
+    let player_index = match &state.phase {
+        GamePhase::Action { action_state } => action_state.current_player_index,
+        _ => return None,
+    };
+    let mut root = state.clone();
+    root.draw_log = None;
+
+    let mut planner = TurnPlanner {
+        player_index,
+        objective,
+        memo: FxHashMap::default(),
+        max_states,
+        rng,
+    };
+    let expected_value = planner.value(&root)?;
+
+    let mut choices = Vec::new();
+    let mut current = root;
+    loop {
+        let choice = planner.memo[&TurnKey::new(&current, player_index)].1.clone();
+        let is_end_turn = matches!(choice, Choice::EndTurn);
+        choices.push(choice);
+        if is_end_turn {
+            break;
+        }
+        let mut outcomes = planner.apply(&current, choices.last().unwrap());
+        if outcomes.len() > 1 {
+            break;
+        }
+        current = outcomes.pop().unwrap().1;
+    }
+
+    Some(TurnPlan {
+        choices,
+        expected_value,
+        states_searched: planner.memo.len(),
+    })
+}
+
+struct TurnPlanner<'a, 'o, R> {
+    player_index: usize,
+    objective: &'a TurnObjective<'o>,
+    memo: FxHashMap<TurnKey, (f64, Choice)>,
+    max_states: usize,
+    rng: &'a mut R,
+}
+
+impl<R: Rng> TurnPlanner<'_, '_, R> {
+    /// Best expected objective from `state`, or None once the state budget
+    /// runs out.
+    fn value(&mut self, state: &GameState) -> Option<f64> {
+        let key = TurnKey::new(state, self.player_index);
+        if let Some((value, _)) = self.memo.get(&key) {
+            return Some(*value);
+        }
+        if self.memo.len() >= self.max_states {
+            return None;
+        }
+
+        let mut best: Option<(f64, Choice)> = None;
+        for choice in enumerate_choices(state) {
+            let value = if matches!(choice, Choice::EndTurn) {
+                self.end_of_turn_value(state)
+            } else {
+                let mut expected = 0.0;
+                for (probability, next) in &self.apply(state, &choice) {
+                    expected += probability * self.value(next)?;
+                }
+                expected
+            };
+            if best.as_ref().is_none_or(|(best_value, _)| value > *best_value) {
+                best = Some((value, choice));
+            }
+        }
+
+        let (value, choice) = best.expect("No legal choices during action phase");
+        self.memo.insert(key, (value, choice));
+        Some(value)
+    }
+
+    fn end_of_turn_value(&mut self, state: &GameState) -> f64 {
+        let mut state = state.clone();
+        end_player_turn(&mut state, self.rng);
+        let player = &state.players[self.player_index];
+        match self.objective {
+            TurnObjective::Score => calculate_score(player) as f64,
+            TurnObjective::Heuristic { params, card_table } => heuristic_score(
+                player,
+                &state.sell_card_display,
+                &state.card_lookup,
+                params,
+                card_table,
+            ),
+        }
+    }
+
+    /// Every distinct result of applying `choice`, with its probability. When
+    /// something was drawn, the choice is replayed once per outcome with the
+    /// drawn cards arranged to be the only ones available.
+    fn apply(&mut self, state: &GameState, choice: &Choice) -> Vec<(f64, GameState)> {
+        let mut next = state.clone();
+        next.draw_log = Some(DrawLog::Recording(Vec::new()));
+        apply_choice_to_state(&mut next, choice, self.rng);
+        let Some(DrawLog::Recording(events)) = next.draw_log.take() else {
+            unreachable!();
+        };
+        if events.is_empty() {
+            return vec![(1.0, next)];
+        }
+
+        let mut drawn = 0;
+        let mut revealed = false;
+        for event in &events {
+            match event {
+                DrawEvent::PlayerDeckDraw { cards, .. } => drawn += cards.len() as u32,
+                DrawEvent::SellCardReveal { .. } => revealed = true,
+                _ => {}
+            }
+        }
+
+        let player = &state.players[self.player_index];
+        let from_discard = drawn > player.deck.len();
+        let (source, count) = if from_discard {
+            (player.discard, drawn - player.deck.len())
+        } else {
+            (player.deck, drawn)
+        };
+        let draws = card_outcomes(source, count, &state.card_lookup);
+        let reveals = if revealed {
+            sell_card_outcomes(state.sell_card_deck, &state.sell_card_lookup)
+        } else {
+            vec![(1.0, UnorderedSellCards::new())]
+        };
+
+        let mut outcomes = Vec::with_capacity(draws.len() * reveals.len());
+        for &(draw_probability, picked) in &draws {
+            for &(reveal_probability, reveal) in &reveals {
+                let mut next = state.clone();
+                let rest = source.difference(picked);
+                let player = &mut next.players[self.player_index];
+                if from_discard {
+                    player.discard = picked;
+                } else {
+                    player.deck = picked;
+                }
+                let sell_rest = next.sell_card_deck.difference(reveal);
+                if !reveal.is_empty() {
+                    next.sell_card_deck = reveal;
+                }
+                apply_choice_to_state(&mut next, choice, self.rng);
+                let player = &mut next.players[self.player_index];
+                player.deck = player.deck.union(rest);
+                if !reveal.is_empty() {
+                    next.sell_card_deck = next.sell_card_deck.union(sell_rest);
+                }
+                outcomes.push((draw_probability * reveal_probability, next));
+            }
+        }
+        outcomes
+    }
+}
+
+/// Each distinct set of card types that drawing `count` cards from `source`
+/// can produce, as a representative set of ids with its probability.
+fn card_outcomes(source: UnorderedCards, count: u32, card_lookup: &[Card; MAX_CARD_INSTANCES]) -> Vec<(f64, UnorderedCards)> {
+    let mut groups: SmallVec<[(Card, SmallVec<[CardId; 8]>); 16]> = SmallVec::new();
+    for id in source.iter() {
+        let card = card_lookup[id as usize];
+        match groups.iter_mut().find(|(c, _)| *c == card) {
+            Some((_, ids)) => ids.push(id),
+            None => groups.push((card, SmallVec::from_slice(&[id]))),
+        }
+    }
+    let sizes: SmallVec<[u32; 16]> = groups.iter().map(|(_, ids)| ids.len() as u32).collect();
+    let total = binomial(source.len(), count);
+
+    let mut outcomes = Vec::new();
+    for_each_split(&sizes, count, &mut |picks| {
+        let mut picked = UnorderedCards::new();
+        let mut ways = 1.0;
+        for (i, &k) in picks.iter().enumerate() {
+            ways *= binomial(sizes[i], k);
+            for &id in &groups[i].1[..k as usize] {
+                picked.insert(id);
+            }
+        }
+        outcomes.push((ways / total, picked));
+    });
+    outcomes
+}
+
+/// Each distinct sell card that can be revealed from `deck`, with its
+/// probability.
+fn sell_card_outcomes(deck: UnorderedSellCards, sell_card_lookup: &[SellCard; MAX_CARD_INSTANCES]) -> Vec<(f64, UnorderedSellCards)> {
+    let mut groups: SmallVec<[(SellCard, CardId, u32); 16]> = SmallVec::new();
+    for id in deck.iter() {
+        let sell_card = sell_card_lookup[id as usize];
+        match groups.iter_mut().find(|(s, _, _)| *s == sell_card) {
+            Some((_, _, n)) => *n += 1,
+            None => groups.push((sell_card, id, 1)),
+        }
+    }
+    let total = deck.len() as f64;
+    groups
+        .into_iter()
+        .map(|(_, id, n)| {
+            let mut revealed = UnorderedSellCards::new();
+            revealed.insert(id);
+            (n as f64 / total, revealed)
+        })
+        .collect()
+}
+
+/// Calls `f` with every way of taking `count` items from groups of the given
+/// sizes.
+fn for_each_split(sizes: &[u32], count: u32, f: &mut impl FnMut(&[u32])) {
+    fn recurse(sizes: &[u32], remaining: u32, picks: &mut SmallVec<[u32; 16]>, f: &mut impl FnMut(&[u32])) {
+        let i = picks.len();
+        if i == sizes.len() {
+            if remaining == 0 {
+                f(picks);
+            }
+            return;
+        }
+        let left_after: u32 = sizes[i + 1..].iter().sum();
+        let lo = remaining.saturating_sub(left_after);
+        for k in lo..=sizes[i].min(remaining) {
+            picks.push(k);
+            recurse(sizes, remaining - k, picks, f);
+            picks.pop();
+        }
+    }
+    recurse(sizes, count, &mut SmallVec::new(), f);
+}
+
+fn binomial(n: u32, k: u32) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// Everything that can change during the player's turn.
+#[derive(PartialEq, Eq, Hash)]
+struct TurnKey {
+    cards: [UnorderedCards; 5],
+    color_wheel: [u32; NUM_COLORS],
+    materials: [u32; 3],
+    ducats: u32,
+    completed_sell_cards: SmallVec<[u32; 12]>,
+    ability_stack: AbilityStack,
+    sell_card_display: SmallVec<[u32; MAX_SELL_CARD_DISPLAY]>,
+    sell_card_deck: UnorderedSellCards,
+}
+
+impl TurnKey {
+    fn new(state: &GameState, player_index: usize) -> Self {
+        let player = &state.players[player_index];
+        let ability_stack = match &state.phase {
+            GamePhase::Action { action_state } => action_state.ability_stack.clone(),
+            _ => AbilityStack::new(),
+        };
+        let mut completed_sell_cards: SmallVec<[u32; 12]> =
+            player.completed_sell_cards.iter().map(|s| s.instance_id).collect();
+        completed_sell_cards.sort_unstable();
+        let mut sell_card_display: SmallVec<[u32; MAX_SELL_CARD_DISPLAY]> =
+            state.sell_card_display.iter().map(|s| s.instance_id).collect();
+        sell_card_display.sort_unstable();
+        TurnKey {
+            cards: [
+                player.deck,
+                player.discard,
+                player.workshopped_cards,
+                player.workshop_cards,
+                player.drafted_cards,
+            ],
+            color_wheel: player.color_wheel.counts,
+            materials: player.materials.counts,
+            ducats: player.ducats,
+            completed_sell_cards,
+            ability_stack,
+            sell_card_display,
+            sell_card_deck: state.sell_card_deck,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw_phase::execute_draw_phase;
+    use crate::ismcts::{ismcts, MctsConfig};
+    use crate::setup::create_initial_game_state;
+    use rand::{RngExt, SeedableRng};
+    use wyrand::WyRand;
+
+    const TEST_MAX_STATES: usize = 2_000;
+
+    fn test_heuristic_params() -> HeuristicParams {
+        const PARAMS_JSON: &str = include_str!("../../genetic-algorithm/batch-lki08w-gen-32.json");
+        serde_json::from_str(PARAMS_JSON).expect("Failed to parse test heuristic params")
+    }
+
+    /// States at the start of action-phase turns from random games.
+    fn turn_start_states(num_players: usize, seed: u64) -> Vec<GameState> {
+        let mut rng = WyRand::seed_from_u64(seed);
+        let mut state = create_initial_game_state(num_players, &vec![true; num_players], &mut rng);
+        execute_draw_phase(&mut state, &mut rng);
+        let mut states = Vec::new();
+        while !matches!(state.phase, GamePhase::GameOver) {
+            if let GamePhase::Action { action_state } = &state.phase {
+                if action_state.ability_stack.is_empty()
+                    && state.players[action_state.current_player_index].workshopped_cards.is_empty()
+                {
+                    states.push(state.clone());
+                }
+            }
+            let choices = enumerate_choices(&state);
+            let choice = &choices[rng.random_range(0..choices.len())];
+            apply_choice_to_state(&mut state, choice, &mut rng);
+        }
+        states
+    }
+
+    fn objective_value(state: &GameState, player_index: usize, objective: &TurnObjective) -> f64 {
+        let player = &state.players[player_index];
+        match objective {
+            TurnObjective::Score => calculate_score(player) as f64,
+            TurnObjective::Heuristic { params, card_table } => {
+                heuristic_score(player, &state.sell_card_display, &state.card_lookup, params, card_table)
+            }
+        }
+    }
+
+    /// Plays the rest of the turn randomly. Returns the objective at the end
+    /// of the turn, or None if anything was drawn along the way.
+    fn random_turn_without_draws(state: &GameState, objective: &TurnObjective, rng: &mut WyRand) -> Option<f64> {
+        let mut state = state.clone();
+        state.draw_log = Some(DrawLog::Recording(Vec::new()));
+        let player_index = match &state.phase {
+            GamePhase::Action { action_state } => action_state.current_player_index,
+            _ => unreachable!(),
+        };
+        loop {
+            let choices = enumerate_choices(&state);
+            let choice = &choices[rng.random_range(0..choices.len())];
+            if matches!(choice, Choice::EndTurn) {
+                end_player_turn(&mut state, rng);
+                break;
+            }
+            apply_choice_to_state(&mut state, choice, rng);
+        }
+        match &state.draw_log {
+            Some(DrawLog::Recording(events)) if events.is_empty() => {
+                Some(objective_value(&state, player_index, objective))
+            }
+            _ => None,
+        }
+    }
+
+    fn check_plan_beats_random_lines(num_players: usize, seeds: std::ops::Range<u64>, lines_per_state: usize) {
+        let params = test_heuristic_params();
+        let card_table = CardHeuristicTable::new(&params);
+        let objectives = [TurnObjective::Score, TurnObjective::Heuristic { params: &params, card_table: &card_table }];
+        let mut rng = WyRand::seed_from_u64(7);
+        let mut planned = 0;
+        for seed in seeds {
+            for state in turn_start_states(num_players, seed) {
+                for objective in &objectives {
+                    let Some(plan) = plan_turn(&state, objective, TEST_MAX_STATES, &mut rng) else {
+                        continue;
+                    };
+                    assert!(!plan.choices.is_empty());
+                    planned += 1;
+                    for _ in 0..lines_per_state {
+                        if let Some(value) = random_turn_without_draws(&state, objective, &mut rng) {
+                            assert!(value <= plan.expected_value + 1e-9, "seed={seed}: {value} > {}", plan.expected_value);
+                        }
+                    }
+                }
+            }
+        }
+        assert!(planned > 0);
+    }
+
+    #[test]
+    fn test_plan_is_at_least_as_good_as_any_deterministic_line() {
+        check_plan_beats_random_lines(2, 0..1, 5);
+    }
+
+    #[test]
+    #[ignore = "slow; run with --ignored"]
+    fn test_plan_is_at_least_as_good_as_any_deterministic_line_exhaustive() {
+        check_plan_beats_random_lines(3, 0..2, 10);
+    }
+
+    #[test]
+    fn test_deterministic_plan_achieves_expected_value() {
+        let mut rng = WyRand::seed_from_u64(11);
+        let mut checked = 0;
+        for seed in 0..3 {
+            for state in turn_start_states(2, seed) {
+                let Some(plan) = plan_turn(&state, &TurnObjective::Score, TEST_MAX_STATES, &mut rng) else {
+                    continue;
+                };
+                if !matches!(plan.choices.last(), Some(Choice::EndTurn)) {
+                    continue;
+                }
+                let player_index = match &state.phase {
+                    GamePhase::Action { action_state } => action_state.current_player_index,
+                    _ => unreachable!(),
+                };
+                let mut state = state.clone();
+                for choice in &plan.choices[..plan.choices.len() - 1] {
+                    apply_choice_to_state(&mut state, choice, &mut rng);
+                }
+                end_player_turn(&mut state, &mut rng);
+                assert_eq!(calculate_score(&state.players[player_index]) as f64, plan.expected_value);
+                checked += 1;
+            }
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn test_plan_outside_action_phase_is_none() {
+        let mut rng = WyRand::seed_from_u64(1);
+        let mut state = create_initial_game_state(2, &[true, true], &mut rng);
+        assert!(plan_turn(&state, &TurnObjective::Score, TEST_MAX_STATES, &mut rng).is_none());
+        execute_draw_phase(&mut state, &mut rng);
+        assert!(matches!(state.phase, GamePhase::Draft { .. }));
+        assert!(plan_turn(&state, &TurnObjective::Score, TEST_MAX_STATES, &mut rng).is_none());
+    }
+
+    /// In a one-player game that ends with this turn, the terminal reward
+    /// rises with the final score, so playing the turn out with ISMCTS should
+    /// reach the planner's best score wherever the best line has no draws.
+    /// Only turns whose best line is at most `max_plan_len` choices long are
+    /// checked.
+    fn check_ismcts_matches_planner(seeds: impl IntoIterator<Item = u64>, iterations: u32, max_plan_len: usize) {
+        let config = MctsConfig {
+            iterations,
+            exploration_constant: 0.05,
+            use_heuristic_eval: false,
+            early_termination: false,
+            ..MctsConfig::new(test_heuristic_params())
+        };
+        let mut rng = WyRand::seed_from_u64(13);
+        let mut checked = 0;
+        for seed in seeds {
+            for mut state in turn_start_states(1, seed) {
+                state.max_rounds = state.round;
+                let Some(plan) = plan_turn(&state, &TurnObjective::Score, TEST_MAX_STATES, &mut rng) else {
+                    continue;
+                };
+                if plan.choices.len() > max_plan_len
+                    || !matches!(plan.choices.last(), Some(Choice::EndTurn))
+                    || plan.expected_value <= calculate_score(&state.players[0]) as f64
+                {
+                    continue;
+                }
+                while !matches!(state.phase, GamePhase::GameOver) {
+                    let choice = ismcts(&state, 0, &config, None, None, &mut rng).choice;
+                    if matches!(choice, Choice::EndTurn) {
+                        end_player_turn(&mut state, &mut rng);
+                    } else {
+                        apply_choice_to_state(&mut state, &choice, &mut rng);
+                    }
+                }
+                let score = calculate_score(&state.players[0]) as f64;
+                assert!(score >= plan.expected_value, "seed={seed}: {score} < {}", plan.expected_value);
+                checked += 1;
+            }
+        }
+        assert!(checked > 0);
+    }
+
+    /// Seeds 11, 12 and 19 are the only ones in 0..20 with a last turn short
+    /// enough to check.
+    #[test]
+    fn test_ismcts_matches_planner_on_last_turn() {
+        check_ismcts_matches_planner([11, 12, 19], 500, 6);
+    }
+
+    #[test]
+    #[ignore = "slow; run with --ignored"]
+    fn test_ismcts_matches_planner_on_last_turn_exhaustive() {
+        check_ismcts_matches_planner(0..20, 3_000, usize::MAX);
+    }
+
+    #[test]
+    fn test_outcome_probabilities_sum_to_one() {
+        let mut rng = WyRand::seed_from_u64(3);
+        let state = create_initial_game_state(2, &[true, true], &mut rng);
+        let deck = state.players[0].deck;
+        for count in 0..=deck.len() {
+            let outcomes = card_outcomes(deck, count, &state.card_lookup);
+            let total: f64 = outcomes.iter().map(|(p, _)| p).sum();
+            assert!((total - 1.0).abs() < 1e-9);
+            assert!(outcomes.iter().all(|(_, picked)| picked.len() == count && picked.difference(deck).is_empty()));
+        }
+        let reveals = sell_card_outcomes(state.sell_card_deck, &state.sell_card_lookup);
+        let total: f64 = reveals.iter().map(|(p, _)| p).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
+use std::sync::Arc;
 
 use crate::fixed_vec::FixedVec;
 use crate::game_log::DrawLog;
@@ -10,6 +11,18 @@ pub type AbilityStack = SmallVec<[Ability; 4]>;
 pub const MAX_PLAYERS: usize = 4;
 pub const MAX_SELL_CARD_DISPLAY: usize = 6;
 
+/// Instance id of a card or sell card within a game. The `wide-card-ids`
+/// feature raises the limit from 256 to 512 instances per game.
+#[cfg(not(feature = "wide-card-ids"))]
+pub type CardId = u8;
+#[cfg(feature = "wide-card-ids")]
+pub type CardId = u16;
+
+#[cfg(not(feature = "wide-card-ids"))]
+pub const MAX_CARD_INSTANCES: usize = 256;
+#[cfg(feature = "wide-card-ids")]
+pub const MAX_CARD_INSTANCES: usize = 512;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Color {
     Red,
@@ -314,6 +327,25 @@ impl Card {
     }
 }
 
+/// Every card type, in discriminant order.
+pub const ALL_CARDS: [Card; 45] = [
+    Card::BasicRed, Card::BasicYellow, Card::BasicBlue,
+    Card::Lac, Card::Brazilwood, Card::Pomegranate,
+    Card::Sumac, Card::Elderberry, Card::Turnsole,
+    Card::Madder, Card::Turmeric, Card::DyersGreenweed,
+    Card::Verdigris, Card::Orchil, Card::Logwood,
+    Card::VermilionDye, Card::Saffron, Card::PersianBerries,
+    Card::Azurite, Card::IndigoDye, Card::Cochineal,
+    Card::StarterCeramics, Card::StarterPaintings, Card::StarterTextiles,
+    Card::TerraCotta, Card::OchreWare, Card::CobaltWare,
+    Card::CinnabarCanvas, Card::OrpimentCanvas, Card::UltramarineCanvas,
+    Card::AlizarinFabric, Card::FusticFabric, Card::PastelFabric,
+    Card::ClayCanvas, Card::ClayFabric, Card::CanvasFabric,
+    Card::Alum, Card::CreamOfTartar, Card::GumArabic,
+    Card::Potash, Card::Vinegar, Card::Chalk,
+    Card::LinseedOil, Card::Lye, Card::SalAmmoniac,
+];
+
 // ── SellCard enum (54 variants) ──
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -693,7 +725,7 @@ pub enum GamePhase {
     GameOver,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GameState {
     pub players: FixedVec<PlayerState, MAX_PLAYERS>,
@@ -706,26 +738,72 @@ pub struct GameState {
     #[serde(default = "default_max_rounds")]
     pub max_rounds: u32,
     pub ai_players: FixedVec<bool, MAX_PLAYERS>,
+    /// Card type of every instance id. Fixed for the whole game, so clones
+    /// share it instead of copying it.
     #[serde(skip, default = "default_card_lookup")]
-    pub card_lookup: [Card; 256],
+    pub card_lookup: Arc<[Card; MAX_CARD_INSTANCES]>,
+    /// Sell card of every sell card instance id, shared like `card_lookup`.
     #[serde(skip, default = "default_sell_card_lookup")]
-    pub sell_card_lookup: [SellCard; 256],
+    pub sell_card_lookup: Arc<[SellCard; MAX_CARD_INSTANCES]>,
     #[serde(skip)]
     pub draw_log: Option<DrawLog>,
     #[serde(skip)]
     pub force_max_workshop: bool,
 }
 
+impl Clone for GameState {
+    fn clone(&self) -> Self {
+        GameState {
+            players: self.players.clone(),
+            draft_deck: self.draft_deck,
+            destroyed_pile: self.destroyed_pile,
+            sell_card_deck: self.sell_card_deck,
+            sell_card_display: self.sell_card_display.clone(),
+            phase: self.phase.clone(),
+            round: self.round,
+            max_rounds: self.max_rounds,
+            ai_players: self.ai_players.clone(),
+            card_lookup: self.card_lookup.clone(),
+            sell_card_lookup: self.sell_card_lookup.clone(),
+            draw_log: self.draw_log.clone(),
+            force_max_workshop: self.force_max_workshop,
+        }
+    }
+
+    /// Clones field by field, so that ISMCTS determinizations reuse the
+    /// players' allocations and skip the lookups' refcounts when both states
+    /// already share them.
+    fn clone_from(&mut self, source: &Self) {
+        self.players.clone_from(&source.players);
+        self.draft_deck = source.draft_deck;
+        self.destroyed_pile = source.destroyed_pile;
+        self.sell_card_deck = source.sell_card_deck;
+        self.sell_card_display.clone_from(&source.sell_card_display);
+        self.phase.clone_from(&source.phase);
+        self.round = source.round;
+        self.max_rounds = source.max_rounds;
+        self.ai_players.clone_from(&source.ai_players);
+        if !Arc::ptr_eq(&self.card_lookup, &source.card_lookup) {
+            self.card_lookup = source.card_lookup.clone();
+        }
+        if !Arc::ptr_eq(&self.sell_card_lookup, &source.sell_card_lookup) {
+            self.sell_card_lookup = source.sell_card_lookup.clone();
+        }
+        self.draw_log.clone_from(&source.draw_log);
+        self.force_max_workshop = source.force_max_workshop;
+    }
+}
+
 fn default_max_rounds() -> u32 {
     20
 }
 
-fn default_card_lookup() -> [Card; 256] {
-    [Card::BasicRed; 256]
+fn default_card_lookup() -> Arc<[Card; MAX_CARD_INSTANCES]> {
+    Arc::new([Card::BasicRed; MAX_CARD_INSTANCES])
 }
 
-fn default_sell_card_lookup() -> [SellCard; 256] {
-    [SellCard::Textiles2Vermilion; 256]
+fn default_sell_card_lookup() -> Arc<[SellCard; MAX_CARD_INSTANCES]> {
+    Arc::new([SellCard::Textiles2Vermilion; MAX_CARD_INSTANCES])
 }
 
 // ── Choice ──
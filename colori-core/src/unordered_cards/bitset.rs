@@ -3,10 +3,15 @@ use std::marker::PhantomData;
 use rand::Rng;
 use rand::RngExt;
 
-const BINOM: [[u64; 10]; 257] = {
-    let mut table = [[0u64; 10]; 257];
+use crate::types::{CardId, MAX_CARD_INSTANCES};
+
+/// Number of `u128` limbs in the default-width `BitSet`.
+pub const CARD_LIMBS: usize = MAX_CARD_INSTANCES / 128;
+
+const BINOM: [[u64; 10]; MAX_CARD_INSTANCES + 1] = {
+    let mut table = [[0u64; 10]; MAX_CARD_INSTANCES + 1];
     let mut n = 0usize;
-    while n <= 256 {
+    while n <= MAX_CARD_INSTANCES {
         table[n][0] = 1;
         let mut k = 1usize;
         while k <= 9 && k <= n {
@@ -18,10 +23,10 @@ const BINOM: [[u64; 10]; 257] = {
     table
 };
 
-const BINOM_CUM: [[u64; 10]; 257] = {
-    let mut table = [[0u64; 10]; 257];
+const BINOM_CUM: [[u64; 10]; MAX_CARD_INSTANCES + 1] = {
+    let mut table = [[0u64; 10]; MAX_CARD_INSTANCES + 1];
     let mut n = 0usize;
-    while n <= 256 {
+    while n <= MAX_CARD_INSTANCES {
         table[n][0] = BINOM[n][0];
         let mut k = 1usize;
         while k <= 9 {
@@ -38,40 +43,51 @@ pub enum CardMarker {}
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SellCardMarker {}
 
+/// A set of instance ids backed by `LIMBS` 128-bit words. The default width
+/// covers every id up to `MAX_CARD_INSTANCES`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct BitSet<T>(pub [u128; 2], PhantomData<T>);
+pub struct BitSet<T, const LIMBS: usize = CARD_LIMBS>(pub [u128; LIMBS], PhantomData<T>);
 
 pub type UnorderedCards = BitSet<CardMarker>;
 pub type UnorderedSellCards = BitSet<SellCardMarker>;
 
-impl<T> Default for BitSet<T> {
+impl<T, const LIMBS: usize> Default for BitSet<T, LIMBS> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T> BitSet<T> {
+/// Clears the `k` lowest set bits of `val` and returns the position of the next one.
+#[inline]
+fn kth_bit(mut val: u128, k: u32) -> u32 {
+    for _ in 0..k {
+        val &= val - 1;
+    }
+    val.trailing_zeros()
+}
+
+impl<T, const LIMBS: usize> BitSet<T, LIMBS> {
     #[inline]
     pub fn new() -> Self {
-        BitSet([0; 2], PhantomData)
+        BitSet([0; LIMBS], PhantomData)
     }
 
     #[inline]
-    pub fn insert(&mut self, id: u8) {
+    pub fn insert(&mut self, id: CardId) {
         let limb = (id >> 7) as usize;
         let bit = id & 127;
         self.0[limb] |= 1u128 << bit;
     }
 
     #[inline]
-    pub fn remove(&mut self, id: u8) {
+    pub fn remove(&mut self, id: CardId) {
         let limb = (id >> 7) as usize;
         let bit = id & 127;
         self.0[limb] &= !(1u128 << bit);
     }
 
     #[inline]
-    pub fn contains(&self, id: u8) -> bool {
+    pub fn contains(&self, id: CardId) -> bool {
         let limb = (id >> 7) as usize;
         let bit = id & 127;
         (self.0[limb] >> bit) & 1 != 0
@@ -79,95 +95,81 @@ impl<T> BitSet<T> {
 
     #[inline]
     pub fn len(&self) -> u32 {
-        self.0[0].count_ones() + self.0[1].count_ones()
+        self.0.iter().map(|limb| limb.count_ones()).sum()
     }
 
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.0[0] == 0 && self.0[1] == 0
+        self.0.iter().all(|&limb| limb == 0)
     }
 
     #[inline]
-    pub fn union(self, other: Self) -> Self {
-        BitSet(
-            [self.0[0] | other.0[0], self.0[1] | other.0[1]],
-            PhantomData,
-        )
+    pub fn union(mut self, other: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a |= b;
+        }
+        self
     }
 
     #[inline]
-    pub fn intersection(self, other: Self) -> Self {
-        BitSet(
-            [self.0[0] & other.0[0], self.0[1] & other.0[1]],
-            PhantomData,
-        )
+    pub fn intersection(mut self, other: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a &= b;
+        }
+        self
     }
 
     #[inline]
-    pub fn difference(self, other: Self) -> Self {
-        BitSet(
-            [self.0[0] & !other.0[0], self.0[1] & !other.0[1]],
-            PhantomData,
-        )
+    pub fn difference(mut self, other: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a &= !b;
+        }
+        self
     }
 
     /// Returns the position of the lowest set bit, or None if empty.
     #[inline]
-    pub fn lowest_bit(&self) -> Option<u8> {
-        if self.0[0] != 0 {
-            Some(self.0[0].trailing_zeros() as u8)
-        } else if self.0[1] != 0 {
-            Some(128 + self.0[1].trailing_zeros() as u8)
-        } else {
-            None
+    pub fn lowest_bit(&self) -> Option<CardId> {
+        for (i, &limb) in self.0.iter().enumerate() {
+            if limb != 0 {
+                return Some((i * 128) as CardId + limb.trailing_zeros() as CardId);
+            }
         }
+        None
     }
 
+    /// Position of the `k`-th set bit (0-based) across all limbs. `k` must be
+    /// less than `len()`.
     #[inline]
-    pub fn pick_random<R: Rng>(&self, rng: &mut R) -> Option<u8> {
+    fn nth_set_bit(&self, mut k: u32) -> CardId {
+        for (i, &limb) in self.0.iter().enumerate() {
+            let count = limb.count_ones();
+            if k < count {
+                return (i * 128) as CardId + kth_bit(limb, k) as CardId;
+            }
+            k -= count;
+        }
+        unreachable!("nth_set_bit index out of range")
+    }
+
+    #[inline]
+    pub fn pick_random<R: Rng>(&self, rng: &mut R) -> Option<CardId> {
         let count = self.len();
         if count == 0 {
             return None;
         }
         let k = rng.random_range(0..count);
-        // Find the k-th set bit across both limbs
-        let lo_count = self.0[0].count_ones();
-        if k < lo_count {
-            let mut val = self.0[0];
-            for _ in 0..k {
-                val &= val - 1;
-            }
-            Some(val.trailing_zeros() as u8)
-        } else {
-            let mut val = self.0[1];
-            for _ in 0..(k - lo_count) {
-                val &= val - 1;
-            }
-            Some(128 + val.trailing_zeros() as u8)
-        }
+        Some(self.nth_set_bit(k))
     }
 
     #[inline]
-    pub fn draw<R: Rng>(&mut self, rng: &mut R) -> Option<u8> {
+    pub fn draw<R: Rng>(&mut self, rng: &mut R) -> Option<CardId> {
         let count = self.len();
         if count == 0 {
             return None;
         }
         let k = rng.random_range(0..count);
-        let lo_count = self.0[0].count_ones();
-        let pos = if k < lo_count {
-            let mut val = self.0[0];
-            for _ in 0..k {
-                val &= val - 1;
-            }
-            val.trailing_zeros() as u8
-        } else {
-            let mut val = self.0[1];
-            for _ in 0..(k - lo_count) {
-                val &= val - 1;
-            }
-            128 + val.trailing_zeros() as u8
-        };
+        let pos = self.nth_set_bit(k);
         self.remove(pos);
         Some(pos)
     }
@@ -180,7 +182,7 @@ impl<T> BitSet<T> {
         }
         if count >= n {
             let all = self.0;
-            self.0 = [0; 2];
+            self.0 = [0; LIMBS];
             return BitSet(all, PhantomData);
         }
         let mut selected = Self::new();
@@ -204,27 +206,11 @@ impl<T> BitSet<T> {
                 return Self::new();
             }
             // Find the (r-1)-th set bit
-            let k = (r - 1) as u32;
-            let lo_count = self.0[0].count_ones();
-            let pos;
-            let mut result = [0u128; 2];
-            if k < lo_count {
-                let mut val = self.0[0];
-                for _ in 0..k {
-                    val &= val - 1;
-                }
-                pos = val.trailing_zeros() as u8;
-                result[0] = 1u128 << pos;
-            } else {
-                let mut val = self.0[1];
-                for _ in 0..(k - lo_count) {
-                    val &= val - 1;
-                }
-                pos = 128 + val.trailing_zeros() as u8;
-                result[1] = 1u128 << (pos - 128);
-            }
+            let pos = self.nth_set_bit((r - 1) as u32);
             self.remove(pos);
-            return BitSet(result, PhantomData);
+            let mut result = Self::new();
+            result.insert(pos);
+            return result;
         }
         let n = self.len() as usize;
         let c = (max_count as usize).min(n);
@@ -234,8 +220,8 @@ impl<T> BitSet<T> {
         if c >= BINOM_CUM[0].len() {
             // Table doesn't cover this c value. Fall back to per-element
             // coin flips, which gives a uniform random subset when c >= n.
-            let mut selected = [0u128; 2];
-            for word in 0..2 {
+            let mut selected = [0u128; LIMBS];
+            for word in 0..LIMBS {
                 let mut bits = self.0[word];
                 while bits != 0 {
                     let pos = bits.trailing_zeros();
@@ -245,9 +231,7 @@ impl<T> BitSet<T> {
                     }
                 }
             }
-            self.0[0] &= !selected[0];
-            self.0[1] &= !selected[1];
-            return BitSet(selected, PhantomData);
+            return self.take(selected);
         }
         let total = BINOM_CUM[n][c];
         let r = rng.random_range(0..total);
@@ -265,87 +249,79 @@ impl<T> BitSet<T> {
         // Selection sampling for `size` elements
         let mut remaining = n as u32;
         let mut to_pick = size as u32;
-        let mut selected = [0u128; 2];
-
-        let mut bits0 = self.0[0];
-        while to_pick > 0 && bits0 != 0 {
-            if remaining == to_pick {
-                selected[0] |= bits0;
-                selected[1] = self.0[1];
-                self.0[0] &= !selected[0];
-                self.0[1] = 0;
-                return BitSet(selected, PhantomData);
-            }
-            let pos = bits0.trailing_zeros();
-            bits0 &= bits0 - 1;
-            if rng.random_range(0..remaining) < to_pick {
-                selected[0] |= 1u128 << pos;
-                to_pick -= 1;
-            }
-            remaining -= 1;
-        }
-        let mut bits1 = self.0[1];
-        while to_pick > 0 && bits1 != 0 {
-            if remaining == to_pick {
-                selected[1] |= bits1;
-                self.0[0] &= !selected[0];
-                self.0[1] &= !selected[1];
-                return BitSet(selected, PhantomData);
-            }
-            let pos = bits1.trailing_zeros();
-            bits1 &= bits1 - 1;
-            if rng.random_range(0..remaining) < to_pick {
-                selected[1] |= 1u128 << pos;
-                to_pick -= 1;
+        let mut selected = [0u128; LIMBS];
+
+        for word in 0..LIMBS {
+            let mut bits = self.0[word];
+            while to_pick > 0 && bits != 0 {
+                if remaining == to_pick {
+                    // Every element left must be picked.
+                    selected[word] |= bits;
+                    selected[word + 1..].copy_from_slice(&self.0[word + 1..]);
+                    return self.take(selected);
+                }
+                let pos = bits.trailing_zeros();
+                bits &= bits - 1;
+                if rng.random_range(0..remaining) < to_pick {
+                    selected[word] |= 1u128 << pos;
+                    to_pick -= 1;
+                }
+                remaining -= 1;
             }
-            remaining -= 1;
         }
 
-        self.0[0] &= !selected[0];
-        self.0[1] &= !selected[1];
-        BitSet(selected, PhantomData)
+        self.take(selected)
     }
 
+    /// Removes the bits in `selected` from `self` and returns them as a set.
     #[inline]
-    pub fn iter(self) -> BitIter {
-        BitIter {
-            lo: self.0[0],
-            hi: self.0[1],
+    fn take(&mut self, selected: [u128; LIMBS]) -> Self {
+        for (a, b) in self.0.iter_mut().zip(selected) {
+            *a &= !b;
         }
+        BitSet(selected, PhantomData)
+    }
+
+    #[inline]
+    pub fn iter(self) -> BitIter<LIMBS> {
+        BitIter { limbs: self.0, base: 0 }
     }
 }
 
-pub struct BitIter {
-    lo: u128,
-    hi: u128,
+pub struct BitIter<const LIMBS: usize = CARD_LIMBS> {
+    /// Unvisited bits, shifted down so that `limbs[0]` starts at id `base`.
+    limbs: [u128; LIMBS],
+    base: usize,
 }
 
-impl Iterator for BitIter {
-    type Item = u8;
+impl<const LIMBS: usize> Iterator for BitIter<LIMBS> {
+    type Item = CardId;
 
     #[inline]
-    fn next(&mut self) -> Option<u8> {
-        if self.lo != 0 {
-            let pos = self.lo.trailing_zeros() as u8;
-            self.lo &= self.lo - 1;
-            Some(pos)
-        } else if self.hi != 0 {
-            let pos = self.hi.trailing_zeros() as u8;
-            self.hi &= self.hi - 1;
-            Some(128 + pos)
-        } else {
-            None
+    fn next(&mut self) -> Option<CardId> {
+        loop {
+            let bits = self.limbs[0];
+            if bits != 0 {
+                self.limbs[0] = bits & (bits - 1);
+                return Some((self.base + bits.trailing_zeros() as usize) as CardId);
+            }
+            if self.base == (LIMBS - 1) * 128 {
+                return None;
+            }
+            self.limbs.copy_within(1.., 0);
+            self.limbs[LIMBS - 1] = 0;
+            self.base += 128;
         }
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let c = (self.lo.count_ones() + self.hi.count_ones()) as usize;
+        let c = self.limbs.iter().map(|limb| limb.count_ones() as usize).sum();
         (c, Some(c))
     }
 }
 
-impl ExactSizeIterator for BitIter {}
+impl<const LIMBS: usize> ExactSizeIterator for BitIter<LIMBS> {}
 
 #[cfg(test)]
 mod tests {
@@ -596,7 +572,7 @@ mod tests {
     #[test]
     fn test_iter_empty() {
         let s = UnorderedCards::new();
-        assert_eq!(s.iter().collect::<Vec<_>>(), Vec::<u8>::new());
+        assert_eq!(s.iter().collect::<Vec<_>>(), Vec::<CardId>::new());
     }
 
     #[test]
@@ -605,7 +581,7 @@ mod tests {
         s.insert(3);
         s.insert(7);
         s.insert(100);
-        let elems: Vec<u8> = s.iter().collect();
+        let elems: Vec<CardId> = s.iter().collect();
         assert_eq!(elems, vec![3, 7, 100]);
     }
 
@@ -616,7 +592,7 @@ mod tests {
         s.insert(127);
         s.insert(128);
         s.insert(200);
-        let elems: Vec<u8> = s.iter().collect();
+        let elems: Vec<CardId> = s.iter().collect();
         assert_eq!(elems, vec![50, 127, 128, 200]);
     }
 
@@ -626,7 +602,7 @@ mod tests {
         for i in 0..8 {
             s.insert(i);
         }
-        let elems: Vec<u8> = s.iter().collect();
+        let elems: Vec<CardId> = s.iter().collect();
         assert_eq!(elems, vec![0, 1, 2, 3, 4, 5, 6, 7]);
     }
 
@@ -750,7 +726,7 @@ mod tests {
     #[test]
     fn test_draw_all() {
         let mut s = UnorderedCards::new();
-        let elements: Vec<u8> = vec![5, 15, 25, 35, 45];
+        let elements: Vec<CardId> = vec![5, 15, 25, 35, 45];
         for &e in &elements {
             s.insert(e);
         }
@@ -826,7 +802,7 @@ mod tests {
         use std::collections::HashMap;
         let base = {
             let mut s = UnorderedCards::new();
-            for i in 0u8..5 {
+            for i in 0..5 {
                 s.insert(i);
             }
             s
@@ -892,7 +868,7 @@ mod tests {
     fn test_draw_up_to_can_return_zero() {
         let base = {
             let mut s = UnorderedCards::new();
-            for i in 0u8..5 {
+            for i in 0..5 {
                 s.insert(i);
             }
             s
@@ -1031,4 +1007,46 @@ mod tests {
             }
         }
     }
+
+    // ── Width ──
+
+    #[test]
+    fn test_wider_set_draws_like_default() {
+        let mut narrow = UnorderedCards::new();
+        let mut wide = BitSet::<CardMarker, { CARD_LIMBS + 2 }>::new();
+        for id in (0..250).step_by(3) {
+            narrow.insert(id);
+            wide.insert(id);
+        }
+        let mut rng_narrow = WyRand::seed_from_u64(9);
+        let mut rng_wide = WyRand::seed_from_u64(9);
+        for _ in 0..20 {
+            assert_eq!(narrow.pick_random(&mut rng_narrow), wide.pick_random(&mut rng_wide));
+            assert_eq!(narrow.draw(&mut rng_narrow), wide.draw(&mut rng_wide));
+            let a: Vec<CardId> = narrow.draw_up_to(4, &mut rng_narrow).iter().collect();
+            let b: Vec<CardId> = wide.draw_up_to(4, &mut rng_wide).iter().collect();
+            assert_eq!(a, b);
+        }
+        assert_eq!(narrow.iter().collect::<Vec<_>>(), wide.iter().collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "wide-card-ids")]
+    #[test]
+    fn test_wide_ids() {
+        let mut s = UnorderedCards::new();
+        s.insert(300);
+        s.insert(511);
+        s.insert(5);
+        assert_eq!(s.len(), 3);
+        assert!(s.contains(300) && s.contains(511));
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![5, 300, 511]);
+        s.remove(5);
+        assert_eq!(s.lowest_bit(), Some(300));
+
+        let mut rng = WyRand::seed_from_u64(3);
+        let drawn = s.draw_up_to(9, &mut rng);
+        assert!(drawn.union(s).contains(511));
+        assert_eq!(drawn.intersection(s).len(), 0);
+        assert_eq!(BINOM[MAX_CARD_INSTANCES][1], MAX_CARD_INSTANCES as u64);
+    }
 }
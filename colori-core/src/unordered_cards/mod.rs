@@ -2,7 +2,7 @@ mod bitset;
 pub(crate) mod registry;
 mod serde_impls;
 
-pub use bitset::{BitIter, BitSet, SellCardMarker, CardMarker, UnorderedSellCards, UnorderedCards};
+pub use bitset::{BitIter, BitSet, SellCardMarker, CardMarker, UnorderedSellCards, UnorderedCards, CARD_LIMBS};
 pub use registry::{
     get_sell_card_registry, get_card_registry, set_sell_card_registry, set_card_registry,
 };
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use super::bitset::{UnorderedSellCards, UnorderedCards};
 use super::registry::{SELL_CARD_REGISTRY, CARD_REGISTRY};
-use crate::types::{SellCardInstance, CardInstance};
+use crate::types::{CardId, SellCardInstance, CardInstance};
 
 // Serde for UnorderedCards: serialize as Vec<CardInstance>, deserialize and rebuild bitset
 impl Serialize for UnorderedCards {
@@ -27,7 +27,7 @@ impl<'de> Deserialize<'de> for UnorderedCards {
         CARD_REGISTRY.with(|r| {
             let mut reg = r.borrow_mut();
             for c in &cards {
-                let id = c.instance_id as u8;
+                let id = c.instance_id as CardId;
                 reg[id as usize] = c.card;
                 bitset.insert(id);
             }
@@ -59,7 +59,7 @@ impl<'de> Deserialize<'de> for UnorderedSellCards {
         SELL_CARD_REGISTRY.with(|r| {
             let mut reg = r.borrow_mut();
             for b in &sell_cards {
-                let id = b.instance_id as u8;
+                let id = b.instance_id as CardId;
                 reg[id as usize] = b.sell_card;
                 bitset.insert(id);
             }
@@ -72,11 +72,11 @@ impl<'de> Deserialize<'de> for UnorderedSellCards {
 mod tests {
     use super::*;
     use super::super::registry::{set_sell_card_registry, set_card_registry};
-    use crate::types::{SellCard, Card};
+    use crate::types::{SellCard, Card, MAX_CARD_INSTANCES};
 
     #[test]
     fn test_serde_roundtrip_cards() {
-        let mut registry = [Card::BasicRed; 256];
+        let mut registry = [Card::BasicRed; MAX_CARD_INSTANCES];
         registry[0] = Card::BasicRed;
         registry[5] = Card::BasicYellow;
         registry[10] = Card::BasicBlue;
@@ -94,7 +94,7 @@ mod tests {
 
     #[test]
     fn test_serde_roundtrip_empty() {
-        set_card_registry(&[Card::BasicRed; 256]);
+        set_card_registry(&[Card::BasicRed; MAX_CARD_INSTANCES]);
         let s = UnorderedCards::new();
         let json = serde_json::to_string(&s).unwrap();
         assert_eq!(json, "[]");
@@ -104,7 +104,7 @@ mod tests {
 
     #[test]
     fn test_serde_roundtrip_sell_cards() {
-        let mut registry = [SellCard::Textiles2Vermilion; 256];
+        let mut registry = [SellCard::Textiles2Vermilion; MAX_CARD_INSTANCES];
         registry[0] = SellCard::Textiles2Vermilion;
         registry[3] = SellCard::Textiles2Amber;
         registry[7] = SellCard::Textiles2Chartreuse;
@@ -2,13 +2,14 @@ use colori_core::colori_game::{apply_choice_to_state, check_choice_available, en
 use colori_core::draw_phase::execute_draw_phase;
 use colori_core::scoring::calculate_score;
 use colori_core::setup::create_initial_game_state;
-use colori_core::types::{Ability, SellCard, Card, Choice, Color, GamePhase, GameState};
+use colori_core::types::{Ability, SellCard, Card, CardId, Choice, Color, GamePhase, GameState};
 use colori_core::unordered_cards::{
     get_sell_card_registry, get_card_registry, set_sell_card_registry, set_card_registry,
 };
 use rand::RngExt;
 use rand::SeedableRng;
 use smallvec::SmallVec;
+use std::sync::Arc;
 use wyrand::WyRand;
 
 // ── Helpers ──
@@ -419,8 +420,8 @@ fn serialize_state(state: &GameState) -> String {
 
 fn deserialize_state(json: &str) -> GameState {
     let mut state: GameState = serde_json::from_str(json).unwrap();
-    state.card_lookup = get_card_registry();
-    state.sell_card_lookup = get_sell_card_registry();
+    state.card_lookup = Arc::new(get_card_registry());
+    state.sell_card_lookup = Arc::new(get_sell_card_registry());
     for p in state.players.iter_mut() {
         p.cached_score = calculate_score(p);
     }
@@ -935,6 +936,26 @@ fn test_draft_deck_card_count() {
     );
 }
 
+#[test]
+fn test_clones_share_card_lookups() {
+    let mut rng = WyRand::seed_from_u64(0);
+    let state = create_initial_game_state(3, &[true, true, true], &mut rng);
+    let mut det = state.clone();
+    execute_draw_phase(&mut det, &mut rng);
+    assert!(Arc::ptr_eq(&state.card_lookup, &det.card_lookup));
+    assert!(Arc::ptr_eq(&state.sell_card_lookup, &det.sell_card_lookup));
+
+    // clone_from copies every field, and takes the lookups of a state from
+    // another game
+    let mut other = create_initial_game_state(2, &[true, true], &mut rng);
+    other.clone_from(&det);
+    assert_eq!(serialize_state(&other), serialize_state(&det));
+    assert!(Arc::ptr_eq(&other.card_lookup, &det.card_lookup));
+    assert!(Arc::ptr_eq(&other.sell_card_lookup, &det.sell_card_lookup));
+    other.clone_from(&state);
+    assert_eq!(serialize_state(&other), serialize_state(&state));
+}
+
 #[test]
 fn test_sell_card_deck_card_count() {
     for num_players in 2..=4 {
@@ -1021,23 +1042,23 @@ fn test_starting_color_wheel() {
 /// Find a card id whose lookup matches `target` in any of the sources
 /// (player decks, draft_deck) and return it along with a state ready for
 /// action phase mutation.
-fn find_card_id(state: &GameState, target: Card) -> u8 {
+fn find_card_id(state: &GameState, target: Card) -> CardId {
     for (idx, &c) in state.card_lookup.iter().enumerate() {
         if c == target {
             for player in state.players.iter() {
-                if player.deck.contains(idx as u8) { return idx as u8; }
-                if player.discard.contains(idx as u8) { return idx as u8; }
-                if player.workshop_cards.contains(idx as u8) { return idx as u8; }
-                if player.workshopped_cards.contains(idx as u8) { return idx as u8; }
-                if player.drafted_cards.contains(idx as u8) { return idx as u8; }
+                if player.deck.contains(idx as CardId) { return idx as CardId; }
+                if player.discard.contains(idx as CardId) { return idx as CardId; }
+                if player.workshop_cards.contains(idx as CardId) { return idx as CardId; }
+                if player.workshopped_cards.contains(idx as CardId) { return idx as CardId; }
+                if player.drafted_cards.contains(idx as CardId) { return idx as CardId; }
             }
-            if state.draft_deck.contains(idx as u8) { return idx as u8; }
+            if state.draft_deck.contains(idx as CardId) { return idx as CardId; }
         }
     }
     panic!("Could not find a {:?} in the game state", target);
 }
 
-fn remove_card_anywhere(state: &mut GameState, id: u8) {
+fn remove_card_anywhere(state: &mut GameState, id: CardId) {
     for player in state.players.iter_mut() {
         if player.deck.contains(id) { player.deck.remove(id); return; }
         if player.discard.contains(id) { player.discard.remove(id); return; }
@@ -1054,7 +1075,7 @@ fn setup_action_state_with(
     workshopped_target: Option<Card>,
     workshop_target: Option<Card>,
     top_ability: Option<Ability>,
-) -> (GameState, u8, Option<u8>, Option<u8>) {
+) -> (GameState, CardId, Option<CardId>, Option<CardId>) {
     let mut rng = WyRand::seed_from_u64(12345);
     let mut state = create_initial_game_state(2, &[true, true], &mut rng);
 
@@ -1267,3 +1288,52 @@ fn test_score_is_ducats() {
         }
     }
 }
+
+#[test]
+fn test_game_log_deal_seed_round_trip() {
+    use colori_core::game_log::{LogGameState, StructuredGameLog};
+
+    let seed = u64::MAX - 1;
+    let log = StructuredGameLog {
+        version: 1,
+        game_started_at: String::new(),
+        game_ended_at: None,
+        player_names: Vec::new(),
+        ai_players: Vec::new(),
+        initial_state: LogGameState {
+            players: Vec::new(),
+            draft_deck: Vec::new(),
+            destroyed_pile: Vec::new(),
+            sell_card_deck: Vec::new(),
+            sell_card_display: Vec::new(),
+            round: 1,
+        },
+        final_scores: None,
+        final_player_stats: None,
+        entries: Vec::new(),
+        initial_draws: Vec::new(),
+        duration_ms: None,
+        player_time_ms: Vec::new(),
+        player_iterations: Vec::new(),
+        iterations: None,
+        player_variants: None,
+        note: None,
+        deal_seed: Some(seed),
+    };
+
+    // Seeds are written as strings so JavaScript keeps every digit.
+    let mut value = serde_json::to_value(&log).unwrap();
+    assert_eq!(value["dealSeed"], serde_json::Value::String(seed.to_string()));
+    let read: StructuredGameLog = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(read.deal_seed, Some(seed));
+
+    // Older logs wrote the seed as a plain number.
+    value["dealSeed"] = serde_json::Value::from(seed);
+    let read: StructuredGameLog = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(read.deal_seed, Some(seed));
+
+    value.as_object_mut().unwrap().remove("dealSeed");
+    let read: StructuredGameLog = serde_json::from_value(value).unwrap();
+    assert_eq!(read.deal_seed, None);
+    assert!(serde_json::to_value(&read).unwrap().get("dealSeed").is_none());
+}
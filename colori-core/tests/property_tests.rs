@@ -0,0 +1,273 @@
+//! Property tests for the rules engine.
+//!
+//! States are generated by random play from a random deal, so every generated
+//! state is reachable. Choices are either picked from `enumerate_choices` or
+//! built from arbitrary fields, so most of the latter are illegal.
+
+use colori_core::cards::generate_all_sell_cards;
+use colori_core::colori_game::{
+    apply_choice_to_state, check_choice_available, enumerate_choices, try_apply_choice_to_state,
+};
+use colori_core::draw_phase::execute_draw_phase;
+use colori_core::game_log::{DrawLog, StructuredLogEntry};
+use colori_core::perft::seed_position;
+use colori_core::replay::GameReplay;
+use colori_core::scoring::calculate_score;
+use colori_core::setup::create_initial_game_state;
+use colori_core::types::{Card, Choice, Color, GamePhase, GameState, ALL_CARDS, ALL_COLORS};
+use colori_core::unordered_cards::{get_card_registry, get_sell_card_registry};
+use proptest::prelude::*;
+use proptest::sample::Index;
+use rand::{RngExt, SeedableRng};
+use smallvec::SmallVec;
+use std::sync::Arc;
+use wyrand::WyRand;
+
+// ── Strategies ──
+
+fn arb_card() -> impl Strategy<Value = Card> {
+    prop::sample::select(ALL_CARDS.to_vec())
+}
+
+fn arb_color() -> impl Strategy<Value = Color> {
+    prop::sample::select(ALL_COLORS.to_vec())
+}
+
+fn arb_cards() -> impl Strategy<Value = SmallVec<[Card; 4]>> {
+    prop::collection::vec(arb_card(), 0..=4).prop_map(SmallVec::from_vec)
+}
+
+fn arb_mixes() -> impl Strategy<Value = SmallVec<[(Color, Color); 2]>> {
+    prop::collection::vec((arb_color(), arb_color()), 0..=3).prop_map(SmallVec::from_vec)
+}
+
+fn arb_choice() -> impl Strategy<Value = Choice> {
+    let sell_card = prop::sample::select(generate_all_sell_cards().to_vec());
+    prop_oneof![
+        arb_card().prop_map(|card| Choice::DraftPick { card }),
+        arb_card().prop_map(|card| Choice::DestroyDraftedCard { card }),
+        Just(Choice::EndTurn),
+        arb_cards().prop_map(|card_types| Choice::Workshop { card_types }),
+        Just(Choice::SkipWorkshop),
+        prop::option::of(arb_card()).prop_map(|card| Choice::DestroyDrawnCards { card }),
+        sell_card.clone().prop_map(|sell_card| Choice::SelectSellCard { sell_card }),
+        arb_color().prop_map(|color| Choice::GainSecondary { color }),
+        arb_color().prop_map(|color| Choice::GainPrimary { color }),
+        arb_mixes().prop_map(|mixes| Choice::MixAll { mixes }),
+        (arb_color(), arb_color()).prop_map(|(lose, gain)| Choice::SwapTertiary { lose, gain }),
+        (arb_card(), arb_mixes()).prop_map(|(card, mixes)| Choice::DestroyAndMix { card, mixes }),
+        (arb_card(), sell_card).prop_map(|(card, sell_card)| Choice::DestroyAndSell { card, sell_card }),
+        (arb_card(), arb_cards())
+            .prop_map(|(card, workshop_cards)| Choice::DestroyAndWorkshop { card, workshop_cards }),
+        (arb_card(), prop::option::of(arb_card()))
+            .prop_map(|(card, target)| Choice::DestroyAndDestroyCards { card, target }),
+        arb_card().prop_map(|card| Choice::SelectMoveToDrafted { card }),
+        Just(Choice::SkipMoveToDrafted),
+        arb_card().prop_map(|card| Choice::SelectMoveToWorkshop { card }),
+        Just(Choice::SkipMoveToWorkshop),
+        arb_card().prop_map(|card| Choice::DeferredMoveToDraft { card }),
+        arb_card().prop_map(|card| Choice::DestroyWorkshopCardDeferred { card }),
+    ]
+}
+
+/// A state reached by up to 300 random choices from a fresh deal.
+fn arb_state() -> impl Strategy<Value = GameState> {
+    (1..=4usize, any::<u64>(), 0..300u32).prop_map(|(num_players, seed, plies)| seed_position(num_players, seed, plies))
+}
+
+/// Either one of the enumerated choices of the state or an arbitrary one.
+fn pick_choice(state: &GameState, enumerated: bool, index: Index, arbitrary: Choice) -> Choice {
+    let choices = enumerate_choices(state);
+    if enumerated && !choices.is_empty() {
+        choices[index.index(choices.len())].clone()
+    } else {
+        arbitrary
+    }
+}
+
+// ── Helpers ──
+
+fn count_all_cards(state: &GameState) -> u32 {
+    let mut total = state.draft_deck.len() + state.destroyed_pile.len();
+    for player in state.players.iter() {
+        total += player.deck.len()
+            + player.discard.len()
+            + player.workshopped_cards.len()
+            + player.workshop_cards.len()
+            + player.drafted_cards.len();
+    }
+    if let GamePhase::Draft { ref draft_state } = state.phase {
+        for i in 0..draft_state.num_hands {
+            total += draft_state.hands[i].len();
+        }
+    }
+    total
+}
+
+fn count_all_sell_cards(state: &GameState) -> u32 {
+    let mut total = state.sell_card_deck.len() + state.sell_card_display.len() as u32;
+    for player in state.players.iter() {
+        total += player.completed_sell_cards.len() as u32;
+    }
+    total
+}
+
+/// The serialized form of a state, which covers everything but the card
+/// lookups and caches.
+fn snapshot(state: &GameState) -> String {
+    serde_json::to_string(state).unwrap()
+}
+
+/// Whether `choice` only exists for the human UI and is never enumerated.
+fn is_ui_only(choice: &Choice) -> bool {
+    matches!(choice, Choice::DeferredMoveToDraft { .. } | Choice::DestroyWorkshopCardDeferred { .. })
+}
+
+/// Whether enumeration may leave out `choice` although it is legal: it emits
+/// one canonical form of workshop selections and mix sequences, and it does
+/// not offer declining to destroy a card while there is one to destroy.
+fn may_be_pruned(choice: &Choice) -> bool {
+    matches!(
+        choice,
+        Choice::Workshop { .. }
+            | Choice::DestroyAndWorkshop { .. }
+            | Choice::MixAll { .. }
+            | Choice::DestroyAndMix { .. }
+            | Choice::DestroyDrawnCards { card: None }
+            | Choice::DestroyAndDestroyCards { target: None, .. }
+    )
+}
+
+/// Plays a random game while recording it the way `colori-runner` writes
+/// game logs. Returns the initial state JSON, the initial draws, the entries
+/// and the snapshot after each entry.
+fn record_random_game(
+    num_players: usize,
+    seed: u64,
+) -> (serde_json::Value, Vec<colori_core::game_log::DrawEvent>, Vec<StructuredLogEntry>, Vec<String>) {
+    let mut rng = WyRand::seed_from_u64(seed);
+    let mut state = create_initial_game_state(num_players, &vec![true; num_players], &mut rng);
+    let initial_state = serde_json::to_value(&state).unwrap();
+
+    state.draw_log = Some(DrawLog::Recording(Vec::new()));
+    execute_draw_phase(&mut state, &mut rng);
+    let Some(DrawLog::Recording(initial_draws)) = state.draw_log.take() else { unreachable!() };
+
+    let mut entries = Vec::new();
+    let mut snapshots = Vec::new();
+    while !matches!(state.phase, GamePhase::GameOver) {
+        let (player_index, phase) = match &state.phase {
+            GamePhase::Draft { draft_state } => (draft_state.current_player_index, "draft"),
+            GamePhase::Action { action_state } => (action_state.current_player_index, "action"),
+            _ => unreachable!(),
+        };
+        let choices = enumerate_choices(&state);
+        let choice = choices[rng.random_range(0..choices.len())].clone();
+        let round = state.round;
+
+        state.draw_log = Some(DrawLog::Recording(Vec::new()));
+        apply_choice_to_state(&mut state, &choice, &mut rng);
+        let Some(DrawLog::Recording(draws)) = state.draw_log.take() else { unreachable!() };
+
+        entries.push(StructuredLogEntry {
+            seq: entries.len() as u32 + 1,
+            timestamp: 0,
+            round,
+            phase: phase.to_string(),
+            player_index,
+            choice,
+            draws,
+        });
+        snapshots.push(snapshot(&state));
+    }
+    (initial_state, initial_draws, entries, snapshots)
+}
+
+// ── Properties ──
+
+proptest! {
+    #[test]
+    fn try_apply_never_panics(
+        state in arb_state(),
+        enumerated in any::<bool>(),
+        index in any::<Index>(),
+        arbitrary in arb_choice(),
+        seed in any::<u64>(),
+    ) {
+        let choice = pick_choice(&state, enumerated, index, arbitrary);
+        let before = snapshot(&state);
+        let cards = count_all_cards(&state);
+        let sell_cards = count_all_sell_cards(&state);
+
+        let mut next = state.clone();
+        let mut rng = WyRand::seed_from_u64(seed);
+        if try_apply_choice_to_state(&mut next, &choice, &mut rng) {
+            prop_assert_eq!(count_all_cards(&next), cards);
+            prop_assert_eq!(count_all_sell_cards(&next), sell_cards);
+        } else {
+            prop_assert!(!enumerated || enumerate_choices(&state).is_empty(), "enumerated choice {:?} was rejected", choice);
+            prop_assert_eq!(snapshot(&next), before);
+        }
+    }
+
+    #[test]
+    fn check_choice_available_agrees_with_enumerate(mut state in arb_state(), choice in arb_choice()) {
+        // With this set, enumeration leaves out workshop choices that use
+        // fewer cards than possible, on purpose.
+        state.force_max_workshop = false;
+        let choices = enumerate_choices(&state);
+        let available = check_choice_available(&state, &choice);
+        if choices.contains(&choice) {
+            prop_assert!(available, "enumerated choice {:?} is not available", choice);
+        }
+        if available && !is_ui_only(&choice) && !may_be_pruned(&choice) {
+            prop_assert!(choices.contains(&choice), "available choice {:?} is not enumerated in {:?}", choice, choices);
+        }
+        if available && !is_ui_only(&choice) {
+            let variant = std::mem::discriminant(&choice);
+            prop_assert!(
+                choices.iter().any(|c| std::mem::discriminant(c) == variant),
+                "available choice {:?} has no enumerated counterpart in {:?}",
+                choice,
+                choices
+            );
+        }
+    }
+
+    #[test]
+    fn serde_round_trip_preserves_state(state in arb_state()) {
+        let json = snapshot(&state);
+        let mut restored: GameState = serde_json::from_str(&json).unwrap();
+        restored.card_lookup = Arc::new(get_card_registry());
+        restored.sell_card_lookup = Arc::new(get_sell_card_registry());
+        restored.force_max_workshop = state.force_max_workshop;
+        for player in restored.players.iter_mut() {
+            player.cached_score = calculate_score(player);
+        }
+
+        prop_assert_eq!(snapshot(&restored), json);
+        prop_assert_eq!(enumerate_choices(&restored), enumerate_choices(&state));
+        for (player, original) in restored.players.iter().zip(state.players.iter()) {
+            prop_assert_eq!(player.cached_score, original.cached_score);
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn replay_reproduces_logged_games(num_players in 1..=4usize, seed in any::<u64>()) {
+        let (initial_state, initial_draws, entries, snapshots) = record_random_game(num_players, seed);
+        let mut replay = GameReplay::new(&initial_state, &initial_draws);
+        for (i, (entry, expected)) in entries.iter().zip(&snapshots).enumerate() {
+            replay.apply_entry(entry);
+            // The replay drafts simultaneously, so mid-draft it only agrees
+            // with the recorded game once pointed at the next player to pick.
+            if let Some(next) = entries.get(i + 1) {
+                replay.fix_current_player_for_next(next);
+            }
+            prop_assert_eq!(&snapshot(&replay.state), expected, "after entry {}", i);
+        }
+    }
+}
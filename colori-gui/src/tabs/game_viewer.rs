@@ -7,8 +7,9 @@ use wyrand::WyRand;
 
 use colori_core::colori_game::enumerate_choices;
 use colori_core::game_log::{DrawEvent, StructuredGameLog, StructuredLogEntry};
-use colori_core::ismcts::{ismcts, MctsConfig, MctsNode, TreeStats};
+use colori_core::ismcts::{ismcts, MctsConfig, MctsNode, MctsTree, TreeStats};
 use colori_core::replay::{GameReplay, replay_to};
+use colori_core::rollout_policy::RolloutPolicyKind;
 use colori_core::scoring::calculate_score;
 use colori_core::types::{
     Card, CardInstance, Choice, Color, GamePhase, GameState, SellCardInstance, ALL_COLORS, ALL_MATERIAL_TYPES,
@@ -46,7 +47,11 @@ fn phase_name(phase: &str) -> &str {
 pub struct MctsAnalysisResult {
     pub iterations_used: u32,
     pub tree_stats: TreeStats,
-    pub root: MctsNode,
+    pub tree: MctsTree,
+    /// Each opponent's own tree when searching in multi-observer mode; the
+    /// analysed player's slot is empty.
+    pub opponent_trees: Vec<MctsTree>,
+    pub player_index: usize,
 }
 
 struct BatchMctsEntry {
@@ -86,6 +91,7 @@ struct MctsGuiConfig {
     use_heuristic_eval: bool,
     heuristic_rollout: bool,
     early_termination: bool,
+    multi_observer: bool,
     heuristic_params_path: String,
 }
 
@@ -97,6 +103,7 @@ impl Default for MctsGuiConfig {
             use_heuristic_eval: true,
             heuristic_rollout: true,
             early_termination: true,
+            multi_observer: false,
             heuristic_params_path: "genetic-algorithm/batch-lki08w-gen-32.json".to_string(),
         }
     }
@@ -116,8 +123,9 @@ impl MctsGuiConfig {
             iterations: self.iterations,
             exploration_constant: self.exploration_constant,
             use_heuristic_eval: self.use_heuristic_eval,
-            heuristic_rollout: self.heuristic_rollout,
+            rollout_policy: RolloutPolicyKind::from_flags(self.heuristic_rollout, false),
             early_termination: self.early_termination,
+            multi_observer: self.multi_observer,
             ..MctsConfig::new(heuristic_params)
         }
     }
@@ -250,7 +258,7 @@ impl GameViewerState {
         };
 
         let config = self.mcts_config.to_mcts_config();
-        let max_rollout_round = std::cmp::max(8, state.round + 2);
+        let max_rollout_round = config.max_rollout_round(state.round);
 
         let (tx, rx) = mpsc::channel();
         self.mcts_receiver = Some(rx);
@@ -266,13 +274,15 @@ impl GameViewerState {
                 None,
                 &mut rng,
             );
-            if let Some(root) = result.tree {
-                let tree_stats = root.tree_stats();
-                let iterations_used = root.visit_count();
+            if let Some(tree) = result.tree {
+                let tree_stats = tree.root().tree_stats();
+                let iterations_used = tree.root().visit_count();
                 let _ = tx.send(MctsAnalysisResult {
                     iterations_used,
                     tree_stats,
-                    root,
+                    tree,
+                    opponent_trees: result.opponent_trees,
+                    player_index,
                 });
             }
         });
@@ -314,7 +324,7 @@ impl GameViewerState {
                 };
 
                 if let Some(player_index) = player_index {
-                    let max_rollout_round = std::cmp::max(8, replay.state.round + 2);
+                    let max_rollout_round = config.max_rollout_round(replay.state.round);
                     let result = ismcts(
                         &replay.state,
                         player_index,
@@ -339,13 +349,15 @@ impl GameViewerState {
                             .any(|e| choices_equivalent(mcts_choice, &e.choice));
                     }
 
-                    if let Some(root) = result.tree {
-                        let tree_stats = root.tree_stats();
-                        let iterations_used = root.visit_count();
+                    if let Some(tree) = result.tree {
+                        let tree_stats = tree.root().tree_stats();
+                        let iterations_used = tree.root().visit_count();
                         let analysis = MctsAnalysisResult {
                             iterations_used,
                             tree_stats,
-                            root,
+                            tree,
+                            opponent_trees: result.opponent_trees,
+                            player_index,
                         };
                         let batch_entry = BatchMctsEntry {
                             mcts_best_choice: result.choice,
@@ -971,6 +983,7 @@ fn render_mcts_section(
             ui.checkbox(&mut config.use_heuristic_eval, "Heuristic eval");
             ui.checkbox(&mut config.heuristic_rollout, "Heuristic rollout");
             ui.checkbox(&mut config.early_termination, "Early termination");
+            ui.checkbox(&mut config.multi_observer, "Multi-observer (one tree per player)");
             ui.horizontal(|ui| {
                 ui.label("Heuristic params file:");
                 ui.text_edit_singleline(&mut config.heuristic_params_path);
@@ -1008,14 +1021,31 @@ fn render_mcts_section(
         ));
 
         ui.add_space(4.0);
-        render_mcts_children(ui, &result.root, 0);
+        render_mcts_children(ui, result.tree.root(), 0);
+
+        for (player, tree) in result.opponent_trees.iter().enumerate() {
+            if player == result.player_index {
+                continue;
+            }
+            ui.add_space(4.0);
+            ui.push_id(("opponent_tree", player), |ui| {
+                egui::CollapsingHeader::new(format!(
+                    "Player {}'s tree ({} nodes)",
+                    player,
+                    tree.root().tree_stats().total_nodes,
+                ))
+                .default_open(false)
+                .show(ui, |ui| {
+                    render_mcts_children(ui, tree.root(), 0);
+                });
+            });
+        }
     }
 }
 
-fn render_mcts_children(ui: &mut egui::Ui, node: &MctsNode, depth: usize) {
-    let mut children: Vec<&MctsNode> = node
+fn render_mcts_children(ui: &mut egui::Ui, node: MctsNode, depth: usize) {
+    let mut children: Vec<MctsNode> = node
         .children()
-        .iter()
         .filter(|c| c.visit_count() > 0)
         .collect();
     children.sort_by(|a, b| b.visit_count().cmp(&a.visit_count()));
@@ -1034,10 +1064,7 @@ fn render_mcts_children(ui: &mut egui::Ui, node: &MctsNode, depth: usize) {
             ui.end_row();
 
             for child in &children {
-                let choice_text = child
-                    .choice()
-                    .map(|ch| format_choice(ch))
-                    .unwrap_or_else(|| "?".to_string());
+                let choice_text = mcts_choice_text(child);
                 let stats = child.tree_stats();
 
                 let truncated = if choice_text.len() > max_choice_chars {
@@ -1056,13 +1083,10 @@ fn render_mcts_children(ui: &mut egui::Ui, node: &MctsNode, depth: usize) {
 
     // Expandable subtrees below the table
     for (i, child) in children.iter().enumerate() {
-        if child.children().is_empty() {
+        if child.children().len() == 0 {
             continue;
         }
-        let choice_text = child
-            .choice()
-            .map(|ch| format_choice(ch))
-            .unwrap_or_else(|| "?".to_string());
+        let choice_text = mcts_choice_text(child);
 
         egui::CollapsingHeader::new(
             egui::RichText::new(format!("▶ {}", choice_text)).monospace().small(),
@@ -1070,11 +1094,19 @@ fn render_mcts_children(ui: &mut egui::Ui, node: &MctsNode, depth: usize) {
         .id_salt(format!("mcts_d{}_c{}", depth, i))
         .default_open(false)
         .show(ui, |ui| {
-            render_mcts_children(ui, child, depth + 1);
+            render_mcts_children(ui, *child, depth + 1);
         });
     }
 }
 
+fn mcts_choice_text(node: &MctsNode) -> String {
+    match node.choice() {
+        Some(choice) => format_choice(choice),
+        None if node.is_hidden() => "(hidden pick)".to_string(),
+        None => "?".to_string(),
+    }
+}
+
 /// Compare two choices for equivalence, ignoring order of workshop cards and mix pairs.
 fn choices_equivalent(a: &Choice, b: &Choice) -> bool {
     match (a, b) {
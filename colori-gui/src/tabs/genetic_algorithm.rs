@@ -55,6 +55,7 @@ const PARAM_GROUPS: &[(&str, &[&str])] = &[
     ]),
     ("Sell Card Weights", &[
         "sell_card_material_alignment", "sell_card_color_alignment",
+        "sell_card_reach_weight",
     ]),
     ("Action Card Overrides", &[
         "alum_quality", "cream_of_tartar_quality", "gum_arabic_quality",
@@ -111,6 +112,7 @@ fn get_param_value(params: &HeuristicParams, name: &str) -> Option<f64> {
         "dual_material_quality" => Some(params.dual_material_quality),
         "sell_card_material_alignment" => Some(params.sell_card_material_alignment),
         "sell_card_color_alignment" => Some(params.sell_card_color_alignment),
+        "sell_card_reach_weight" => Some(params.sell_card_reach_weight),
         "heuristic_round_threshold" => Some(params.heuristic_round_threshold as f64),
         "heuristic_lookahead" => Some(params.heuristic_lookahead as f64),
         "alum_quality" => Some(params.alum_quality),
@@ -0,0 +1,6 @@
+"""Python bindings for the Colori game engine."""
+
+from ._colori import ACTION_SPACE_SIZE, OBSERVATION_LEN, Game
+from .env import ColoriEnv
+
+__all__ = ["ACTION_SPACE_SIZE", "OBSERVATION_LEN", "ColoriEnv", "Game"]
@@ -0,0 +1,59 @@
+"""Gym-style environment where one agent plays against ISMCTS opponents."""
+
+from ._colori import ACTION_SPACE_SIZE, OBSERVATION_LEN, Game
+
+
+class ColoriEnv:
+    """Single-agent view of a Colori game.
+
+    ``reset`` and ``step`` follow the Gymnasium signatures, but the class has
+    no dependency on Gymnasium. Actions are indices into the fixed action
+    space; the legal ones for the next step are in ``info["legal_actions"]``.
+    The reward is 0 until the game ends, then the agent's terminal reward.
+    """
+
+    action_space_size = ACTION_SPACE_SIZE
+    observation_len = OBSERVATION_LEN
+
+    def __init__(self, num_players=2, agent=0, opponent_iterations=100, max_rounds=None):
+        if not 0 <= agent < num_players:
+            raise ValueError("agent must be a valid player index")
+        self.num_players = num_players
+        self.agent = agent
+        self.opponent_iterations = opponent_iterations
+        self.max_rounds = max_rounds
+        self.game = None
+        self._next_seed = 0
+
+    def reset(self, seed=None, options=None):
+        if seed is None:
+            seed = self._next_seed
+        self._next_seed = seed + 1
+        self.game = Game(self.num_players, seed, self.max_rounds)
+        self._play_opponents()
+        return self._observation(), self._info()
+
+    def step(self, action):
+        if self.game is None:
+            raise RuntimeError("Call reset() before step()")
+        if self.game.is_over:
+            raise RuntimeError("Game is over; call reset()")
+        self.game.apply_action(action)
+        self._play_opponents()
+        terminated = self.game.is_over
+        reward = self.game.rewards()[self.agent] if terminated else 0.0
+        return self._observation(), reward, terminated, False, self._info()
+
+    def _play_opponents(self):
+        while not self.game.is_over and self.game.current_player != self.agent:
+            self.game.apply_action(self.game.ismcts_action(self.opponent_iterations))
+
+    def _observation(self):
+        return self.game.observation(self.agent)
+
+    def _info(self):
+        return {
+            "legal_actions": [] if self.game.is_over else self.game.legal_actions(),
+            "round": self.game.round,
+            "scores": self.game.scores(),
+        }
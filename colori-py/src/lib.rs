@@ -0,0 +1,159 @@
+use colori_core::action_space::{choice_to_index, index_to_choice, legal_action_indices, ACTION_SPACE_SIZE};
+use colori_core::colori_game::{apply_choice_to_state, check_choice_available, enumerate_choices};
+use colori_core::draw_phase::execute_draw_phase;
+use colori_core::ismcts::{ismcts, MctsConfig};
+use colori_core::observation::{encode_observation, OBSERVATION_LEN};
+use colori_core::scoring::{calculate_score, compute_terminal_rewards, HeuristicParams};
+use colori_core::setup::create_initial_game_state;
+use colori_core::types::{Choice, GamePhase, GameState, MAX_PLAYERS};
+use colori_core::unordered_cards::{set_card_registry, set_sell_card_registry};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::SeedableRng;
+use wyrand::WyRand;
+
+const TRAINED_PARAMS_JSON: &str = include_str!("../../genetic-algorithm/batch-lki08w-gen-32.json");
+
+/// A single game with its own seeded RNG. Every chance outcome (shuffles,
+/// draws, ISMCTS determinizations) comes from that RNG, so a game created
+/// with the same seed and fed the same actions always plays out identically.
+#[pyclass(module = "colori._colori", skip_from_py_object)]
+#[derive(Clone)]
+struct Game {
+    state: GameState,
+    rng: WyRand,
+}
+
+impl Game {
+    fn apply(&mut self, choice: &Choice) -> PyResult<()> {
+        if !check_choice_available(&self.state, choice) {
+            return Err(PyValueError::new_err(format!("Illegal choice: {:?}", choice)));
+        }
+        apply_choice_to_state(&mut self.state, choice, &mut self.rng);
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl Game {
+    #[new]
+    #[pyo3(signature = (num_players, seed, max_rounds=None))]
+    fn new(num_players: usize, seed: u64, max_rounds: Option<u32>) -> PyResult<Self> {
+        if !(1..=MAX_PLAYERS).contains(&num_players) {
+            return Err(PyValueError::new_err(format!(
+                "num_players must be between 1 and {}",
+                MAX_PLAYERS
+            )));
+        }
+        let mut rng = WyRand::seed_from_u64(seed);
+        let ai_players = vec![true; num_players];
+        let mut state = create_initial_game_state(num_players, &ai_players, &mut rng);
+        if let Some(mr) = max_rounds {
+            state.max_rounds = mr;
+        }
+        execute_draw_phase(&mut state, &mut rng);
+        Ok(Game { state, rng })
+    }
+
+    #[getter]
+    fn num_players(&self) -> usize {
+        self.state.players.len()
+    }
+
+    #[getter]
+    fn round(&self) -> u32 {
+        self.state.round
+    }
+
+    #[getter]
+    fn is_over(&self) -> bool {
+        matches!(self.state.phase, GamePhase::GameOver)
+    }
+
+    /// Index of the player to move, or None once the game is over.
+    #[getter]
+    fn current_player(&self) -> Option<usize> {
+        match &self.state.phase {
+            GamePhase::Draft { draft_state } => Some(draft_state.current_player_index),
+            GamePhase::Action { action_state } => Some(action_state.current_player_index),
+            _ => None,
+        }
+    }
+
+    /// Legal action indices, in `legal_choices` order.
+    fn legal_actions(&self) -> Vec<u32> {
+        legal_action_indices(&self.state)
+    }
+
+    /// Legal choices as JSON strings, in the same format the web client uses.
+    fn legal_choices(&self) -> Vec<String> {
+        enumerate_choices(&self.state)
+            .iter()
+            .map(|c| serde_json::to_string(c).expect("Failed to serialize choice"))
+            .collect()
+    }
+
+    fn apply_action(&mut self, action: u32) -> PyResult<()> {
+        let choice = index_to_choice(action)
+            .ok_or_else(|| PyValueError::new_err(format!("Action index out of range: {}", action)))?;
+        self.apply(&choice)
+    }
+
+    fn apply_choice(&mut self, choice_json: &str) -> PyResult<()> {
+        let choice: Choice = serde_json::from_str(choice_json)
+            .map_err(|e| PyValueError::new_err(format!("Invalid choice JSON: {}", e)))?;
+        self.apply(&choice)
+    }
+
+    /// Fixed-length feature vector of what `player` can observe.
+    fn observation(&self, player: usize) -> PyResult<Vec<f32>> {
+        if player >= self.state.players.len() {
+            return Err(PyValueError::new_err(format!("No player {}", player)));
+        }
+        Ok(encode_observation(&self.state, player))
+    }
+
+    fn scores(&self) -> Vec<u32> {
+        self.state.players.iter().map(calculate_score).collect()
+    }
+
+    /// Terminal rewards per player (winners share 1.0). Only meaningful once
+    /// the game is over.
+    fn rewards(&self) -> Vec<f64> {
+        compute_terminal_rewards(&self.state.players)[..self.state.players.len()].to_vec()
+    }
+
+    /// Runs ISMCTS for the player to move and returns the chosen action index.
+    /// Uses the bundled trained heuristic params unless `params_json` is given.
+    #[pyo3(signature = (iterations=1000, params_json=None))]
+    fn ismcts_action(&mut self, iterations: u32, params_json: Option<&str>) -> PyResult<u32> {
+        let player_index = self
+            .current_player()
+            .ok_or_else(|| PyValueError::new_err("Game is over"))?;
+        let heuristic_params: HeuristicParams = serde_json::from_str(params_json.unwrap_or(TRAINED_PARAMS_JSON))
+            .map_err(|e| PyValueError::new_err(format!("Invalid heuristic params JSON: {}", e)))?;
+        let config = MctsConfig { iterations, ..MctsConfig::new(heuristic_params) };
+        let max_rollout_round = config.max_rollout_round(self.state.round);
+        let result = ismcts(&self.state, player_index, &config, Some(max_rollout_round), None, &mut self.rng);
+        Ok(choice_to_index(&result.choice).expect("ISMCTS chose a choice outside the action space"))
+    }
+
+    /// Full game state as JSON, including hidden information.
+    fn state_json(&self) -> String {
+        set_card_registry(&self.state.card_lookup);
+        set_sell_card_registry(&self.state.sell_card_lookup);
+        serde_json::to_string(&self.state).expect("Failed to serialize game state")
+    }
+
+    fn copy(&self) -> Game {
+        self.clone()
+    }
+}
+
+#[pymodule]
+fn _colori(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Game>()?;
+    m.add("ACTION_SPACE_SIZE", ACTION_SPACE_SIZE)?;
+    m.add("OBSERVATION_LEN", OBSERVATION_LEN)?;
+    Ok(())
+}
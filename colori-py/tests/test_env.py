@@ -0,0 +1,28 @@
+from colori import OBSERVATION_LEN, ColoriEnv
+
+
+def test_reset_returns_observation_and_legal_actions():
+    env = ColoriEnv(num_players=2, opponent_iterations=20)
+    obs, info = env.reset(seed=0)
+    assert len(obs) == OBSERVATION_LEN
+    assert info["legal_actions"]
+
+
+def test_episode_runs_to_completion():
+    env = ColoriEnv(num_players=2, agent=1, opponent_iterations=20)
+    obs, info = env.reset(seed=1)
+    terminated = False
+    total_reward = 0.0
+    while not terminated:
+        obs, reward, terminated, truncated, info = env.step(info["legal_actions"][0])
+        assert not truncated
+        total_reward += reward
+    assert 0.0 <= total_reward <= 1.0
+    assert info["legal_actions"] == []
+
+
+def test_reset_is_deterministic():
+    env = ColoriEnv(num_players=3, opponent_iterations=20)
+    first, _ = env.reset(seed=5)
+    second, _ = env.reset(seed=5)
+    assert first == second
@@ -0,0 +1,73 @@
+import json
+import random
+
+from colori import ACTION_SPACE_SIZE, OBSERVATION_LEN, Game
+
+
+def play_random(game, rng):
+    while not game.is_over:
+        actions = game.legal_actions()
+        game.apply_action(rng.choice(actions))
+
+
+def test_random_game_terminates():
+    game = Game(3, seed=1)
+    play_random(game, random.Random(1))
+    assert game.current_player is None
+    rewards = game.rewards()
+    assert len(rewards) == 3
+    assert abs(sum(rewards) - 1.0) < 1e-9
+
+
+def test_same_seed_same_game():
+    a = Game(2, seed=42)
+    b = Game(2, seed=42)
+    play_random(a, random.Random(7))
+    play_random(b, random.Random(7))
+    assert a.state_json() == b.state_json()
+
+
+def test_legal_actions_match_choices():
+    game = Game(2, seed=3)
+    actions = game.legal_actions()
+    choices = game.legal_choices()
+    assert len(actions) == len(choices)
+    assert all(0 <= a < ACTION_SPACE_SIZE for a in actions)
+    assert all(json.loads(c)["type"] == "draftPick" for c in choices)
+
+
+def test_apply_choice_json():
+    game = Game(2, seed=5)
+    choice = game.legal_choices()[0]
+    game.apply_choice(choice)
+    assert game.current_player is not None
+
+
+def test_illegal_action_rejected():
+    game = Game(2, seed=5)
+    illegal = next(a for a in range(ACTION_SPACE_SIZE) if a not in game.legal_actions())
+    try:
+        game.apply_action(illegal)
+    except ValueError:
+        pass
+    else:
+        raise AssertionError("illegal action was accepted")
+
+
+def test_observation_length():
+    game = Game(4, seed=9)
+    for player in range(4):
+        assert len(game.observation(player)) == OBSERVATION_LEN
+
+
+def test_ismcts_action_is_legal():
+    game = Game(2, seed=11)
+    action = game.ismcts_action(iterations=50)
+    assert action in game.legal_actions()
+
+
+def test_copy_is_independent():
+    game = Game(2, seed=13)
+    clone = game.copy()
+    clone.apply_action(clone.legal_actions()[0])
+    assert game.state_json() != clone.state_json()
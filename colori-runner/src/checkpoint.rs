@@ -0,0 +1,293 @@
+//! Checkpoints, so that a long run can be stopped and picked up again with
+//! `--resume`.
+//!
+//! A command keeps two files in its output directory. The checkpoint holds
+//! its arguments and whatever state cannot be rebuilt from finished work (the
+//! batch id, seeds, the schedule, the GA population), and is rewritten
+//! whenever that state changes. The journal gets one JSON line for every
+//! finished unit of work as it finishes. Resuming reloads the checkpoint,
+//! replays the journal to rebuild the statistics and carries on with the work
+//! the journal does not cover, so an interruption only loses the work that
+//! was in flight.
+
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::duplicate::{PlayerShares, win_shares};
+use crate::ratings::{Ranking, final_rankings};
+use crate::simulation::GameRunOutput;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint<A, S> {
+    /// The arguments of the run; a resumed run uses these rather than the
+    /// ones it was started with.
+    pub args: A,
+    pub state: S,
+}
+
+fn checkpoint_path(output: &str, command: &str) -> String {
+    format!("{}/{}-checkpoint.json", output, command)
+}
+
+fn journal_path(output: &str, command: &str) -> String {
+    format!("{}/{}-journal.jsonl", output, command)
+}
+
+/// Writes `contents` to a temporary file and renames it over `path`, so that
+/// an interrupted write never leaves a truncated file behind.
+pub fn write_atomic(path: &str, contents: &str) {
+    let tmp = format!("{}.tmp", path);
+    std::fs::write(&tmp, contents).unwrap_or_else(|e| panic!("Failed to write {}: {}", tmp, e));
+    std::fs::rename(&tmp, path).unwrap_or_else(|e| panic!("Failed to write {}: {}", path, e));
+}
+
+pub fn save<A: Serialize, S: Serialize>(output: &str, command: &str, checkpoint: &Checkpoint<A, S>) {
+    let json = serde_json::to_string_pretty(checkpoint).unwrap();
+    write_atomic(&checkpoint_path(output, command), &json);
+}
+
+/// Whether `command` has saved a checkpoint in `output`.
+pub fn exists(output: &str, command: &str) -> bool {
+    std::path::Path::new(&checkpoint_path(output, command)).exists()
+}
+
+/// Loads the checkpoint of `command` from `output`, exiting if there is none.
+pub fn load<A: DeserializeOwned, S: DeserializeOwned>(output: &str, command: &str) -> Checkpoint<A, S> {
+    let path = checkpoint_path(output, command);
+    let contents = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        eprintln!("No {} checkpoint to resume from: {} not found", command, path);
+        std::process::exit(1);
+    });
+    let checkpoint = serde_json::from_str(&contents)
+        .unwrap_or_else(|e| panic!("Failed to parse checkpoint: {}: {}", path, e));
+    eprintln!("Resuming from {} with the arguments it was started with", path);
+    checkpoint
+}
+
+/// Exits unless the variants of a resumed run are the ones it was started
+/// with.
+pub fn check_labels(checkpoint_labels: &[String], labels: &[String]) {
+    if checkpoint_labels != labels {
+        eprintln!(
+            "The variants file no longer matches the checkpoint: expected {}, found {}",
+            checkpoint_labels.join(", "),
+            labels.join(", ")
+        );
+        std::process::exit(1);
+    }
+}
+
+// ── Journal ──
+
+/// An append-only file of finished work, one JSON record per line.
+pub struct Journal {
+    file: Mutex<File>,
+}
+
+impl Journal {
+    /// Starts an empty journal for `command`.
+    pub fn create(output: &str, command: &str) -> Self {
+        let path = journal_path(output, command);
+        let file = File::create(&path).unwrap_or_else(|e| panic!("Failed to create journal {}: {}", path, e));
+        Journal { file: Mutex::new(file) }
+    }
+
+    /// Reads the records of the journal of `command` and reopens it for
+    /// appending. A last line cut short by an interruption is dropped.
+    pub fn resume<T: DeserializeOwned>(output: &str, command: &str) -> (Self, Vec<T>) {
+        let path = journal_path(output, command);
+        let contents = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut records = Vec::new();
+        let mut valid_len = 0;
+        for line in contents.split_inclusive('\n') {
+            if !line.ends_with('\n') {
+                break;
+            }
+            let record = serde_json::from_str(line)
+                .unwrap_or_else(|e| panic!("Failed to parse journal {}: {}", path, e));
+            records.push(record);
+            valid_len += line.len();
+        }
+        if valid_len < contents.len() {
+            eprintln!("Dropping an incomplete last record from {}", path);
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .unwrap_or_else(|e| panic!("Failed to open journal {}: {}", path, e));
+        file.set_len(valid_len as u64)
+            .unwrap_or_else(|e| panic!("Failed to truncate journal {}: {}", path, e));
+        (Journal { file: Mutex::new(file) }, records)
+    }
+
+    pub fn append<T: Serialize>(&self, record: &T) {
+        let mut line = serde_json::to_string(record).unwrap();
+        line.push('\n');
+        self.file
+            .lock()
+            .unwrap()
+            .write_all(line.as_bytes())
+            .expect("Failed to write journal record");
+    }
+}
+
+/// One finished game as recorded in a journal.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameRecord {
+    /// Which game of the run's schedule this was.
+    pub item: usize,
+    /// File name of the game log in the output directory.
+    pub log_file: String,
+    /// Variant index of each seat.
+    pub variants: Vec<usize>,
+    /// Final ranking of each seat.
+    pub rankings: Vec<Ranking>,
+    pub player_time_ms: Vec<u64>,
+    pub player_iterations: Vec<u64>,
+}
+
+impl GameRecord {
+    pub fn new(item: usize, log_file: String, log: &GameRunOutput, variants: Vec<usize>) -> Self {
+        GameRecord {
+            item,
+            log_file,
+            variants,
+            rankings: final_rankings(log),
+            player_time_ms: log.player_time_ms.clone(),
+            player_iterations: log.player_iterations.clone(),
+        }
+    }
+
+    /// The players as `(variant index, ranking)`, in seat order.
+    pub fn players(&self) -> Vec<(usize, Ranking)> {
+        self.variants.iter().copied().zip(self.rankings.iter().copied()).collect()
+    }
+
+    pub fn shares(&self) -> PlayerShares {
+        self.variants.iter().copied().zip(win_shares(&self.rankings)).collect()
+    }
+}
+
+/// Deletes the game logs of `batch_id` that no journal record refers to:
+/// games that were still being written when the run was interrupted, and
+/// that the resumed run plays again.
+pub fn remove_unrecorded_logs(output: &str, batch_id: &str, records: &[GameRecord]) {
+    let recorded: HashSet<&str> = records.iter().map(|r| r.log_file.as_str()).collect();
+    let marker = format!("-{}-", batch_id);
+    let entries = std::fs::read_dir(output).unwrap_or_else(|e| panic!("Failed to read {}: {}", output, e));
+    let mut removed = 0;
+    for entry in entries {
+        let name = entry.unwrap().file_name().to_string_lossy().into_owned();
+        let is_log = name.starts_with("game-") && (name.ends_with(".json") || name.ends_with(".json.tmp"));
+        if is_log && name.contains(&marker) && !recorded.contains(name.as_str()) {
+            let path = format!("{}/{}", output, name);
+            std::fs::remove_file(&path).unwrap_or_else(|e| panic!("Failed to remove {}: {}", path, e));
+            removed += 1;
+        }
+    }
+    if removed > 0 {
+        eprintln!("Removed {} game logs of unfinished games", removed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test, removed when dropped.
+    struct TempDir(String);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("colori-checkpoint-{}-{}", std::process::id(), name));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            TempDir(path.to_str().unwrap().to_string())
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn record(item: usize, log_file: &str) -> GameRecord {
+        GameRecord {
+            item,
+            log_file: log_file.to_string(),
+            variants: vec![0, 1],
+            rankings: vec![(10, 1, 0), (8, 0, 0)],
+            player_time_ms: vec![1, 2],
+            player_iterations: vec![3, 4],
+        }
+    }
+
+    #[test]
+    fn test_resume_drops_incomplete_last_line() {
+        let dir = TempDir::new("incomplete");
+        let journal = Journal::create(&dir.0, "test");
+        journal.append(&1u32);
+        journal.append(&2u32);
+        drop(journal);
+        let path = journal_path(&dir.0, "test");
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"3").unwrap();
+        drop(file);
+
+        let (journal, records) = Journal::resume::<u32>(&dir.0, "test");
+        assert_eq!(records, vec![1, 2]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1\n2\n");
+        journal.append(&4u32);
+        drop(journal);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1\n2\n4\n");
+        let (_, records) = Journal::resume::<u32>(&dir.0, "test");
+        assert_eq!(records, vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn test_resume_without_journal_is_empty() {
+        let dir = TempDir::new("missing");
+        let (journal, records) = Journal::resume::<u32>(&dir.0, "test");
+        assert!(records.is_empty());
+        journal.append(&7u32);
+        drop(journal);
+        assert_eq!(std::fs::read_to_string(journal_path(&dir.0, "test")).unwrap(), "7\n");
+    }
+
+    #[test]
+    fn test_remove_unrecorded_logs_keeps_other_files() {
+        let dir = TempDir::new("logs");
+        let files = [
+            "game-100-batch1-0.json",
+            "game-101-batch1-1.json",
+            "game-102-batch1-2.json.tmp",
+            "game-103-batch2-0.json",
+            "tournament-journal.jsonl",
+            "notes-batch1-.json",
+        ];
+        for name in files {
+            std::fs::write(format!("{}/{}", dir.0, name), "{}").unwrap();
+        }
+        remove_unrecorded_logs(&dir.0, "batch1", &[record(0, "game-100-batch1-0.json")]);
+
+        let mut left: Vec<String> = std::fs::read_dir(&dir.0)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            vec!["game-100-batch1-0.json", "game-103-batch2-0.json", "notes-batch1-.json", "tournament-journal.jsonl"]
+        );
+    }
+}
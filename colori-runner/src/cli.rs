@@ -1,7 +1,8 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use colori_core::ismcts::MctsConfig;
+use colori_core::rollout_policy::RolloutPolicyKind;
 use colori_core::scoring::HeuristicParams;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 // ── Top-level CLI ──
 
@@ -16,6 +17,11 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub output: Option<String>,
 
+    /// Continue the interrupted run whose checkpoint is in the output
+    /// directory, with the arguments it was started with
+    #[arg(long, global = true)]
+    pub resume: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -24,15 +30,25 @@ pub struct Cli {
 pub enum Commands {
     /// Run batch game simulations
     Simulate(SimulateArgs),
-    /// Run a round-robin tournament between variants
+    /// Run a tournament between variants and fit ratings
     Tournament(TournamentArgs),
     /// Train heuristic params using genetic algorithm
     Train(TrainArgs),
+    /// Generate self-play training data from ISMCTS games
+    Selfplay(SelfplayArgs),
+    /// Count choice sequences from a seed position to check move generation
+    Perft(PerftArgs),
+    /// Play two variants head-to-head until an SPRT accepts or rejects an Elo bound
+    Sprt(SprtArgs),
+    /// Generate variants from a base variant over a grid or random sample of
+    /// field values, and rank them in a tournament
+    Sweep(SweepArgs),
 }
 
 // ── Subcommand args ──
 
-#[derive(Parser)]
+#[derive(Parser, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SimulateArgs {
     /// Number of games to simulate
     #[arg(long, default_value_t = 10_000)]
@@ -57,9 +73,18 @@ pub struct SimulateArgs {
     /// Max rounds for solo mode (1 variant = solo; ignored for multiplayer)
     #[arg(long, default_value_t = 5)]
     pub max_rounds: u32,
+    /// Play every deal once per seating, rotating the variants through the
+    /// seats, and report results paired by deal
+    #[arg(long)]
+    pub duplicate: bool,
+
+    /// Base seed for duplicate deals; deal N is dealt from seed + N
+    #[arg(long)]
+    pub seed: Option<u64>,
 }
 
-#[derive(Parser)]
+#[derive(Parser, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TournamentArgs {
     /// Number of games to simulate
     #[arg(long, default_value_t = 10_000)]
@@ -72,11 +97,100 @@ pub struct TournamentArgs {
     /// Path to variants JSON file
     #[arg(long, default_value = "variants.json")]
     pub variants_file: String,
+
+    /// Players per game (2-4); each game seats that many distinct variants
+    #[arg(long, default_value_t = 2)]
+    pub players: usize,
+
+    /// Which variants meet in which seats
+    #[arg(long, value_enum, default_value_t = ScheduleKind::Random)]
+    pub schedule: ScheduleKind,
+
+    /// Reference variant of a gauntlet, by name (default: the first variant)
+    #[arg(long)]
+    pub reference: Option<String>,
+
+    /// Play every deal once per seating, rotating the variants through the
+    /// seats, and report results paired by deal
+    #[arg(long)]
+    pub duplicate: bool,
+
+    /// Base seed for duplicate deals; deal N is dealt from seed + N
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Ratings file from an earlier tournament to use as priors
+    #[arg(long)]
+    pub priors: Option<String>,
+
+    /// Where to write the fitted ratings (default: ratings-<batch>.json in the output directory)
+    #[arg(long)]
+    pub ratings_file: Option<String>,
 }
 
-#[derive(Parser)]
+#[derive(Clone, Copy, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScheduleKind {
+    /// Random tables with shuffled seats
+    Random,
+    /// Every other variant against the reference variant
+    Gauntlet,
+    /// Pairings by current rating, refitted after every round
+    Swiss,
+    /// Every set of variants at a table in every seating order equally
+    /// often. Plays whole cycles of every seating, so --games must cover at
+    /// least one cycle
+    Balanced,
+}
+
+#[derive(Clone, Copy, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Optimizer {
+    /// Genetic algorithm with tournament selection, crossover and mutation
+    Ga,
+    /// Covariance matrix adaptation evolution strategy
+    CmaEs,
+}
+
+fn default_optimizer() -> Optimizer {
+    Optimizer::Ga
+}
+
+#[derive(Clone, Copy, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Fitness {
+    /// Every game against the baseline params
+    Baseline,
+    /// Against the champions of past generations, the baseline until there
+    /// are any
+    HallOfFame,
+    /// Against other individuals of the generation, drawn at random
+    Sample,
+    /// Against every other individual of the generation
+    RoundRobin,
+}
+
+fn default_fitness() -> Fitness {
+    Fitness::Baseline
+}
+
+fn default_opponents() -> usize {
+    4
+}
+
+fn default_player_mix() -> String {
+    "2".to_string()
+}
+
+#[derive(Parser, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TrainArgs {
-    /// Population size
+    /// Search to run over the heuristic params
+    #[arg(long, value_enum, default_value_t = Optimizer::Ga)]
+    #[serde(default = "default_optimizer")]
+    pub optimizer: Optimizer,
+
+    /// Population size (points sampled per generation for CMA-ES)
     #[arg(long, default_value_t = 20)]
     pub population: usize,
 
@@ -92,14 +206,42 @@ pub struct TrainArgs {
     #[arg(long, default_value_t = 0.15)]
     pub mutation_rate: f64,
 
-    /// Mutation scale (std dev of Gaussian perturbation)
+    /// Mutation scale (std dev of Gaussian perturbation); for CMA-ES, the
+    /// initial step size relative to each seed gene
     #[arg(long, default_value_t = 0.25)]
     pub mutation_scale: f64,
 
+    /// Opponents an individual's fitness is measured against
+    #[arg(long, value_enum, default_value_t = Fitness::Baseline)]
+    #[serde(default = "default_fitness")]
+    pub fitness: Fitness,
+
+    /// Opponents per individual for the hall-of-fame and sample fitness
+    /// modes; the hall of fame uses its most recent champions
+    #[arg(long, default_value_t = 4)]
+    #[serde(default = "default_opponents")]
+    pub opponents: usize,
+
+    /// Table sizes of the evaluation games as `players:weight` pairs, e.g.
+    /// `2:0.4,3:0.3,4:0.3`; the individual's other seats are filled with
+    /// its opponents and fitness is the share of them it finishes ahead of
+    #[arg(long, default_value = "2")]
+    #[serde(default = "default_player_mix")]
+    pub player_mix: String,
+
     /// MCTS iterations for evaluation games
     #[arg(long, default_value_t = 4000)]
     pub eval_iterations: u32,
 
+    /// Also evolve the search settings: exploration constant, progressive
+    /// bias weight, max rollout steps, rollout round horizon and the
+    /// heuristic rollout and draft switches, starting from their defaults.
+    /// Evaluation rollouts then stop at the evolved horizon, as they do in
+    /// simulate; without it they run to the end of the game
+    #[arg(long)]
+    #[serde(default)]
+    pub search_genes: bool,
+
     /// Path to seed heuristic params JSON file
     #[arg(long)]
     pub seed_params: Option<String>,
@@ -109,6 +251,120 @@ pub struct TrainArgs {
     pub baseline_params: Option<String>,
 }
 
+#[derive(Parser, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfplayArgs {
+    /// Number of games to play
+    #[arg(long, default_value_t = 1000)]
+    pub games: usize,
+
+    /// Seed of the run; every game's seed is mixed from it and the game number
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Number of games written to each JSONL shard
+    #[arg(long, default_value_t = 100)]
+    pub games_per_shard: usize,
+
+    /// Path to variants JSON file (one entry per player)
+    #[arg(long, default_value = "variants.json")]
+    pub variants_file: String,
+}
+
+#[derive(Parser)]
+pub struct PerftArgs {
+    /// Number of players
+    #[arg(long, default_value_t = 2)]
+    pub players: usize,
+
+    /// Seed for the deal, the random opening plies and every chance event
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Random choices played from the deal before counting
+    #[arg(long, default_value_t = 0)]
+    pub plies: u32,
+
+    /// Number of choices to count sequences of
+    #[arg(long, default_value_t = 3)]
+    pub depth: u32,
+
+    /// Break the count down by first choice
+    #[arg(long)]
+    pub divide: bool,
+
+    /// Count from the game state in this JSON file instead of a seeded deal;
+    /// --players and --plies are then ignored
+    #[arg(long)]
+    pub state: Option<String>,
+}
+
+#[derive(Parser, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SprtArgs {
+    /// Path to variants JSON file (exactly two entries: the candidate, then the baseline)
+    #[arg(long, default_value = "variants.json")]
+    pub variants_file: String,
+
+    /// Elo difference of the null hypothesis (candidate is no stronger than this)
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    pub elo0: f64,
+
+    /// Elo difference of the alternative hypothesis (candidate is at least this strong)
+    #[arg(long, default_value_t = 20.0, allow_negative_numbers = true)]
+    pub elo1: f64,
+
+    /// False positive rate: probability of accepting elo1 when elo0 holds
+    #[arg(long, default_value_t = 0.05)]
+    pub alpha: f64,
+
+    /// False negative rate: probability of accepting elo0 when elo1 holds
+    #[arg(long, default_value_t = 0.05)]
+    pub beta: f64,
+
+    /// Stop undecided after this many games
+    #[arg(long, default_value_t = 20_000)]
+    pub max_games: usize,
+
+    /// Optional note to include in game logs
+    #[arg(long)]
+    pub note: Option<String>,
+}
+
+#[derive(Parser, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepArgs {
+    /// Path to the sweep spec JSON file: the base variant and the values to try
+    #[arg(long)]
+    pub spec: Option<String>,
+
+    /// Number of games to play
+    #[arg(long, default_value_t = 1000)]
+    pub games: usize,
+
+    /// Players per game (2-4)
+    #[arg(long, default_value_t = 2)]
+    pub players: usize,
+
+    /// Which variants meet in which seats; a gauntlet plays every point
+    /// against the base variant
+    #[arg(long, value_enum, default_value_t = ScheduleKind::Gauntlet)]
+    pub schedule: ScheduleKind,
+
+    /// Play every deal once per seating, rotating the variants through the
+    /// seats, and report results paired by deal
+    #[arg(long)]
+    pub duplicate: bool,
+
+    /// Base seed for duplicate deals; deal N is dealt from seed + N
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Optional note to include in game logs
+    #[arg(long)]
+    pub note: Option<String>,
+}
+
 // ── Variant types ──
 
 #[derive(Clone)]
@@ -142,6 +398,8 @@ struct VariantFileEntry {
     #[serde(default)]
     heuristic_draft: Option<bool>,
     #[serde(default)]
+    rollout_policy: Option<RolloutPolicyKind>,
+    #[serde(default)]
     early_termination: Option<bool>,
     #[serde(default)]
     time_limit_ms: Option<u64>,
@@ -149,6 +407,16 @@ struct VariantFileEntry {
     random_first_pick: Option<bool>,
     #[serde(default)]
     force_max_workshop: Option<bool>,
+    #[serde(default)]
+    rave: Option<bool>,
+    #[serde(default)]
+    rave_equivalence: Option<f64>,
+    #[serde(default)]
+    multi_observer: Option<bool>,
+    #[serde(default)]
+    max_nodes: Option<usize>,
+    #[serde(default)]
+    rollout_round_horizon: Option<u32>,
 }
 
 impl VariantFileEntry {
@@ -164,6 +432,8 @@ impl VariantFileEntry {
             panic!("Variant must specify heuristicParams or heuristicParamsFile");
         };
         let base = MctsConfig::new(heuristic_params);
+        let rollout_policy = RolloutPolicyKind::resolve(self.rollout_policy, self.heuristic_rollout, self.heuristic_draft)
+            .unwrap_or_else(|e| panic!("Invalid variant: {}", e));
         NamedVariant {
             name: self.name,
             ai: MctsConfig {
@@ -174,12 +444,16 @@ impl VariantFileEntry {
                 progressive_bias_weight: self.progressive_bias_weight.unwrap_or(base.progressive_bias_weight),
                 heuristic_params: base.heuristic_params,
                 no_rollout: self.no_rollout.unwrap_or(base.no_rollout),
-                heuristic_rollout: self.heuristic_rollout.unwrap_or(base.heuristic_rollout),
-                heuristic_draft: self.heuristic_draft.unwrap_or(base.heuristic_draft),
+                rollout_policy,
                 early_termination: self.early_termination.unwrap_or(base.early_termination),
                 time_limit_ms: self.time_limit_ms,
                 random_first_pick: self.random_first_pick.unwrap_or(base.random_first_pick),
                 force_max_workshop: self.force_max_workshop.unwrap_or(base.force_max_workshop),
+                rave: self.rave.unwrap_or(base.rave),
+                rave_equivalence: self.rave_equivalence.unwrap_or(base.rave_equivalence),
+                multi_observer: self.multi_observer.unwrap_or(base.multi_observer),
+                max_nodes: self.max_nodes,
+                rollout_round_horizon: self.rollout_round_horizon.unwrap_or(base.rollout_round_horizon),
             },
         }
     }
@@ -198,6 +472,44 @@ pub fn load_variants_from_file(variants_file: &str) -> Vec<NamedVariant> {
         .collect()
 }
 
+/// Parses one entry of a variants file.
+pub fn variant_from_entry(entry: serde_json::Value) -> NamedVariant {
+    let entry: VariantFileEntry =
+        serde_json::from_value(entry).unwrap_or_else(|e| panic!("Invalid variant: {}", e));
+    entry.into_named_variant()
+}
+
+/// A variants file entry giving every setting of `variant`, with its
+/// heuristic params inline.
+pub fn variant_entry(variant: &NamedVariant) -> serde_json::Value {
+    let ai = &variant.ai;
+    let mut entry = serde_json::json!({
+        "name": variant.name,
+        "iterations": ai.iterations,
+        "explorationConstant": ai.exploration_constant,
+        "maxRolloutSteps": ai.max_rollout_steps,
+        "useHeuristicEval": ai.use_heuristic_eval,
+        "progressiveBiasWeight": ai.progressive_bias_weight,
+        "heuristicParams": ai.heuristic_params,
+        "noRollout": ai.no_rollout,
+        "rolloutPolicy": ai.rollout_policy,
+        "earlyTermination": ai.early_termination,
+        "randomFirstPick": ai.random_first_pick,
+        "forceMaxWorkshop": ai.force_max_workshop,
+        "rave": ai.rave,
+        "raveEquivalence": ai.rave_equivalence,
+        "multiObserver": ai.multi_observer,
+        "rolloutRoundHorizon": ai.rollout_round_horizon,
+    });
+    if let Some(ms) = ai.time_limit_ms {
+        entry["timeLimitMs"] = ms.into();
+    }
+    if let Some(nodes) = ai.max_nodes {
+        entry["maxNodes"] = nodes.into();
+    }
+    entry
+}
+
 pub fn parse_inline_variants(variants_str: &str, heuristic_params: HeuristicParams) -> Vec<NamedVariant> {
     variants_str
         .split(',')
@@ -211,6 +523,20 @@ pub fn parse_inline_variants(variants_str: &str, heuristic_params: HeuristicPara
         .collect()
 }
 
+/// Parses a player mix of `players:weight` pairs; a table size without a
+/// weight has weight 1.
+pub fn parse_player_mix(mix: &str) -> Vec<(usize, f64)> {
+    mix.split(',')
+        .map(|entry| {
+            let (players, weight) = entry.trim().split_once(':').unwrap_or((entry.trim(), "1"));
+            let players: usize = players.trim().parse().expect("Invalid --player-mix table size");
+            let weight: f64 = weight.trim().parse().expect("Invalid --player-mix weight");
+            assert!(weight > 0.0, "--player-mix weights must be positive");
+            (players, weight)
+        })
+        .collect()
+}
+
 pub fn load_heuristic_params(path: &str) -> HeuristicParams {
     let contents = std::fs::read_to_string(path)
         .unwrap_or_else(|_| panic!("Failed to read heuristic params file: {}", path));
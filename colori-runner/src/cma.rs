@@ -0,0 +1,271 @@
+//! CMA-ES, the covariance matrix adaptation evolution strategy, following
+//! Hansen's tutorial ("The CMA Evolution Strategy: A Tutorial", 2016) with
+//! positive recombination weights only.
+//!
+//! The search is ask/tell: `ask` samples a generation of candidate points
+//! around the mean, and `tell` takes their fitness (higher is better) and
+//! moves the mean, step size and covariance towards the best half. The whole
+//! state, including the points of the generation being evaluated, is
+//! serializable so that a run can be checkpointed between evaluations.
+
+use serde::{Deserialize, Serialize};
+use wyrand::WyRand;
+
+use crate::genetic::sample_normal;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CmaEs {
+    pub mean: Vec<f64>,
+    pub sigma: f64,
+    covariance: Vec<Vec<f64>>,
+    path_sigma: Vec<f64>,
+    path_c: Vec<f64>,
+    /// Generations told so far.
+    generation: usize,
+    lambda: usize,
+    weights: Vec<f64>,
+    mu_eff: f64,
+    c_sigma: f64,
+    d_sigma: f64,
+    c_c: f64,
+    c_1: f64,
+    c_mu: f64,
+    /// Expected length of a standard normal vector.
+    chi_n: f64,
+    /// The points of the current generation, from the last `ask`.
+    samples: Vec<Vec<f64>>,
+}
+
+impl CmaEs {
+    /// A search starting at `mean` with step size `sigma` and `lambda`
+    /// points per generation.
+    pub fn new(mean: Vec<f64>, sigma: f64, lambda: usize) -> Self {
+        assert!(lambda >= 2, "CMA-ES needs at least 2 points per generation, got {}", lambda);
+        let n = mean.len() as f64;
+        let mu = lambda / 2;
+        let raw: Vec<f64> = (1..=mu).map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln()).collect();
+        let total: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|w| w / total).collect();
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let c_sigma = (mu_eff + 2.0) / (n + mu_eff + 5.0);
+        let d_sigma = 1.0 + 2.0 * (((mu_eff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let c_c = (4.0 + mu_eff / n) / (n + 4.0 + 2.0 * mu_eff / n);
+        let c_1 = 2.0 / ((n + 1.3) * (n + 1.3) + mu_eff);
+        let c_mu = (1.0 - c_1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((n + 2.0) * (n + 2.0) + mu_eff));
+        let chi_n = n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n));
+
+        let dim = mean.len();
+        CmaEs {
+            mean,
+            sigma,
+            covariance: identity(dim),
+            path_sigma: vec![0.0; dim],
+            path_c: vec![0.0; dim],
+            generation: 0,
+            lambda,
+            weights,
+            mu_eff,
+            c_sigma,
+            d_sigma,
+            c_c,
+            c_1,
+            c_mu,
+            chi_n,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Samples the points of the next generation.
+    pub fn ask(&mut self, rng: &mut WyRand) -> &[Vec<f64>] {
+        let dim = self.mean.len();
+        let (values, vectors) = eigen_symmetric(&self.covariance);
+        let scales: Vec<f64> = values.iter().map(|v| v.max(0.0).sqrt()).collect();
+        self.samples = (0..self.lambda)
+            .map(|_| {
+                let z: Vec<f64> = (0..dim).map(|i| scales[i] * sample_normal(rng, 1.0)).collect();
+                (0..dim)
+                    .map(|row| self.mean[row] + self.sigma * (0..dim).map(|k| vectors[row][k] * z[k]).sum::<f64>())
+                    .collect()
+            })
+            .collect();
+        &self.samples
+    }
+
+    /// Updates the distribution from the fitness of each point of the last
+    /// `ask`.
+    pub fn tell(&mut self, fitness: &[f64]) {
+        let dim = self.mean.len();
+        let n = dim as f64;
+        let mut order: Vec<usize> = (0..self.samples.len()).collect();
+        order.sort_by(|&a, &b| fitness[b].partial_cmp(&fitness[a]).unwrap());
+
+        // Steps of the selected points from the old mean, in units of sigma
+        let steps: Vec<Vec<f64>> = order[..self.weights.len()]
+            .iter()
+            .map(|&i| (0..dim).map(|d| (self.samples[i][d] - self.mean[d]) / self.sigma).collect())
+            .collect();
+        let step: Vec<f64> = (0..dim)
+            .map(|d| self.weights.iter().zip(&steps).map(|(w, y)| w * y[d]).sum())
+            .collect();
+        for (m, y) in self.mean.iter_mut().zip(&step) {
+            *m += self.sigma * y;
+        }
+
+        // Step size path, through C^(-1/2)
+        let (values, vectors) = eigen_symmetric(&self.covariance);
+        let projected: Vec<f64> = (0..dim)
+            .map(|k| (0..dim).map(|d| vectors[d][k] * step[d]).sum::<f64>() / values[k].max(1e-300).sqrt())
+            .collect();
+        let whitened: Vec<f64> = (0..dim)
+            .map(|d| (0..dim).map(|k| vectors[d][k] * projected[k]).sum())
+            .collect();
+        let sigma_rate = (self.c_sigma * (2.0 - self.c_sigma) * self.mu_eff).sqrt();
+        for (p, w) in self.path_sigma.iter_mut().zip(&whitened) {
+            *p = (1.0 - self.c_sigma) * *p + sigma_rate * w;
+        }
+        self.generation += 1;
+        let path_sigma_norm = self.path_sigma.iter().map(|p| p * p).sum::<f64>().sqrt();
+        let decay = 1.0 - (1.0 - self.c_sigma).powi(2 * self.generation as i32);
+        let stalled = path_sigma_norm / decay.sqrt() >= (1.4 + 2.0 / (n + 1.0)) * self.chi_n;
+        let h_sigma = if stalled { 0.0 } else { 1.0 };
+
+        // Covariance path and rank-one plus rank-mu update
+        let c_rate = (self.c_c * (2.0 - self.c_c) * self.mu_eff).sqrt();
+        for (p, y) in self.path_c.iter_mut().zip(&step) {
+            *p = (1.0 - self.c_c) * *p + h_sigma * c_rate * y;
+        }
+        let correction = (1.0 - h_sigma) * self.c_c * (2.0 - self.c_c);
+        for i in 0..dim {
+            for j in 0..dim {
+                let rank_mu: f64 = self.weights.iter().zip(&steps).map(|(w, y)| w * y[i] * y[j]).sum();
+                self.covariance[i][j] = (1.0 - self.c_1 - self.c_mu) * self.covariance[i][j]
+                    + self.c_1 * (self.path_c[i] * self.path_c[j] + correction * self.covariance[i][j])
+                    + self.c_mu * rank_mu;
+            }
+        }
+
+        self.sigma *= ((self.c_sigma / self.d_sigma) * (path_sigma_norm / self.chi_n - 1.0)).exp();
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect()
+}
+
+/// Eigenvalues and eigenvectors (as the columns of the second result) of a
+/// symmetric matrix, by cyclic Jacobi rotations.
+fn eigen_symmetric(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a: Vec<Vec<f64>> = matrix.to_vec();
+    let mut v = identity(n);
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        let diagonal: f64 = (0..n).map(|i| a[i][i] * a[i][i]).sum();
+        if off_diagonal <= 1e-24 * diagonal {
+            break;
+        }
+        for p in 0..n {
+            for q in (p + 1)..n {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (row_p, row_q) = (a[p].clone(), a[q].clone());
+                for (k, (apk, aqk)) in row_p.into_iter().zip(row_q).enumerate() {
+                    a[p][k] = c * apk - s * aqk;
+                    a[q][k] = s * apk + c * aqk;
+                }
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    ((0..n).map(|i| a[i][i]).collect(), v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn sphere(x: &[f64]) -> f64 {
+        x.iter().map(|v| (v - 3.0) * (v - 3.0)).sum()
+    }
+
+    fn run(cma: &mut CmaEs, generations: usize, rng: &mut WyRand) {
+        for _ in 0..generations {
+            let fitness: Vec<f64> = cma.ask(rng).iter().map(|x| -sphere(x)).collect();
+            cma.tell(&fitness);
+        }
+    }
+
+    #[test]
+    fn test_converges_on_sphere() {
+        let mut rng = WyRand::seed_from_u64(1);
+        let mut cma = CmaEs::new(vec![0.0; 5], 1.0, 10);
+        run(&mut cma, 200, &mut rng);
+        assert!(sphere(&cma.mean) < 1e-6, "mean {:?} did not reach the optimum", cma.mean);
+        assert!(cma.sigma < 1e-2, "sigma {} did not shrink", cma.sigma);
+    }
+
+    #[test]
+    fn test_smallest_population_makes_progress() {
+        let mut rng = WyRand::seed_from_u64(2);
+        let mut cma = CmaEs::new(vec![0.0; 3], 1.0, 2);
+        let start = sphere(&cma.mean);
+        run(&mut cma, 300, &mut rng);
+        assert!(cma.mean.iter().chain([&cma.sigma]).all(|v| v.is_finite()));
+        assert!(sphere(&cma.mean) < start / 100.0, "mean {:?} barely moved", cma.mean);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 2 points")]
+    fn test_rejects_single_point_generations() {
+        CmaEs::new(vec![0.0; 3], 1.0, 1);
+    }
+
+    #[test]
+    fn test_resumes_identically_from_serialized_state() {
+        let mut rng = WyRand::seed_from_u64(3);
+        let mut cma = CmaEs::new(vec![0.0; 4], 0.5, 8);
+        run(&mut cma, 5, &mut rng);
+        cma.ask(&mut rng);
+
+        let mut restored: CmaEs = serde_json::from_str(&serde_json::to_string(&cma).unwrap()).unwrap();
+        let fitness: Vec<f64> = cma.samples.iter().map(|x| -sphere(x)).collect();
+        cma.tell(&fitness);
+        restored.tell(&fitness);
+        assert_eq!(cma.mean, restored.mean);
+        assert_eq!(cma.sigma, restored.sigma);
+    }
+
+    #[test]
+    fn test_eigen_symmetric_reconstructs_matrix() {
+        let matrix = vec![vec![4.0, 1.0, 0.5], vec![1.0, 3.0, 0.2], vec![0.5, 0.2, 2.0]];
+        let (values, vectors) = eigen_symmetric(&matrix);
+        for i in 0..3 {
+            for j in 0..3 {
+                let rebuilt: f64 = (0..3).map(|k| vectors[i][k] * values[k] * vectors[j][k]).sum();
+                assert!((rebuilt - matrix[i][j]).abs() < 1e-9, "entry ({}, {}) is {}", i, j, rebuilt);
+            }
+        }
+    }
+}
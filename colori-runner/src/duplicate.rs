@@ -0,0 +1,178 @@
+//! Duplicate deals: the same deal replayed with the variants rotated through
+//! every seat, so that card luck is shared out evenly between them.
+//!
+//! A deal is a seed for every chance outcome of a game: the initial state,
+//! the draft and sell card decks and every draw. The AIs search with their
+//! own generator, so replaying a deal gives the same cards for as long as the
+//! players make the same choices; once their choices differ, so do the
+//! decks drawn from, and the draws diverge from there.
+
+use std::sync::Mutex;
+
+use crate::ratings::Ranking;
+
+/// The `(variant index, win share)` of each player of a game.
+pub type PlayerShares = Vec<(usize, f64)>;
+
+/// Each seat's share of the win in a finished game, from the final ranking
+/// of each seat: 1 for the sole winner, split evenly between the players
+/// tied for first.
+pub fn win_shares(rankings: &[Ranking]) -> Vec<f64> {
+    let best = *rankings.iter().max().unwrap();
+    let winners = rankings.iter().filter(|&&r| r == best).count();
+    rankings
+        .iter()
+        .map(|&r| if r == best { 1.0 / winners as f64 } else { 0.0 })
+        .collect()
+}
+
+#[derive(Default, Clone)]
+struct VariantDeals {
+    /// Win share of every game played.
+    games: Vec<f64>,
+    /// Mean win share over the seatings of each deal.
+    deals: Vec<f64>,
+}
+
+/// Win shares of each variant, kept both per game and per deal so that the
+/// paired standard error can be set against the unpaired one.
+pub struct PairedStats {
+    labels: Vec<String>,
+    variants: Mutex<Vec<VariantDeals>>,
+}
+
+impl PairedStats {
+    pub fn new(labels: Vec<String>) -> Self {
+        let n = labels.len();
+        PairedStats {
+            labels,
+            variants: Mutex::new(vec![VariantDeals::default(); n]),
+        }
+    }
+
+    /// Records every seating of one deal.
+    pub fn record_deal(&self, games: &[PlayerShares]) {
+        let mut variants = self.variants.lock().unwrap();
+        let mut totals = vec![(0.0, 0usize); variants.len()];
+        for game in games {
+            for &(variant, share) in game {
+                variants[variant].games.push(share);
+                totals[variant].0 += share;
+                totals[variant].1 += 1;
+            }
+        }
+        for (variant, (total, count)) in totals.into_iter().enumerate() {
+            if count > 0 {
+                variants[variant].deals.push(total / count as f64);
+            }
+        }
+    }
+
+    pub fn print_summary(&self) {
+        let variants = self.variants.lock().unwrap();
+        let max_label = self.labels.iter().map(|l| l.len()).max().unwrap_or(7).max(7);
+
+        eprintln!();
+        eprintln!("=== Duplicate Results ===");
+        eprintln!();
+        eprintln!(
+            "{:<width$}  {:>5}  {:>5}  {:>9}  {:>9}  {:>11}",
+            "Variant",
+            "Deals",
+            "Games",
+            "Win Share",
+            "Paired SE",
+            "Unpaired SE",
+            width = max_label,
+        );
+        eprintln!("{}", "-".repeat(max_label + 51));
+        for (label, v) in self.labels.iter().zip(variants.iter()) {
+            eprintln!(
+                "{:<width$}  {:>5}  {:>5}  {:>8.1}%  {:>8.2}%  {:>10.2}%",
+                label,
+                v.deals.len(),
+                v.games.len(),
+                mean(&v.games) * 100.0,
+                standard_error(&v.deals) * 100.0,
+                standard_error(&v.games) * 100.0,
+                width = max_label,
+            );
+        }
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Standard error of the mean of `values`; zero for fewer than two values.
+fn standard_error(values: &[f64]) -> f64 {
+    let n = values.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    let variance = values.iter().map(|v| (v - m) * (v - m)).sum::<f64>() / (n - 1) as f64;
+    (variance / n as f64).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::NamedVariant;
+    use crate::simulation::{Seating, run_game};
+    use colori_core::ismcts::MctsConfig;
+    use rand::SeedableRng;
+    use wyrand::WyRand;
+
+    #[test]
+    fn test_win_shares_split_ties() {
+        assert_eq!(win_shares(&[(10, 1, 0), (8, 2, 0), (9, 0, 0)]), vec![1.0, 0.0, 0.0]);
+        // Ties break on sell cards, then color wheel total
+        assert_eq!(win_shares(&[(10, 1, 3), (10, 2, 0), (10, 1, 5)]), vec![0.0, 1.0, 0.0]);
+        assert_eq!(win_shares(&[(10, 1, 0), (7, 0, 0), (10, 1, 0)]), vec![0.5, 0.0, 0.5]);
+        assert_eq!(win_shares(&[(4, 0, 0); 4]), vec![0.25; 4]);
+    }
+
+    #[test]
+    fn test_record_deal_averages_per_deal() {
+        let stats = PairedStats::new(vec!["a".into(), "b".into(), "c".into()]);
+        // Two seatings of a 2-player deal between a and b; c sits out
+        stats.record_deal(&[vec![(0, 1.0), (1, 0.0)], vec![(1, 0.5), (0, 0.5)]]);
+        stats.record_deal(&[vec![(0, 0.0), (2, 1.0)], vec![(2, 1.0), (0, 0.0)]]);
+        let variants = stats.variants.lock().unwrap();
+        assert_eq!(variants[0].games, vec![1.0, 0.5, 0.0, 0.0]);
+        assert_eq!(variants[0].deals, vec![0.75, 0.0]);
+        assert_eq!(variants[1].deals, vec![0.25]);
+        assert_eq!(variants[2].games, vec![1.0, 1.0]);
+        assert_eq!(variants[2].deals, vec![1.0]);
+    }
+
+    #[test]
+    fn test_standard_error() {
+        assert_eq!(standard_error(&[]), 0.0);
+        assert_eq!(standard_error(&[0.7]), 0.0);
+        assert_eq!(standard_error(&[0.5, 0.5, 0.5]), 0.0);
+        // Sample variance 0.5 over 2 values
+        assert!((standard_error(&[0.0, 1.0]) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_deal_is_independent_of_ai_rng() {
+        let params = serde_json::from_str(include_str!("../../genetic-algorithm/batch-lki08w-gen-32.json")).unwrap();
+        let ai = MctsConfig { iterations: 5, ..MctsConfig::new(params) };
+        let variants = vec![NamedVariant { name: None, ai: ai.clone() }, NamedVariant { name: None, ai }];
+        let deal = |deal_seed: u64, ai_seed: u64| {
+            let mut rng = WyRand::seed_from_u64(ai_seed);
+            let log = run_game(0, &variants, None, None, Seating::Rotated(0), Some(deal_seed), &mut rng);
+            assert_eq!(log.deal_seed, deal_seed);
+            serde_json::to_string(&(&log.initial_state, &log.initial_draws)).unwrap()
+        };
+        let first = deal(11, 1);
+        assert_eq!(first, deal(11, 2));
+        assert_ne!(first, deal(12, 1));
+    }
+}
@@ -1,12 +1,19 @@
-use crate::cli::{TrainArgs, load_heuristic_params};
+use crate::checkpoint::{self, Checkpoint};
+use crate::cli::{Fitness, NamedVariant, Optimizer, TrainArgs, load_heuristic_params, parse_player_mix, variant_entry};
+use crate::duplicate::win_shares;
+use crate::ratings::Ranking;
+use crate::cma::CmaEs;
 use colori_core::colori_game::apply_choice_to_state;
 use colori_core::draw_phase::execute_draw_phase;
 use colori_core::ismcts::{ismcts, MctsConfig};
+use colori_core::rollout_policy::RolloutPolicyKind;
 use colori_core::scoring::{calculate_score, HeuristicParams};
 use colori_core::setup::create_initial_game_state;
-use colori_core::types::GamePhase;
+use colori_core::types::{GamePhase, MAX_PLAYERS};
 use rand::RngExt;
+use rand::seq::SliceRandom;
 use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
 use std::sync::atomic::Ordering;
 use std::time::Instant;
 use wyrand::WyRand;
@@ -69,15 +76,33 @@ enum Gene {
     RolloutWsActionWorkshopPerCard = 48,
     RolloutWsActionColorDemandMultiplier = 49,
     LinseedOilQuality = 50,
+    SellCardReachWeight = 51,
 }
 
-const NUM_GENES: usize = 51;
+const NUM_GENES: usize = 52;
+
+/// Gene indices of the search settings, which follow the heuristic genes
+/// when `--search-genes` is set.
+#[repr(usize)]
+#[derive(Clone, Copy)]
+enum SearchGene {
+    ExplorationConstant = NUM_GENES,
+    ProgressiveBiasWeight,
+    MaxRolloutSteps,
+    RolloutRoundHorizon,
+    HeuristicRollout,
+    HeuristicDraft,
+}
+
+const NUM_SEARCH_GENES: usize = NUM_GENES + 6;
 
 trait GeneTarget: Clone {
     fn to_genes(&self) -> Vec<f64>;
     fn from_genes(genes: &[f64], base: &Self) -> Self;
     fn integer_gene_indices() -> Vec<usize>;
     fn probability_gene_indices() -> Vec<usize>;
+    /// Genes that are either 0 or 1, flipped rather than scaled by mutation.
+    fn switch_gene_indices() -> Vec<usize>;
 }
 
 impl GeneTarget for HeuristicParams {
@@ -109,6 +134,7 @@ impl GeneTarget for HeuristicParams {
         v[DualMaterialQuality as usize] = self.dual_material_quality;
         v[SellCardMaterialAlignment as usize] = self.sell_card_material_alignment;
         v[SellCardColorAlignment as usize] = self.sell_card_color_alignment;
+        v[SellCardReachWeight as usize] = self.sell_card_reach_weight;
         v[HeuristicLookahead as usize] = self.heuristic_lookahead as f64;
         v[RolloutEpsilon as usize] = self.rollout_epsilon;
         v[RolloutSellAffordableMultiplier as usize] = self.rollout_sell_affordable_multiplier as f64;
@@ -157,6 +183,7 @@ impl GeneTarget for HeuristicParams {
             dual_material_quality: v[DualMaterialQuality as usize],
             sell_card_material_alignment: v[SellCardMaterialAlignment as usize],
             sell_card_color_alignment: v[SellCardColorAlignment as usize],
+            sell_card_reach_weight: v[SellCardReachWeight as usize],
             heuristic_round_threshold: base.heuristic_round_threshold,
             heuristic_lookahead: (v[HeuristicLookahead as usize].round() as u32).max(1),
             alum_quality: v[AlumQuality as usize],
@@ -231,11 +258,86 @@ impl GeneTarget for HeuristicParams {
             Gene::RolloutEndTurnProbabilityLate as usize,
         ]
     }
+
+    fn switch_gene_indices() -> Vec<usize> {
+        Vec::new()
+    }
+}
+
+impl GeneTarget for MctsConfig {
+    fn to_genes(&self) -> Vec<f64> {
+        use SearchGene::*;
+        let (heuristic_rollout, heuristic_draft) = match self.rollout_policy {
+            RolloutPolicyKind::Heuristic { heuristic_draft } => (true, heuristic_draft),
+            RolloutPolicyKind::Random { heuristic_draft } => (false, heuristic_draft),
+            RolloutPolicyKind::Greedy { .. } | RolloutPolicyKind::Custom(_) => (true, false),
+        };
+        let mut v = self.heuristic_params.to_genes();
+        v.resize(NUM_SEARCH_GENES, 0.0);
+        v[ExplorationConstant as usize] = self.exploration_constant;
+        v[ProgressiveBiasWeight as usize] = self.progressive_bias_weight;
+        v[MaxRolloutSteps as usize] = self.max_rollout_steps as f64;
+        v[RolloutRoundHorizon as usize] = self.rollout_round_horizon as f64;
+        v[HeuristicRollout as usize] = if heuristic_rollout { 1.0 } else { 0.0 };
+        v[HeuristicDraft as usize] = if heuristic_draft { 1.0 } else { 0.0 };
+        v
+    }
+
+    fn from_genes(v: &[f64], base: &Self) -> Self {
+        use SearchGene::*;
+        MctsConfig {
+            heuristic_params: HeuristicParams::from_genes(&v[..NUM_GENES], &base.heuristic_params),
+            exploration_constant: v[ExplorationConstant as usize].max(0.0),
+            progressive_bias_weight: v[ProgressiveBiasWeight as usize].max(0.0),
+            max_rollout_steps: v[MaxRolloutSteps as usize].round().max(1.0) as u32,
+            rollout_round_horizon: v[RolloutRoundHorizon as usize].round().max(0.0) as u32,
+            rollout_policy: RolloutPolicyKind::from_flags(
+                v[HeuristicRollout as usize] >= 0.5,
+                v[HeuristicDraft as usize] >= 0.5,
+            ),
+            ..base.clone()
+        }
+    }
+
+    fn integer_gene_indices() -> Vec<usize> {
+        let mut indices = HeuristicParams::integer_gene_indices();
+        indices.extend([SearchGene::MaxRolloutSteps as usize, SearchGene::RolloutRoundHorizon as usize]);
+        indices
+    }
+
+    fn probability_gene_indices() -> Vec<usize> {
+        HeuristicParams::probability_gene_indices()
+    }
+
+    fn switch_gene_indices() -> Vec<usize> {
+        vec![SearchGene::HeuristicRollout as usize, SearchGene::HeuristicDraft as usize]
+    }
+}
+
+/// The config an individual plays with. Without search genes, only its
+/// heuristic params differ from `base`.
+fn individual_config(genes: &[f64], base: &MctsConfig) -> MctsConfig {
+    if genes.len() == NUM_SEARCH_GENES {
+        MctsConfig::from_genes(genes, base)
+    } else {
+        MctsConfig {
+            heuristic_params: HeuristicParams::from_genes(genes, &base.heuristic_params),
+            ..base.clone()
+        }
+    }
+}
+
+fn switch_gene_indices(num_genes: usize) -> Vec<usize> {
+    if num_genes == NUM_SEARCH_GENES {
+        MctsConfig::switch_gene_indices()
+    } else {
+        HeuristicParams::switch_gene_indices()
+    }
 }
 
 // ── Genetic algorithm ──
 
-fn sample_normal(rng: &mut WyRand, scale: f64) -> f64 {
+pub fn sample_normal(rng: &mut WyRand, scale: f64) -> f64 {
     // Box-Muller transform
     let u1: f64 = rng.random::<f64>().max(1e-10);
     let u2: f64 = rng.random::<f64>();
@@ -262,29 +364,17 @@ fn tournament_select(
     fitness[best_idx].0
 }
 
-fn run_ga_game(
-    params_a: &HeuristicParams,
-    params_b: &HeuristicParams,
-    eval_iterations: u32,
-    rng: &mut WyRand,
-) -> (f64, f64) {
-    let num_players = 2;
+/// Plays one game with `configs[seat]` in each seat and returns the final
+/// ranking of each seat.
+///
+/// Rollouts run to the end of the game unless `limit_rollouts` is set, in
+/// which case they stop at each config's rollout round horizon. Training
+/// only limits them when the horizon is one of the evolved genes.
+fn run_ga_game(configs: &[&MctsConfig], limit_rollouts: bool, rng: &mut WyRand) -> Vec<Ranking> {
+    let num_players = configs.len();
     let ai_players = vec![true; num_players];
     let mut state = create_initial_game_state(num_players, &ai_players, rng);
 
-    let configs = [
-        MctsConfig {
-            iterations: eval_iterations,
-            use_heuristic_eval: true,
-            ..MctsConfig::new(params_a.clone())
-        },
-        MctsConfig {
-            iterations: eval_iterations,
-            use_heuristic_eval: true,
-            ..MctsConfig::new(params_b.clone())
-        },
-    ];
-
     execute_draw_phase(&mut state, rng);
 
     let max_steps = 5000;
@@ -304,132 +394,422 @@ fn run_ga_game(
             _ => continue,
         };
 
-        let config = &configs[player_index];
-        let result = ismcts(&state, player_index, config, None, None, rng);
+        let config = configs[player_index];
+        let max_rollout_round = limit_rollouts.then(|| config.max_rollout_round(state.round));
+        let result = ismcts(&state, player_index, config, max_rollout_round, None, rng);
         apply_choice_to_state(&mut state, &result.choice, rng);
     }
 
-    let score_a = calculate_score(&state.players[0]);
-    let score_b = calculate_score(&state.players[1]);
-    if score_a > score_b {
-        (1.0, 0.0)
-    } else if score_b > score_a {
-        (0.0, 1.0)
+    state
+        .players
+        .iter()
+        .map(|p| (calculate_score(p), p.completed_sell_cards.len() as u32, p.color_wheel.counts.iter().sum()))
+        .collect()
+}
+
+/// Fitness reward of `seat`: the share of its opponents it finished ahead
+/// of, ties counting half, so 1 for first place and 0 for last.
+fn rank_reward(rankings: &[Ranking], seat: usize) -> f64 {
+    let ahead: f64 = rankings
+        .iter()
+        .enumerate()
+        .filter(|&(other, _)| other != seat)
+        .map(|(_, r)| match rankings[seat].cmp(r) {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.0,
+        })
+        .sum();
+    ahead / (rankings.len() - 1) as f64
+}
+
+fn format_table_win_rates(mix: &[(usize, f64)], results: &[TableResults]) -> String {
+    mix.iter()
+        .zip(results)
+        .map(|(&(players, _), r)| format!("{}p {:.4}", players, r.win_rate()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The entry of the player mix each evaluation game is played with, in
+/// proportion to the weights (largest remainder).
+fn table_sizes(mix: &[(usize, f64)], games: usize) -> Vec<usize> {
+    let total: f64 = mix.iter().map(|&(_, w)| w).sum();
+    let quotas: Vec<f64> = mix.iter().map(|&(_, w)| w / total * games as f64).collect();
+    let mut counts: Vec<usize> = quotas.iter().map(|q| q.floor() as usize).collect();
+    let mut by_remainder: Vec<usize> = (0..mix.len()).collect();
+    by_remainder.sort_by(|&a, &b| (quotas[b] - quotas[b].floor()).partial_cmp(&(quotas[a] - quotas[a].floor())).unwrap());
+    let missing = games - counts.iter().sum::<usize>();
+    for &i in by_remainder.iter().take(missing) {
+        counts[i] += 1;
+    }
+    counts.iter().enumerate().flat_map(|(i, &count)| std::iter::repeat_n(i, count)).collect()
+}
+
+const COMMAND: &str = "train";
+
+/// Where a run stands: the generation being evaluated, its population and
+/// the fitness of the individuals evaluated so far.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrainRun {
+    batch_id: String,
+    generation: usize,
+    population: Vec<Vec<f64>>,
+    /// Total wins of each individual evaluated so far, in population order.
+    wins: Vec<f64>,
+    /// The generator used for selection and mutation.
+    rng: WyRand,
+    /// The search state when training with CMA-ES.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cma: Option<CmaEs>,
+    /// The best individual of each finished generation.
+    #[serde(default)]
+    hall_of_fame: Vec<Vec<f64>>,
+    /// Results of each individual evaluated so far at each table size of the
+    /// player mix.
+    #[serde(default)]
+    table_results: Vec<Vec<TableResults>>,
+}
+
+/// Evaluation games of one individual at one table size.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TableResults {
+    games: usize,
+    /// Sum of the win shares, ties for first splitting the win.
+    wins: f64,
+}
+
+impl TableResults {
+    fn win_rate(&self) -> f64 {
+        if self.games > 0 { self.wins / self.games as f64 } else { 0.0 }
+    }
+}
+
+/// Per-generation CMA-ES summary, saved next to the best individual.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CmaGeneration {
+    generation: usize,
+    sigma: f64,
+    best_fitness: f64,
+    /// The mean as a variants file entry.
+    mean: serde_json::Value,
+}
+
+/// Clamps genes to what the config can hold: non-negative, integer genes
+/// rounded and at least 1, probability genes at most 1 and switch genes 0
+/// or 1.
+fn repair_genes(genes: &mut [f64]) {
+    let (integer, probability) = if genes.len() == NUM_SEARCH_GENES {
+        (MctsConfig::integer_gene_indices(), MctsConfig::probability_gene_indices())
     } else {
-        (0.5, 0.5)
+        (HeuristicParams::integer_gene_indices(), HeuristicParams::probability_gene_indices())
+    };
+    for g in genes.iter_mut() {
+        *g = g.max(0.0);
+    }
+    for idx in integer {
+        genes[idx] = genes[idx].round().max(1.0);
+    }
+    for idx in probability {
+        genes[idx] = genes[idx].clamp(0.0, 1.0);
+    }
+    for idx in switch_gene_indices(genes.len()) {
+        genes[idx] = genes[idx].clamp(0.0, 1.0).round();
     }
 }
 
-pub fn run_genetic_algorithm(args: &TrainArgs, threads: usize, output: &str) {
-    let batch_id = crate::generate_batch_id();
+/// A variants file entry for the config of `genes`.
+fn variant_file_entry(name: String, genes: &[f64], base: &MctsConfig) -> serde_json::Value {
+    variant_entry(&NamedVariant { name: Some(name), ai: individual_config(genes, base) })
+}
 
-    eprintln!(
-        "Genetic Algorithm: population={}, generations={}, games_per_eval={}, eval_iterations={}, \
-         mutation_rate={}, mutation_scale={}, threads={}",
-        args.population, args.generations, args.games_per_eval, args.eval_iterations,
-        args.mutation_rate, args.mutation_scale, threads
-    );
+/// The size of a relative change to gene `g`: its magnitude, or 1 for a
+/// gene at 0, which scaling would leave at 0 forever.
+fn gene_scale(g: f64) -> f64 {
+    if g == 0.0 { 1.0 } else { g.abs() }
+}
 
-    std::fs::create_dir_all(output).expect("Failed to create output directory");
+/// CMA-ES searches a space where every coordinate is a gene relative to the
+/// seed: 0 is the seed's value and 1 a change by the seed's magnitude (or by
+/// 1 for genes the seed has at 0), so that one step size suits every gene.
+fn cma_to_genes(point: &[f64], seed_genes: &[f64]) -> Vec<f64> {
+    let mut genes: Vec<f64> = point
+        .iter()
+        .zip(seed_genes)
+        .map(|(x, &g)| g + x * gene_scale(g))
+        .collect();
+    // Points outside the feasible region are evaluated at the nearest
+    // feasible genes; the search itself still sees the original point.
+    repair_genes(&mut genes);
+    genes
+}
+
+/// The opponents individual `i` plays its evaluation games against, in turn.
+fn choose_opponents<'a>(
+    args: &TrainArgs,
+    i: usize,
+    population: &'a [MctsConfig],
+    hall_of_fame: &'a [MctsConfig],
+    baseline: &'a MctsConfig,
+    rng: &mut WyRand,
+) -> Vec<&'a MctsConfig> {
+    let others = || population.iter().enumerate().filter(move |&(j, _)| j != i).map(|(_, p)| p);
+    match args.fitness {
+        Fitness::Baseline => vec![baseline],
+        Fitness::HallOfFame if hall_of_fame.is_empty() => vec![baseline],
+        Fitness::HallOfFame => hall_of_fame[hall_of_fame.len().saturating_sub(args.opponents)..].iter().collect(),
+        Fitness::Sample => {
+            let mut opponents: Vec<&MctsConfig> = others().collect();
+            opponents.shuffle(rng);
+            opponents.truncate(args.opponents);
+            opponents
+        }
+        Fitness::RoundRobin => others().collect(),
+    }
+}
+
+pub fn run_genetic_algorithm(args: &TrainArgs, threads: usize, output: &str, resume: bool) {
+    let resumed: Option<Checkpoint<TrainArgs, TrainRun>> = resume.then(|| checkpoint::load(output, COMMAND));
+    let args = resumed.as_ref().map_or(args, |checkpoint| &checkpoint.args);
+
+    match args.optimizer {
+        Optimizer::Ga => eprintln!(
+            "Genetic Algorithm: population={}, generations={}, games_per_eval={}, eval_iterations={}, \
+             mutation_rate={}, mutation_scale={}, threads={}",
+            args.population, args.generations, args.games_per_eval, args.eval_iterations,
+            args.mutation_rate, args.mutation_scale, threads
+        ),
+        Optimizer::CmaEs => eprintln!(
+            "CMA-ES: population={}, generations={}, games_per_eval={}, eval_iterations={}, \
+             initial sigma={}, threads={}",
+            args.population, args.generations, args.games_per_eval, args.eval_iterations,
+            args.mutation_scale, threads
+        ),
+    }
+
+    let plays_population = matches!(args.fitness, Fitness::Sample | Fitness::RoundRobin);
+    if plays_population && args.population < 2 {
+        eprintln!("The sample and round-robin fitness modes need a population of at least 2");
+        std::process::exit(1);
+    }
+    if matches!(args.optimizer, Optimizer::CmaEs) && args.population < 2 {
+        eprintln!("CMA-ES needs a population of at least 2, since it recombines the best half of each generation");
+        std::process::exit(1);
+    }
+    if args.opponents == 0 {
+        eprintln!("--opponents must be at least 1");
+        std::process::exit(1);
+    }
+    let player_mix = parse_player_mix(&args.player_mix);
+    if let Some(&(players, _)) = player_mix.iter().find(|&&(p, _)| !(2..=MAX_PLAYERS).contains(&p)) {
+        eprintln!("--player-mix: tables must have 2 to {} players, not {}", MAX_PLAYERS, players);
+        std::process::exit(1);
+    }
+    let tables = table_sizes(&player_mix, args.games_per_eval);
 
-    let mut rng = WyRand::from_rng(&mut rand::rng());
+    std::fs::create_dir_all(output).expect("Failed to create output directory");
 
     let seed_params = load_heuristic_params(
         args.seed_params.as_ref().expect("--seed-params is required for training")
     );
-    let seed = &seed_params;
-    let seed_genes = seed.to_genes();
+    // Evaluation games are played with the default search settings unless
+    // they are evolved too
+    let seed_config = MctsConfig {
+        iterations: args.eval_iterations,
+        use_heuristic_eval: true,
+        ..MctsConfig::new(seed_params)
+    };
+    let seed = &seed_config;
+    let seed_genes = if args.search_genes { seed.to_genes() } else { seed.heuristic_params.to_genes() };
+
+    let num_genes = seed_genes.len();
+
+    let (batch_id, first_generation, mut population, mut evaluated, mut rng, mut cma, mut hall_of_fame, mut table_results) = match &resumed {
+        Some(checkpoint) => {
+            let run = &checkpoint.state;
+            if run.generation < args.generations {
+                eprintln!(
+                    "Resuming at generation {} with {} of {} individuals evaluated",
+                    run.generation + 1,
+                    run.wins.len(),
+                    run.population.len()
+                );
+            }
+            (
+                run.batch_id.clone(),
+                run.generation,
+                run.population.clone(),
+                run.wins.clone(),
+                run.rng.clone(),
+                run.cma.clone(),
+                run.hall_of_fame.clone(),
+                run.table_results.clone(),
+            )
+        }
+        None if matches!(args.optimizer, Optimizer::CmaEs) => {
+            let mut rng = WyRand::from_rng(&mut rand::rng());
 
-    eprintln!("Seeding population from provided params file");
+            eprintln!("Centering the search on the provided params file");
+            let mut cma = CmaEs::new(vec![0.0; num_genes], args.mutation_scale, args.population);
+            let population = cma.ask(&mut rng).iter().map(|x| cma_to_genes(x, &seed_genes)).collect();
+            (crate::generate_batch_id(), 0, population, Vec::new(), rng, Some(cma), Vec::new(), Vec::new())
+        }
+        None => {
+            let mut rng = WyRand::from_rng(&mut rand::rng());
 
-    let num_genes = seed.to_genes().len();
+            eprintln!("Seeding population from provided params file");
 
-    // Initialize population: first individual is seed, rest are perturbed
-    let mut population: Vec<Vec<f64>> = Vec::with_capacity(args.population);
-    population.push(seed_genes.clone());
+            // Initialize population: first individual is seed, rest are perturbed
+            let mut population: Vec<Vec<f64>> = Vec::with_capacity(args.population);
+            population.push(seed_genes.clone());
 
-    for _ in 1..args.population {
-        let mut genes = seed_genes.clone();
-        for g in genes.iter_mut() {
-            use rand::RngExt;
-            let factor = 0.5 + rng.random::<f64>() * 1.5; // [0.5, 2.0)
-            *g *= factor;
-            if *g < 0.0 {
-                *g = 0.0;
+            for _ in 1..args.population {
+                let mut genes = seed_genes.clone();
+                for g in genes.iter_mut() {
+                    use rand::RngExt;
+                    let factor = 0.5 + rng.random::<f64>() * 1.5; // [0.5, 2.0)
+                    *g += gene_scale(*g) * (factor - 1.0);
+                }
+                repair_genes(&mut genes);
+                population.push(genes);
             }
+            (crate::generate_batch_id(), 0, population, Vec::new(), rng, None, Vec::new(), Vec::new())
         }
-        // Clamp integer genes
-        for &idx in &HeuristicParams::integer_gene_indices() {
-            genes[idx] = genes[idx].round().max(1.0);
-        }
-        // Clamp probability genes
-        for &idx in &HeuristicParams::probability_gene_indices() {
-            genes[idx] = genes[idx].clamp(0.0, 1.0);
-        }
-        population.push(genes);
+    };
+
+    // Saved after every evaluated individual and every new generation
+    let save_checkpoint = |generation: usize,
+                           population: &[Vec<f64>],
+                           wins: &[f64],
+                           rng: &WyRand,
+                           cma: &Option<CmaEs>,
+                           hall_of_fame: &[Vec<f64>],
+                           table_results: &[Vec<TableResults>]| {
+        let state = TrainRun {
+            batch_id: batch_id.clone(),
+            generation,
+            population: population.to_vec(),
+            wins: wins.to_vec(),
+            rng: rng.clone(),
+            cma: cma.clone(),
+            hall_of_fame: hall_of_fame.to_vec(),
+            table_results: table_results.to_vec(),
+        };
+        checkpoint::save(output, COMMAND, &Checkpoint { args, state });
+    };
+    if resumed.is_none() {
+        save_checkpoint(0, &population, &evaluated, &rng, &cma, &hall_of_fame, &table_results);
     }
 
-    let baseline_params = args.baseline_params.as_ref()
-        .map(|p| load_heuristic_params(p))
-        .unwrap_or_else(|| seed.clone());
+    let baseline_config = match &args.baseline_params {
+        Some(path) => MctsConfig { heuristic_params: load_heuristic_params(path), ..seed.clone() },
+        None => seed.clone(),
+    };
 
-    for gen in 0..args.generations {
+    for gen in first_generation..args.generations {
         let gen_start = Instant::now();
         let pop_size = population.len();
 
-        let population_params: Vec<HeuristicParams> = population
+        let population_configs: Vec<MctsConfig> = population
             .iter()
-            .map(|g| HeuristicParams::from_genes(g, seed))
+            .map(|g| individual_config(g, seed))
+            .collect();
+        let hall_of_fame_configs: Vec<MctsConfig> = hall_of_fame
+            .iter()
+            .map(|g| individual_config(g, seed))
             .collect();
 
-        let baseline_ref = &baseline_params;
-        let eval_iterations = args.eval_iterations;
         let games_per_eval = args.games_per_eval;
+        let limit_rollouts = args.search_genes;
         let num_threads = threads;
 
         let wins: Vec<std::sync::atomic::AtomicU64> = (0..pop_size)
             .map(|_| std::sync::atomic::AtomicU64::new(0))
             .collect();
 
-        // Evaluate each individual against baseline
-        for i in 0..pop_size {
-            let params = &population_params[i];
+        for (i, &w) in evaluated.iter().enumerate() {
+            wins[i].store((w * 1000.0) as u64, Ordering::Relaxed);
+        }
+        // Checkpoints from before the player mix have no table results
+        table_results.resize(evaluated.len(), vec![TableResults::default(); player_mix.len()]);
+
+        // Evaluate each individual against its opponents
+        for i in evaluated.len()..pop_size {
+            let config = &population_configs[i];
+            let opponents = choose_opponents(
+                args,
+                i,
+                &population_configs,
+                &hall_of_fame_configs,
+                &baseline_config,
+                &mut rng,
+            );
+            let opponents_ref = &opponents;
+            let (player_mix_ref, tables_ref) = (&player_mix, &tables);
             let wins_for_individual = std::sync::atomic::AtomicU64::new(0);
             let wins_ind_ref = &wins_for_individual;
+            let mut individual_tables = vec![TableResults::default(); player_mix.len()];
 
             std::thread::scope(|s| {
-                let games_per_thread = games_per_eval / num_threads;
-                let remainder = games_per_eval % num_threads;
                 let mut handles = Vec::new();
 
                 for t in 0..num_threads {
-                    let count = games_per_thread + if t < remainder { 1 } else { 0 };
-
                     handles.push(s.spawn(move || {
                         let mut rng = WyRand::from_rng(&mut rand::rng());
                         let mut thread_wins = 0.0f64;
-
-                        for _ in 0..count {
-                            let (w, _) = run_ga_game(params, baseline_ref, eval_iterations, &mut rng);
-                            thread_wins += w;
+                        let mut thread_tables = vec![TableResults::default(); player_mix_ref.len()];
+
+                        // Game k seats the individual at k mod the table size,
+                        // and the opponents after those of game k - 1, cycling
+                        for k in (t..games_per_eval).step_by(num_threads) {
+                            let table = tables_ref[k];
+                            let num_players = player_mix_ref[table].0;
+                            let seat = k % num_players;
+                            let mut opponents = (0..num_players - 1)
+                                .map(|j| opponents_ref[(k * (num_players - 1) + j) % opponents_ref.len()]);
+                            let configs: Vec<&MctsConfig> = (0..num_players)
+                                .map(|p| if p == seat { config } else { opponents.next().unwrap() })
+                                .collect();
+                            let rankings = run_ga_game(&configs, limit_rollouts, &mut rng);
+                            thread_wins += rank_reward(&rankings, seat);
+                            thread_tables[table].games += 1;
+                            thread_tables[table].wins += win_shares(&rankings)[seat];
                         }
 
                         wins_ind_ref.fetch_add((thread_wins * 1000.0) as u64, Ordering::Relaxed);
+                        thread_tables
                     }));
                 }
 
                 for h in handles {
-                    h.join().unwrap();
+                    for (total, results) in individual_tables.iter_mut().zip(h.join().unwrap()) {
+                        total.games += results.games;
+                        total.wins += results.wins;
+                    }
                 }
             });
 
             let total_wins = wins_for_individual.load(Ordering::Relaxed) as f64 / 1000.0;
             wins[i].store((total_wins * 1000.0) as u64, Ordering::Relaxed);
             let wr = total_wins / games_per_eval as f64;
-            eprintln!(
-                "  Gen {} [{}/{}] individual {}: win_rate={:.4}",
-                gen + 1, i + 1, pop_size, i, wr
-            );
+            if player_mix.len() > 1 {
+                eprintln!(
+                    "  Gen {} [{}/{}] individual {}: fitness={:.4} (win rate {})",
+                    gen + 1, i + 1, pop_size, i, wr,
+                    format_table_win_rates(&player_mix, &individual_tables)
+                );
+            } else {
+                eprintln!(
+                    "  Gen {} [{}/{}] individual {}: win_rate={:.4}",
+                    gen + 1, i + 1, pop_size, i, wr
+                );
+            }
+            evaluated.push(total_wins);
+            table_results.push(individual_tables);
+            save_checkpoint(gen, &population, &evaluated, &rng, &cma, &hall_of_fame, &table_results);
         }
 
         // Compute fitness
@@ -444,12 +824,16 @@ pub fn run_genetic_algorithm(args: &TrainArgs, threads: usize, output: &str) {
 
         let best_idx = fitness[0].0;
         let best_fitness = fitness[0].1;
-        let best_params = HeuristicParams::from_genes(&population[best_idx], seed);
+        let best_config = &population_configs[best_idx];
 
-        // Save best individual
+        // Save best individual, as params and as a variants file entry
         let output_path = format!("{}/batch-{}-gen-{}.json", output, batch_id, gen);
-        let json = serde_json::to_string_pretty(&best_params).unwrap();
+        let json = serde_json::to_string_pretty(&best_config.heuristic_params).unwrap();
         std::fs::write(&output_path, json).unwrap();
+        let name = format!("batch-{}-gen-{}", batch_id, gen);
+        let variant_path = format!("{}/{}-variant.json", output, name);
+        let entry = variant_file_entry(name, &population[best_idx], seed);
+        std::fs::write(&variant_path, serde_json::to_string_pretty(&entry).unwrap()).unwrap();
 
         let avg_fitness: f64 = fitness.iter().map(|(_, f)| f).sum::<f64>() / fitness.len() as f64;
         let elapsed = gen_start.elapsed();
@@ -463,11 +847,67 @@ pub fn run_genetic_algorithm(args: &TrainArgs, threads: usize, output: &str) {
             elapsed.as_secs_f64(),
             output_path,
         );
+        if player_mix.len() > 1 {
+            // Win rates of the best individual and over the whole generation
+            let mut generation_tables = vec![TableResults::default(); player_mix.len()];
+            for results in &table_results {
+                for (total, r) in generation_tables.iter_mut().zip(results) {
+                    total.games += r.games;
+                    total.wins += r.wins;
+                }
+            }
+            eprintln!(
+                "  win rate by table size: best {}; avg {}",
+                format_table_win_rates(&player_mix, &table_results[best_idx]),
+                format_table_win_rates(&player_mix, &generation_tables)
+            );
+        }
 
+        hall_of_fame.push(population[best_idx].clone());
+        let hall_of_fame_path = format!("{}/batch-{}-hall-of-fame.json", output, batch_id);
+        // A variants file, with each champion named after its generation
+        let champions: Vec<serde_json::Value> = hall_of_fame
+            .iter()
+            .enumerate()
+            .map(|(n, g)| variant_file_entry(format!("batch-{}-gen-{}", batch_id, n), g, seed))
+            .collect();
+        std::fs::write(&hall_of_fame_path, serde_json::to_string_pretty(&champions).unwrap()).unwrap();
+
+        evaluated.clear();
+        table_results.clear();
+        if let Some(cma) = cma.as_mut() {
+            // Fitness back in population order, which is the order of the samples
+            let mut by_index = vec![0.0; pop_size];
+            for &(i, f) in &fitness {
+                by_index[i] = f;
+            }
+            cma.tell(&by_index);
+
+            let summary = CmaGeneration {
+                generation: gen,
+                sigma: cma.sigma,
+                best_fitness,
+                mean: variant_file_entry(
+                    format!("batch-{}-gen-{}-mean", batch_id, gen),
+                    &cma_to_genes(&cma.mean, &seed_genes),
+                    seed,
+                ),
+            };
+            let summary_path = format!("{}/batch-{}-gen-{}-cma.json", output, batch_id, gen);
+            std::fs::write(&summary_path, serde_json::to_string_pretty(&summary).unwrap()).unwrap();
+            eprintln!("  sigma={:.4}, mean saved to {}", cma.sigma, summary_path);
+        }
         if gen + 1 >= args.generations {
+            save_checkpoint(gen + 1, &population, &evaluated, &rng, &cma, &hall_of_fame, &table_results);
             break;
         }
 
+        if let Some(cma) = cma.as_mut() {
+            population = cma.ask(&mut rng).iter().map(|x| cma_to_genes(x, &seed_genes)).collect();
+            save_checkpoint(gen + 1, &population, &evaluated, &rng, &Some(cma.clone()), &hall_of_fame, &table_results);
+            continue;
+        }
+
         // Selection, crossover, mutation
         let mut new_population: Vec<Vec<f64>> = Vec::with_capacity(args.population);
 
@@ -491,29 +931,24 @@ pub fn run_genetic_algorithm(args: &TrainArgs, threads: usize, output: &str) {
             }
 
             // Mutation
-            for g in child.iter_mut() {
+            let switches = switch_gene_indices(num_genes);
+            for (idx, g) in child.iter_mut().enumerate() {
                 if rng.random::<f64>() < args.mutation_rate {
-                    let perturbation = sample_normal(&mut rng, args.mutation_scale);
-                    *g *= 1.0 + perturbation;
-                    if *g < 0.0 {
-                        *g = 0.0;
+                    if switches.contains(&idx) {
+                        *g = 1.0 - *g;
+                    } else {
+                        let perturbation = sample_normal(&mut rng, args.mutation_scale);
+                        *g += gene_scale(*g) * perturbation;
                     }
                 }
             }
-
-            // Clamp integer genes
-            for &idx in &HeuristicParams::integer_gene_indices() {
-                child[idx] = child[idx].round().max(1.0);
-            }
-            // Clamp probability genes
-            for &idx in &HeuristicParams::probability_gene_indices() {
-                child[idx] = child[idx].clamp(0.0, 1.0);
-            }
+            repair_genes(&mut child);
 
             new_population.push(child);
         }
 
         population = new_population;
+        save_checkpoint(gen + 1, &population, &evaluated, &rng, &cma, &hall_of_fame, &table_results);
     }
 
     eprintln!("Genetic algorithm complete. Results in {}/", output);
@@ -1,6 +1,15 @@
+mod checkpoint;
+mod cma;
 mod cli;
+mod duplicate;
 mod genetic;
+mod perft;
+mod ratings;
+mod schedule;
+mod selfplay;
 mod simulation;
+mod sprt;
+mod sweep;
 mod tournament;
 
 use clap::Parser;
@@ -21,19 +30,39 @@ pub(crate) fn generate_batch_id() -> String {
 fn main() {
     let cli = Cli::parse();
     let threads = cli.threads;
+    let resume = cli.resume;
 
     match cli.command {
         Some(Commands::Simulate(args)) => {
             let output = cli.output.unwrap_or_else(|| "game-logs".to_string());
-            simulation::run_simulation(&args, threads, &output);
+            simulation::run_simulation(&args, threads, &output, resume);
         }
         Some(Commands::Tournament(args)) => {
             let output = cli.output.unwrap_or_else(|| "game-logs".to_string());
-            tournament::run_tournament(&args, threads, &output);
+            tournament::run_tournament(&args, threads, &output, resume);
         }
         Some(Commands::Train(args)) => {
             let output = cli.output.unwrap_or_else(|| "genetic-algorithm".to_string());
-            genetic::run_genetic_algorithm(&args, threads, &output);
+            genetic::run_genetic_algorithm(&args, threads, &output, resume);
+        }
+        Some(Commands::Selfplay(args)) => {
+            let output = cli.output.unwrap_or_else(|| "selfplay-data".to_string());
+            selfplay::run_selfplay(&args, threads, &output, resume);
+        }
+        Some(Commands::Perft(args)) => {
+            if resume {
+                eprintln!("Perft runs are not checkpointed and cannot be resumed");
+                std::process::exit(1);
+            }
+            perft::run_perft(&args);
+        }
+        Some(Commands::Sprt(args)) => {
+            let output = cli.output.unwrap_or_else(|| "game-logs".to_string());
+            sprt::run_sprt(&args, threads, &output, resume);
+        }
+        Some(Commands::Sweep(args)) => {
+            let output = cli.output.unwrap_or_else(|| "sweep".to_string());
+            sweep::run_sweep(&args, threads, &output, resume);
         }
         None => {
             // Default: simulate with default args
@@ -44,9 +73,11 @@ fn main() {
                 heuristic_params_file: None,
                 variants_file: "variants.json".to_string(),
                 max_rounds: 5,
+                duplicate: false,
+                seed: None,
             };
             let output = cli.output.unwrap_or_else(|| "game-logs".to_string());
-            simulation::run_simulation(&args, threads, &output);
+            simulation::run_simulation(&args, threads, &output, resume);
         }
     }
 }
@@ -0,0 +1,75 @@
+use std::time::Instant;
+
+use colori_core::perft::{perft, perft_divide, seed_position};
+use colori_core::replay::reconstruct_initial_state;
+use colori_core::types::GameState;
+
+use crate::cli::PerftArgs;
+
+/// Reads a serialized `GameState`, such as one saved by the web app, and
+/// rebuilds the card lookups it does not serialize.
+fn load_state(path: &str) -> GameState {
+    let contents = std::fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("Failed to read state file: {}", path));
+    let json: serde_json::Value = serde_json::from_str(&contents)
+        .unwrap_or_else(|e| panic!("Failed to parse state file: {}: {}", path, e));
+    reconstruct_initial_state(&json)
+}
+
+pub fn run_perft(args: &PerftArgs) {
+    let state = match &args.state {
+        Some(path) => {
+            let state = load_state(path);
+            eprintln!(
+                "Perft: {} players, state from {} (round {}), seed {}, depth {}",
+                state.players.len(), path, state.round, args.seed, args.depth
+            );
+            state
+        }
+        None => {
+            assert!((1..=4).contains(&args.players), "--players must be between 1 and 4");
+            let state = seed_position(args.players, args.seed, args.plies);
+            eprintln!(
+                "Perft: {} players, seed {}, {} plies (round {}), depth {}",
+                args.players, args.seed, args.plies, state.round, args.depth
+            );
+            state
+        }
+    };
+
+    let start = Instant::now();
+    let total = if args.divide && args.depth > 0 {
+        let divide = perft_divide(&state, args.depth, args.seed);
+        for (choice, count) in &divide {
+            println!("{}: {}", serde_json::to_string(choice).unwrap(), count);
+        }
+        divide.iter().map(|(_, count)| count).sum()
+    } else {
+        perft(&state, args.depth, args.seed)
+    };
+    let elapsed = start.elapsed();
+
+    println!("Total: {}", total);
+    eprintln!(
+        "{:.2}s, {:.0} leaves/s",
+        elapsed.as_secs_f64(),
+        total as f64 / elapsed.as_secs_f64().max(1e-9)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_state_counts_like_the_original() {
+        for (players, plies) in [(2, 0), (3, 9), (4, 30)] {
+            let state = seed_position(players, 7, plies);
+            let path = std::env::temp_dir().join(format!("colori-perft-{}-{}-{}.json", std::process::id(), players, plies));
+            std::fs::write(&path, serde_json::to_string(&state).unwrap()).unwrap();
+            let loaded = load_state(path.to_str().unwrap());
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(perft(&loaded, 2, 7), perft(&state, 2, 7), "{} players, {} plies", players, plies);
+        }
+    }
+}
@@ -0,0 +1,404 @@
+//! Bradley–Terry ratings fitted to tournament games.
+//!
+//! Every game is split into one comparison per pair of players, scored 1,
+//! 0.5 or 0 by final ranking and weighted by 1 / (players - 1), so a game
+//! carries the same weight per player whatever its size. Ratings are on the
+//! Elo scale: a variant rated 400 above another is expected to score 10:1
+//! against it.
+//!
+//! The fit maximizes the likelihood of the comparisons, plus a Gaussian prior
+//! for every variant that has one, by Newton's method. Each pair of variants
+//! that met also gets one virtual draw so that a variant that never lost
+//! still gets a finite rating. Without priors the ratings are relative and
+//! centered on 0; with priors they are on the scale of the priors. Standard
+//! deviations come from the inverse Hessian at the optimum.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::GameRunOutput;
+
+/// Final ranking key of a player: score, then completed sell cards, then
+/// color wheel total.
+pub type Ranking = (u32, u32, u32);
+
+const ELO_SCALE: f64 = std::f64::consts::LN_10 / 400.0;
+const VIRTUAL_DRAWS: f64 = 1.0;
+const MAX_ITERATIONS: usize = 100;
+/// Largest change to a rating in one Newton step, to keep early steps from
+/// overshooting.
+const MAX_STEP: f64 = 400.0;
+const RIDGE: f64 = 1e-12;
+/// Floor on prior standard deviations, so that a variant rated alone (whose
+/// centered rating has no uncertainty) can still be used as a prior.
+const MIN_PRIOR_STD_DEV: f64 = 1.0;
+const CI_95: f64 = 1.96;
+
+/// One pairwise comparison: `score` is what `a` scored against `b`.
+pub struct Comparison {
+    pub a: usize,
+    pub b: usize,
+    pub score: f64,
+    pub weight: f64,
+}
+
+/// Final ranking of each seat of a finished game.
+pub fn final_rankings(log: &GameRunOutput) -> Vec<Ranking> {
+    log.final_scores
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|s| (s.score, s.completed_sell_cards, s.color_wheel_total))
+        .collect()
+}
+
+pub fn comparisons_from_game(players: &[(usize, Ranking)]) -> Vec<Comparison> {
+    let n = players.len();
+    let mut comparisons = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+    if n < 2 {
+        return comparisons;
+    }
+    let weight = 1.0 / (n - 1) as f64;
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let (a, ranking_a) = players[i];
+            let (b, ranking_b) = players[j];
+            let score = if ranking_a > ranking_b {
+                1.0
+            } else if ranking_a < ranking_b {
+                0.0
+            } else {
+                0.5
+            };
+            comparisons.push(Comparison { a, b, score, weight });
+        }
+    }
+    comparisons
+}
+
+// ── Results file ──
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingEntry {
+    pub name: String,
+    pub rating: f64,
+    pub std_dev: f64,
+    pub games: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingsFile {
+    pub games: u64,
+    pub ratings: Vec<RatingEntry>,
+}
+
+#[derive(Clone, Copy)]
+pub struct Prior {
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// Priors by variant name from a ratings file written by an earlier
+/// tournament.
+pub fn load_priors(path: &str) -> HashMap<String, Prior> {
+    let contents = std::fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("Failed to read ratings file: {}", path));
+    let file: RatingsFile = serde_json::from_str(&contents)
+        .unwrap_or_else(|e| panic!("Failed to parse ratings file: {}: {}", path, e));
+    file.ratings
+        .into_iter()
+        .map(|r| {
+            let prior = Prior { mean: r.rating, std_dev: r.std_dev };
+            (r.name, prior)
+        })
+        .collect()
+}
+
+// ── Fitting ──
+
+pub struct Ratings {
+    pub ratings: Vec<f64>,
+    pub std_devs: Vec<f64>,
+}
+
+/// Fits ratings for `num_variants` variants to `comparisons`. `priors` is
+/// indexed by variant.
+pub fn fit_ratings(num_variants: usize, comparisons: &[Comparison], priors: &[Option<Prior>]) -> Ratings {
+    let n = num_variants;
+    let has_priors = priors.iter().any(|p| p.is_some());
+
+    let met: BTreeSet<(usize, usize)> = comparisons.iter().map(|c| (c.a.min(c.b), c.a.max(c.b))).collect();
+    let virtual_draws: Vec<Comparison> = met
+        .into_iter()
+        .map(|(a, b)| Comparison { a, b, score: 0.5, weight: VIRTUAL_DRAWS })
+        .collect();
+    let all: Vec<&Comparison> = comparisons.iter().chain(virtual_draws.iter()).collect();
+
+    // Without priors only differences are determined, so the first rating is
+    // pinned at 0 while fitting and the result is centered afterwards.
+    let free: Vec<usize> = if has_priors { (0..n).collect() } else { (1..n).collect() };
+
+    let mut ratings: Vec<f64> = priors.iter().map(|p| p.map_or(0.0, |p| p.mean)).collect();
+    ratings.resize(n, 0.0);
+    for _ in 0..MAX_ITERATIONS {
+        let (gradient, information) = derivatives(&ratings, &all, priors);
+        let inverse = invert(reduce(&information, &free));
+        let mut largest = 0.0f64;
+        for (row, &i) in free.iter().enumerate() {
+            let step: f64 = free.iter().enumerate().map(|(col, &j)| inverse[row][col] * gradient[j]).sum();
+            let step = step.clamp(-MAX_STEP, MAX_STEP);
+            ratings[i] += step;
+            largest = largest.max(step.abs());
+        }
+        if largest < 1e-6 {
+            break;
+        }
+    }
+
+    let (_, information) = derivatives(&ratings, &all, priors);
+    let reduced = invert(reduce(&information, &free));
+    let mut covariance = vec![vec![0.0; n]; n];
+    for (row, &i) in free.iter().enumerate() {
+        for (col, &j) in free.iter().enumerate() {
+            covariance[i][j] = reduced[row][col];
+        }
+    }
+
+    if !has_priors && n > 0 {
+        // Center the ratings, and project the covariance the same way
+        let mean = ratings.iter().sum::<f64>() / n as f64;
+        for r in ratings.iter_mut() {
+            *r -= mean;
+        }
+        let row_means: Vec<f64> = covariance.iter().map(|row| row.iter().sum::<f64>() / n as f64).collect();
+        let total_mean = row_means.iter().sum::<f64>() / n as f64;
+        for i in 0..n {
+            for j in 0..n {
+                covariance[i][j] += total_mean - row_means[i] - row_means[j];
+            }
+        }
+    }
+
+    let std_devs = (0..n).map(|i| covariance[i][i].max(0.0).sqrt()).collect();
+    Ratings { ratings, std_devs }
+}
+
+/// Gradient of the log posterior and its negated Hessian (the observed
+/// information).
+fn derivatives(ratings: &[f64], comparisons: &[&Comparison], priors: &[Option<Prior>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = ratings.len();
+    let mut gradient = vec![0.0; n];
+    let mut information = vec![vec![0.0; n]; n];
+    for c in comparisons {
+        let expected = 1.0 / (1.0 + (-ELO_SCALE * (ratings[c.a] - ratings[c.b])).exp());
+        let g = c.weight * ELO_SCALE * (c.score - expected);
+        gradient[c.a] += g;
+        gradient[c.b] -= g;
+        let h = c.weight * ELO_SCALE * ELO_SCALE * expected * (1.0 - expected);
+        information[c.a][c.a] += h;
+        information[c.b][c.b] += h;
+        information[c.a][c.b] -= h;
+        information[c.b][c.a] -= h;
+    }
+    for (i, prior) in priors.iter().enumerate() {
+        if let Some(p) = prior {
+            let std_dev = p.std_dev.max(MIN_PRIOR_STD_DEV);
+            let precision = 1.0 / (std_dev * std_dev);
+            gradient[i] -= (ratings[i] - p.mean) * precision;
+            information[i][i] += precision;
+        }
+    }
+    for (i, row) in information.iter_mut().enumerate() {
+        row[i] += RIDGE;
+    }
+    (gradient, information)
+}
+
+fn reduce(matrix: &[Vec<f64>], indices: &[usize]) -> Vec<Vec<f64>> {
+    indices
+        .iter()
+        .map(|&i| indices.iter().map(|&j| matrix[i][j]).collect())
+        .collect()
+}
+
+/// Inverse of a small positive definite matrix by Gauss–Jordan elimination.
+fn invert(mut matrix: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&a, &b| matrix[a][col].abs().partial_cmp(&matrix[b][col].abs()).unwrap())
+            .unwrap();
+        matrix.swap(col, pivot);
+        inverse.swap(col, pivot);
+        let scale = matrix[col][col];
+        for j in 0..n {
+            matrix[col][j] /= scale;
+            inverse[col][j] /= scale;
+        }
+        for row in 0..n {
+            if row != col {
+                let factor = matrix[row][col];
+                if factor != 0.0 {
+                    for j in 0..n {
+                        matrix[row][j] -= factor * matrix[col][j];
+                        inverse[row][j] -= factor * inverse[col][j];
+                    }
+                }
+            }
+        }
+    }
+    inverse
+}
+
+// ── Output ──
+
+pub fn print_ratings(labels: &[String], ratings: &Ratings, games: &[u64]) {
+    let mut order: Vec<usize> = (0..labels.len()).collect();
+    order.sort_by(|&a, &b| ratings.ratings[b].partial_cmp(&ratings.ratings[a]).unwrap());
+
+    let max_label = labels.iter().map(|l| l.len()).max().unwrap_or(7).max(7);
+
+    eprintln!();
+    eprintln!("=== Ratings (Bradley–Terry, Elo scale) ===");
+    eprintln!();
+    eprintln!(
+        "{:<width$}  {:>7}  {:>8}  {:>5}",
+        "Variant",
+        "Rating",
+        "95% CI",
+        "Games",
+        width = max_label,
+    );
+    eprintln!("{}", "-".repeat(max_label + 28));
+    for i in order {
+        eprintln!(
+            "{:<width$}  {:>+7.1}  {:>8}  {:>5}",
+            labels[i],
+            ratings.ratings[i],
+            format!("± {:.1}", CI_95 * ratings.std_devs[i]),
+            games[i],
+            width = max_label,
+        );
+    }
+}
+
+pub fn write_ratings(path: &str, labels: &[String], ratings: &Ratings, games: &[u64], total_games: u64) {
+    let file = RatingsFile {
+        games: total_games,
+        ratings: labels
+            .iter()
+            .enumerate()
+            .map(|(i, name)| RatingEntry {
+                name: name.clone(),
+                rating: ratings.ratings[i],
+                std_dev: ratings.std_devs[i],
+                games: games[i],
+            })
+            .collect(),
+    };
+    let json = serde_json::to_string_pretty(&file).unwrap();
+    std::fs::write(path, json).unwrap_or_else(|_| panic!("Failed to write ratings file: {}", path));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `wins` games won by `a` and `losses` lost, between two players.
+    fn results(a: usize, b: usize, wins: usize, losses: usize) -> Vec<Comparison> {
+        let won = (0..wins).map(|_| Comparison { a, b, score: 1.0, weight: 1.0 });
+        let lost = (0..losses).map(|_| Comparison { a, b, score: 0.0, weight: 1.0 });
+        won.chain(lost).collect()
+    }
+
+    /// Elo gap at which the expected score matches the observed one,
+    /// counting the virtual draw.
+    fn observed_gap(wins: usize, losses: usize) -> f64 {
+        let wins = wins as f64 + 0.5 * VIRTUAL_DRAWS;
+        let losses = losses as f64 + 0.5 * VIRTUAL_DRAWS;
+        400.0 * (wins / losses).log10()
+    }
+
+    #[test]
+    fn test_comparisons_from_game() {
+        let comparisons = comparisons_from_game(&[(0, (10, 1, 0)), (1, (10, 0, 5)), (2, (10, 1, 0))]);
+        let scores: Vec<(usize, usize, f64)> = comparisons.iter().map(|c| (c.a, c.b, c.score)).collect();
+        assert_eq!(scores, vec![(0, 1, 1.0), (0, 2, 0.5), (1, 2, 0.0)]);
+        assert!(comparisons.iter().all(|c| c.weight == 0.5));
+        assert!(comparisons_from_game(&[(0, (10, 0, 0))]).is_empty());
+    }
+
+    #[test]
+    fn test_fit_recovers_known_gap() {
+        let fit = fit_ratings(2, &results(0, 1, 100, 10), &[None, None]);
+        let gap = observed_gap(100, 10);
+        assert!((fit.ratings[0] - gap / 2.0).abs() < 1e-6, "ratings {:?}", fit.ratings);
+        assert!((fit.ratings[1] + gap / 2.0).abs() < 1e-6, "ratings {:?}", fit.ratings);
+        assert!((fit.std_devs[0] - fit.std_devs[1]).abs() < 1e-9);
+        assert!(fit.std_devs[0] > 0.0);
+    }
+
+    #[test]
+    fn test_fit_is_transitive_and_centered() {
+        let mut comparisons = results(0, 1, 30, 10);
+        comparisons.extend(results(1, 2, 30, 10));
+        let fit = fit_ratings(3, &comparisons, &[None, None, None]);
+        assert!(fit.ratings.iter().sum::<f64>().abs() < 1e-6);
+        assert!(fit.ratings[0] > fit.ratings[1] && fit.ratings[1] > fit.ratings[2]);
+        // Variants 0 and 2 never met, so their gap is the least certain
+        assert!(fit.std_devs[0] > fit.std_devs[1] && fit.std_devs[2] > fit.std_devs[1]);
+    }
+
+    #[test]
+    fn test_prior_anchors_scale() {
+        let prior = Prior { mean: 1500.0, std_dev: 1.0 };
+        let fit = fit_ratings(2, &results(0, 1, 100, 10), &[Some(prior), None]);
+        assert!((fit.ratings[0] - 1500.0).abs() < 1e-6, "ratings {:?}", fit.ratings);
+        assert!((fit.ratings[0] - fit.ratings[1] - observed_gap(100, 10)).abs() < 1e-6);
+        assert!(fit.std_devs[0] < 1.0);
+    }
+
+    #[test]
+    fn test_prior_pulls_towards_mean() {
+        let comparisons = results(0, 1, 100, 10);
+        let anchor = Some(Prior { mean: 0.0, std_dev: 1.0 });
+        let gap_with = |std_dev: f64| {
+            let fit = fit_ratings(2, &comparisons, &[anchor, Some(Prior { mean: 0.0, std_dev })]);
+            fit.ratings[0] - fit.ratings[1]
+        };
+        let data_gap = observed_gap(100, 10);
+        let loose = gap_with(10_000.0);
+        let medium = gap_with(100.0);
+        let tight = gap_with(1.0);
+        assert!((loose - data_gap).abs() < 1.0, "loose prior gap {}", loose);
+        assert!(tight.abs() < 5.0, "tight prior gap {}", tight);
+        assert!(tight < medium && medium < loose);
+    }
+
+    #[test]
+    fn test_unbeaten_variant_gets_finite_rating() {
+        let fit = fit_ratings(2, &results(0, 1, 50, 0), &[None, None]);
+        assert!(fit.ratings.iter().chain(&fit.std_devs).all(|v| v.is_finite()));
+        assert!((fit.ratings[0] - fit.ratings[1] - observed_gap(50, 0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invert() {
+        // Zero first pivot, so the rows have to be swapped
+        let matrix = vec![vec![0.0, 2.0, 1.0], vec![2.0, 5.0, 0.0], vec![1.0, 0.0, 3.0]];
+        let inverse = invert(matrix.clone());
+        for (i, row) in matrix.iter().enumerate() {
+            for j in 0..3 {
+                let product: f64 = row.iter().zip(&inverse).map(|(m, inverse_row)| m * inverse_row[j]).sum();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((product - expected).abs() < 1e-12, "entry ({}, {}) is {}", i, j, product);
+            }
+        }
+    }
+}
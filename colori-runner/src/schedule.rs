@@ -0,0 +1,225 @@
+//! Tournament schedules: which variants meet at which table, in which seats.
+//!
+//! A schedule is a list of matches. Except in the random schedule, a match is
+//! played once per rotation of its table, so every variant at the table sits
+//! in every seat once and position bias cancels out within the match.
+
+use std::collections::HashSet;
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use wyrand::WyRand;
+
+/// Variants at one table, as indices in seat order.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Match {
+    pub table: Vec<usize>,
+    /// Whether the match is played once per rotation of the table, rather
+    /// than once with the seats shuffled.
+    pub rotated: bool,
+}
+
+impl Match {
+    pub fn games(&self) -> usize {
+        if self.rotated { self.table.len() } else { 1 }
+    }
+}
+
+/// `count` tables of `players` distinct variants drawn at random.
+pub fn random_matches(num_variants: usize, players: usize, count: usize, rotated: bool, rng: &mut WyRand) -> Vec<Match> {
+    (0..count)
+        .map(|_| Match {
+            table: rand::seq::index::sample(rng, num_variants, players).into_vec(),
+            rotated,
+        })
+        .collect()
+}
+
+/// Every other variant against `reference` in head-to-head matches, as
+/// evenly as `games` allows.
+pub fn gauntlet_matches(num_variants: usize, reference: usize, games: usize) -> Vec<Match> {
+    let challengers: Vec<usize> = (0..num_variants).filter(|&v| v != reference).collect();
+    let per_challenger = games.div_ceil(2 * challengers.len());
+    (0..per_challenger)
+        .flat_map(|_| challengers.iter().map(|&c| Match { table: vec![reference, c], rotated: true }))
+        .collect()
+}
+
+/// Every set of `players` variants in every cyclic order once per cycle,
+/// repeated for enough cycles to play at least `games` games. With the
+/// rotations, every seating of every table is played once per cycle, so
+/// each variant meets every other equally often, sits in every seat equally
+/// often and sits directly before every other variant equally often, which
+/// matters since draft hands pass to the next seat. Only whole cycles keep
+/// that balance, so `games` is rounded up to a multiple of
+/// [`balanced_cycle_games`].
+pub fn balanced_matches(num_variants: usize, players: usize, games: usize) -> Vec<Match> {
+    let tables: Vec<Vec<usize>> = combinations(num_variants, players)
+        .into_iter()
+        .flat_map(|t| cyclic_orders(&t))
+        .collect();
+    let cycles = games.div_ceil(balanced_cycle_games(num_variants, players));
+    (0..cycles)
+        .flat_map(|_| tables.iter().map(|t| Match { table: t.clone(), rotated: true }))
+        .collect()
+}
+
+/// Games in one cycle of the balanced schedule: every ordered seating of
+/// `players` distinct variants.
+pub fn balanced_cycle_games(num_variants: usize, players: usize) -> usize {
+    (0..players).map(|i| num_variants - i).product()
+}
+
+/// Every order of `table` up to rotation, as the orders that keep its first
+/// variant in the first seat.
+fn cyclic_orders(table: &[usize]) -> Vec<Vec<usize>> {
+    fn permute(rest: &mut Vec<usize>, current: &mut Vec<usize>, result: &mut Vec<Vec<usize>>) {
+        if rest.is_empty() {
+            result.push(current.clone());
+            return;
+        }
+        for i in 0..rest.len() {
+            let v = rest.remove(i);
+            current.push(v);
+            permute(rest, current, result);
+            current.pop();
+            rest.insert(i, v);
+        }
+    }
+    let mut result = Vec::new();
+    let mut current = vec![table[0]];
+    permute(&mut table[1..].to_vec(), &mut current, &mut result);
+    result
+}
+
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    let mut current = Vec::with_capacity(k);
+    fn extend(start: usize, n: usize, k: usize, current: &mut Vec<usize>, result: &mut Vec<Vec<usize>>) {
+        if current.len() == k {
+            result.push(current.clone());
+            return;
+        }
+        for v in start..n {
+            current.push(v);
+            extend(v + 1, n, k, current, result);
+            current.pop();
+        }
+    }
+    extend(0, n, k, &mut current, &mut result);
+    result
+}
+
+/// One Swiss round: variants sorted by rating, each paired with the closest
+/// rated variant still unpaired that it did not meet in the previous round.
+/// With an odd number of variants the lowest rated unpaired one sits out.
+pub fn swiss_round(ratings: &[f64], previous: &HashSet<(usize, usize)>, rng: &mut WyRand) -> Vec<Match> {
+    // Shuffle first so that equal ratings are paired in random order
+    let mut unpaired: Vec<usize> = (0..ratings.len()).collect();
+    unpaired.shuffle(rng);
+    unpaired.sort_by(|&a, &b| ratings[b].partial_cmp(&ratings[a]).unwrap());
+
+    let mut matches = Vec::new();
+    while unpaired.len() >= 2 {
+        let a = unpaired.remove(0);
+        let pick = unpaired
+            .iter()
+            .position(|&b| !previous.contains(&pair_key(a, b)))
+            .unwrap_or(0);
+        let b = unpaired.remove(pick);
+        matches.push(Match { table: vec![a, b], rotated: true });
+    }
+    matches
+}
+
+pub fn pair_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    /// The seat order of every game a match plays.
+    fn seatings(m: &Match) -> Vec<Vec<usize>> {
+        (0..m.games())
+            .map(|rotation| {
+                let mut seats = m.table.clone();
+                seats.rotate_left(rotation);
+                seats
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_balanced_matches_balance_seats_pairs_and_neighbours() {
+        for (num_variants, players) in [(4, 2), (5, 3), (5, 4), (6, 4)] {
+            let cycle = balanced_cycle_games(num_variants, players);
+            let matches = balanced_matches(num_variants, players, cycle + 1);
+            let games: Vec<Vec<usize>> = matches.iter().flat_map(seatings).collect();
+            assert_eq!(games.len(), 2 * cycle);
+
+            let mut seats = vec![vec![0; players]; num_variants];
+            let mut pairs = vec![vec![0; num_variants]; num_variants];
+            let mut next = vec![vec![0; num_variants]; num_variants];
+            for game in &games {
+                for (seat, &v) in game.iter().enumerate() {
+                    seats[v][seat] += 1;
+                    next[v][game[(seat + 1) % players]] += 1;
+                    for &w in game {
+                        pairs[v][w] += 1;
+                    }
+                }
+            }
+            let off_diagonal = |m: &[Vec<usize>]| -> Vec<usize> {
+                (0..num_variants)
+                    .flat_map(|v| (0..num_variants).filter(move |&w| w != v).map(move |w| (v, w)))
+                    .map(|(v, w)| m[v][w])
+                    .collect()
+            };
+            let seat_counts: Vec<usize> = seats.concat();
+            assert!(seat_counts.iter().all(|&c| c == seat_counts[0]), "seats {:?}", seats);
+            let pair_counts = off_diagonal(&pairs);
+            assert!(pair_counts.iter().all(|&c| c == pair_counts[0]), "pairs {:?}", pairs);
+            let next_counts = off_diagonal(&next);
+            assert!(next_counts.iter().all(|&c| c == next_counts[0]), "neighbours {:?}", next);
+        }
+    }
+
+    #[test]
+    fn test_swiss_round_avoids_previous_pairings() {
+        let mut rng = WyRand::seed_from_u64(1);
+        let previous = HashSet::from([pair_key(1, 0)]);
+        let matches = swiss_round(&[4.0, 3.0, 2.0, 1.0], &previous, &mut rng);
+        let tables: Vec<Vec<usize>> = matches.iter().map(|m| m.table.clone()).collect();
+        assert_eq!(tables, vec![vec![0, 2], vec![1, 3]]);
+        assert!(matches.iter().all(|m| m.rotated));
+    }
+
+    #[test]
+    fn test_swiss_round_lowest_rated_sits_out() {
+        let ratings = [5.0, -1.0, 4.0, 3.0, 2.0];
+        for seed in 0..5 {
+            let mut rng = WyRand::seed_from_u64(seed);
+            let matches = swiss_round(&ratings, &HashSet::new(), &mut rng);
+            assert_eq!(matches.len(), 2);
+            let playing: HashSet<usize> = matches.iter().flat_map(|m| m.table.iter().copied()).collect();
+            assert_eq!(playing, HashSet::from([0, 2, 3, 4]));
+        }
+    }
+
+    #[test]
+    fn test_gauntlet_gives_every_challenger_both_seatings() {
+        let reference = 2;
+        let matches = gauntlet_matches(4, reference, 10);
+        let games: Vec<Vec<usize>> = matches.iter().flat_map(seatings).collect();
+        assert!(games.len() >= 10);
+        for challenger in [0, 1, 3] {
+            let first = games.iter().filter(|g| **g == vec![challenger, reference]).count();
+            let second = games.iter().filter(|g| **g == vec![reference, challenger]).count();
+            assert!(first > 0 && first == second, "challenger {}: {} and {}", challenger, first, second);
+        }
+        assert!(games.iter().all(|g| g.contains(&reference)));
+    }
+}
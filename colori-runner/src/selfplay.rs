@@ -0,0 +1,301 @@
+use std::collections::HashSet;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use colori_core::action_space::{choice_to_index, ACTION_SPACE_SIZE};
+use colori_core::colori_game::{apply_choice_to_state, enumerate_choices};
+use colori_core::draw_phase::execute_draw_phase;
+use colori_core::ismcts::{ismcts, MctsTree};
+use colori_core::observation::{encode_observation, OBSERVATION_LEN};
+use colori_core::scoring::{calculate_score, compute_terminal_rewards};
+use colori_core::setup::create_initial_game_state;
+use colori_core::types::*;
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::Serialize;
+use wyrand::WyRand;
+
+use crate::checkpoint::{self, Checkpoint, Journal};
+use crate::cli::{NamedVariant, SelfplayArgs, load_variants_from_file};
+
+// ── Serialization types ──
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DecisionRecord<'a> {
+    game: usize,
+    decision: u32,
+    round: u32,
+    phase: &'static str,
+    player_index: usize,
+    variant: &'a str,
+    observation: Vec<f32>,
+    choices: Vec<Choice>,
+    actions: Vec<u32>,
+    visits: Vec<u32>,
+    chosen: usize,
+    outcome: f64,
+    final_score: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SelfplayManifest {
+    seed: u64,
+    games: usize,
+    games_per_shard: usize,
+    shards: usize,
+    observation_len: usize,
+    action_space_size: u32,
+    variants: Vec<String>,
+}
+
+struct PendingDecision {
+    round: u32,
+    phase: &'static str,
+    player_index: usize,
+    observation: Vec<f32>,
+    choices: Vec<Choice>,
+    visits: Vec<u32>,
+    chosen: usize,
+}
+
+fn root_visits(tree: &MctsTree, choices: &[Choice]) -> Vec<u32> {
+    choices
+        .iter()
+        .map(|choice| {
+            tree.root()
+                .children()
+                .find(|child| child.choice() == Some(choice))
+                .map_or(0, |child| child.visit_count())
+        })
+        .collect()
+}
+
+// ── Game loop ──
+
+/// Seed of game `game_index` of a run seeded with `seed`. Both are mixed
+/// with SplitMix64 so that runs with nearby seeds share no games.
+fn game_seed(seed: u64, game_index: usize) -> u64 {
+    fn splitmix64(x: u64) -> u64 {
+        let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    splitmix64(splitmix64(seed).wrapping_add(game_index as u64))
+}
+
+fn play_selfplay_game(
+    game_index: usize,
+    player_variants: &[NamedVariant],
+    labels: &[String],
+    seed: u64,
+    writer: &mut impl Write,
+) {
+    let mut rng = WyRand::seed_from_u64(game_seed(seed, game_index));
+    let num_players = player_variants.len();
+
+    let mut variant_order: Vec<usize> = (0..num_players).collect();
+    variant_order.shuffle(&mut rng);
+
+    let ai_players = vec![true; num_players];
+    let mut state = create_initial_game_state(num_players, &ai_players, &mut rng);
+    execute_draw_phase(&mut state, &mut rng);
+
+    let mut decisions: Vec<PendingDecision> = Vec::new();
+
+    while !matches!(state.phase, GamePhase::GameOver) {
+        let (player_index, phase_str) = match &state.phase {
+            GamePhase::Draft { draft_state } => (draft_state.current_player_index, "draft"),
+            GamePhase::Action { action_state } => (action_state.current_player_index, "action"),
+            GamePhase::Draw => {
+                execute_draw_phase(&mut state, &mut rng);
+                continue;
+            }
+            GamePhase::GameOver => break,
+        };
+
+        let config = &player_variants[variant_order[player_index]].ai;
+        let max_rollout_round = config.max_rollout_round(state.round);
+        let result = ismcts(&state, player_index, config, Some(max_rollout_round), None, &mut rng);
+
+        // Forced moves carry no search information, so only record real decisions.
+        if let Some(tree) = &result.tree {
+            let choices = enumerate_choices(&state);
+            let chosen = choices
+                .iter()
+                .position(|c| *c == result.choice)
+                .expect("Chosen move missing from legal choices");
+            decisions.push(PendingDecision {
+                round: state.round,
+                phase: phase_str,
+                player_index,
+                observation: encode_observation(&state, player_index),
+                visits: root_visits(tree, &choices),
+                choices,
+                chosen,
+            });
+        }
+
+        apply_choice_to_state(&mut state, &result.choice, &mut rng);
+    }
+
+    let rewards = compute_terminal_rewards(&state.players);
+    for (i, d) in decisions.into_iter().enumerate() {
+        let record = DecisionRecord {
+            game: game_index,
+            decision: i as u32,
+            round: d.round,
+            phase: d.phase,
+            player_index: d.player_index,
+            variant: &labels[variant_order[d.player_index]],
+            observation: d.observation,
+            actions: d
+                .choices
+                .iter()
+                .map(|c| choice_to_index(c).expect("Enumerated choice outside the action space"))
+                .collect(),
+            choices: d.choices,
+            visits: d.visits,
+            chosen: d.chosen,
+            outcome: rewards[d.player_index],
+            final_score: calculate_score(&state.players[d.player_index]),
+        };
+        serde_json::to_writer(&mut *writer, &record).expect("Failed to serialize decision");
+        writer.write_all(b"\n").expect("Failed to write decision");
+    }
+}
+
+const COMMAND: &str = "selfplay";
+
+pub fn run_selfplay(args: &SelfplayArgs, threads: usize, output: &str, resume: bool) {
+    // The arguments fix every game, so a checkpoint needs no other state;
+    // the journal lists the shards written so far
+    let resumed: Option<Checkpoint<SelfplayArgs, ()>> = resume.then(|| checkpoint::load(output, COMMAND));
+    let args = resumed.as_ref().map_or(args, |checkpoint| &checkpoint.args);
+    let player_variants = load_variants_from_file(&args.variants_file);
+    if player_variants.iter().any(|v| v.ai.time_limit_ms.is_some()) {
+        eprintln!("Warning: time-limited variants make self-play data non-reproducible");
+    }
+    let labels: Vec<String> = player_variants
+        .iter()
+        .enumerate()
+        .map(|(i, v)| v.name.clone().unwrap_or_else(|| format!("variant_{}", i)))
+        .collect();
+
+    let games_per_shard = args.games_per_shard.max(1);
+    let num_shards = args.games.div_ceil(games_per_shard);
+
+    eprintln!(
+        "Self-play: {} games, {} players, {} shards, seed {}, {} threads",
+        args.games,
+        player_variants.len(),
+        num_shards,
+        args.seed,
+        threads
+    );
+
+    std::fs::create_dir_all(output).expect("Failed to create output directory");
+
+    let manifest = SelfplayManifest {
+        seed: args.seed,
+        games: args.games,
+        games_per_shard,
+        shards: num_shards,
+        observation_len: OBSERVATION_LEN,
+        action_space_size: ACTION_SPACE_SIZE,
+        variants: labels.clone(),
+    };
+    let manifest_path = format!("{}/manifest.json", output);
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest).unwrap())
+        .expect("Failed to write manifest");
+
+    let (journal, written) = match &resumed {
+        Some(_) => Journal::resume::<usize>(output, COMMAND),
+        None => {
+            checkpoint::save(output, COMMAND, &Checkpoint { args, state: () });
+            (Journal::create(output, COMMAND), Vec::new())
+        }
+    };
+    let written: HashSet<usize> = written.into_iter().collect();
+    let pending: Vec<usize> = (0..num_shards).filter(|shard| !written.contains(shard)).collect();
+    let already_played: usize = written
+        .iter()
+        .map(|&shard| games_per_shard.min(args.games - shard * games_per_shard))
+        .sum();
+    if resumed.is_some() {
+        eprintln!("{} of {} shards already written", written.len(), num_shards);
+    }
+
+    let next_shard = AtomicUsize::new(0);
+    let completed = AtomicUsize::new(already_played);
+    let player_variants = player_variants.as_slice();
+    let labels = labels.as_slice();
+    let total_games = args.games;
+    let seed = args.seed;
+
+    std::thread::scope(|s| {
+        for _ in 0..threads {
+            let next_shard = &next_shard;
+            let completed = &completed;
+            let pending = &pending;
+            let journal = &journal;
+            s.spawn(move || {
+                while let Some(&shard) = pending.get(next_shard.fetch_add(1, Ordering::Relaxed)) {
+                    // Written under a temporary name until complete, so a shard
+                    // file is never left half written
+                    let path = format!("{}/shard-{:05}.jsonl", output, shard);
+                    let tmp_path = format!("{}.tmp", path);
+                    let file = std::fs::File::create(&tmp_path)
+                        .unwrap_or_else(|_| panic!("Failed to create shard file: {}", tmp_path));
+                    let mut writer = BufWriter::new(file);
+                    let first = shard * games_per_shard;
+                    let last = (first + games_per_shard).min(total_games);
+                    for game_index in first..last {
+                        play_selfplay_game(game_index, player_variants, labels, seed, &mut writer);
+                        let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+                        eprintln!("Game {}/{} complete", done, total_games);
+                    }
+                    writer.flush().expect("Failed to flush shard file");
+                    drop(writer);
+                    std::fs::rename(&tmp_path, &path).unwrap_or_else(|_| panic!("Failed to write shard file: {}", path));
+                    journal.append(&shard);
+                }
+            });
+        }
+    });
+
+    eprintln!("All {} games written to {}/ ({} shards)", total_games, output, num_shards);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use colori_core::ismcts::MctsConfig;
+
+    #[test]
+    fn test_game_seeds_do_not_overlap_between_runs() {
+        let run = |seed| (0..100).map(|i| game_seed(seed, i)).collect::<HashSet<u64>>();
+        assert_eq!(run(0).len(), 100);
+        assert!(run(0).is_disjoint(&run(1)));
+    }
+
+    #[test]
+    fn test_same_seed_and_game_give_identical_records() {
+        let params = serde_json::from_str(include_str!("../../genetic-algorithm/batch-lki08w-gen-32.json")).unwrap();
+        let ai = MctsConfig { iterations: 5, ..MctsConfig::new(params) };
+        let variants = vec![NamedVariant { name: None, ai: ai.clone() }, NamedVariant { name: None, ai }];
+        let labels = vec!["a".to_string(), "b".to_string()];
+        let play = |game_index| {
+            let mut out = Vec::new();
+            play_selfplay_game(game_index, &variants, &labels, 3, &mut out);
+            String::from_utf8(out).unwrap()
+        };
+        let first = play(4);
+        assert!(!first.is_empty());
+        assert_eq!(first, play(4));
+        assert_ne!(first.replace("\"game\":4", "\"game\":5"), play(5));
+    }
+}
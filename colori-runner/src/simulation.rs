@@ -1,7 +1,7 @@
 use colori_core::colori_game::{apply_choice_to_state, enumerate_choices};
 use colori_core::draw_phase::execute_draw_phase;
 use colori_core::game_log::{DrawEvent, DrawLog, FinalPlayerStats, FinalScore, PlayerVariant};
-use colori_core::ismcts::{ismcts, MctsConfig, MctsNode};
+use colori_core::ismcts::{ismcts, MctsConfig, MctsTree};
 use colori_core::scoring::calculate_score;
 use colori_core::setup::create_initial_game_state;
 use colori_core::types::*;
@@ -12,11 +12,15 @@ use rand::seq::SliceRandom;
 use rand::RngExt;
 use rand::SeedableRng;
 use wyrand::WyRand;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::cli::{NamedVariant, SimulateArgs, load_variants_from_file, parse_inline_variants};
+use crate::checkpoint::{self, Checkpoint, GameRecord, Journal, write_atomic};
+use crate::duplicate::{PairedStats, PlayerShares};
 use crate::generate_batch_id;
 
 // ── Serialization types ──
@@ -45,6 +49,8 @@ pub struct GameRunOutput {
     pub player_variants: Option<Vec<PlayerVariant>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    #[serde(serialize_with = "colori_core::game_log::serialize_seed")]
+    pub deal_seed: u64,
     #[serde(skip)]
     pub variant_order: Vec<usize>,
 }
@@ -186,19 +192,37 @@ fn variant_to_player_variant(variant: &NamedVariant) -> PlayerVariant {
 
 // ── Game loop ──
 
+/// How the variants passed to `run_game` are seated.
+#[derive(Clone, Copy)]
+pub enum Seating {
+    /// A random seat order, to even out position bias over many games.
+    Shuffled,
+    /// Seat `s` is taken by variant `(s + rotation) % num_players`.
+    Rotated(usize),
+}
+
 pub fn run_game(
     _game_index: usize,
     player_variants: &[NamedVariant],
     note: Option<String>,
     max_rounds: Option<u32>,
+    seating: Seating,
+    deal_seed: Option<u64>,
     rng: &mut WyRand,
 ) -> GameRunOutput {
     let start = std::time::Instant::now();
     let num_players = player_variants.len();
 
-    // Shuffle variant assignment to eliminate position bias
     let mut variant_order: Vec<usize> = (0..num_players).collect();
-    variant_order.shuffle(rng);
+    match seating {
+        Seating::Shuffled => variant_order.shuffle(rng),
+        Seating::Rotated(rotation) => variant_order.rotate_left(rotation % num_players),
+    }
+
+    // Chance outcomes come from their own generator, so a deal plays out the
+    // same however much the AIs use theirs
+    let deal_seed = deal_seed.unwrap_or_else(|| rng.random());
+    let mut chance_rng = WyRand::seed_from_u64(deal_seed);
     let shuffled_variants: Vec<NamedVariant> = variant_order.iter().map(|&i| player_variants[i].clone()).collect();
 
     let has_variants = has_any_difference(&shuffled_variants);
@@ -214,7 +238,7 @@ pub fn run_game(
         .collect();
 
     let ai_players = vec![true; num_players];
-    let mut state = create_initial_game_state(num_players, &ai_players, rng);
+    let mut state = create_initial_game_state(num_players, &ai_players, &mut chance_rng);
     if let Some(mr) = max_rounds {
         state.max_rounds = mr;
     }
@@ -224,7 +248,7 @@ pub fn run_game(
 
     // Start first round (draw phase -> draft phase)
     state.draw_log = Some(DrawLog::Recording(Vec::new()));
-    execute_draw_phase(&mut state, rng);
+    execute_draw_phase(&mut state, &mut chance_rng);
     let initial_draws = match state.draw_log.take() {
         Some(DrawLog::Recording(events)) => events,
         _ => Vec::new(),
@@ -232,7 +256,7 @@ pub fn run_game(
 
     let mut entries: Vec<StructuredLogEntry> = Vec::new();
     let mut seq: u32 = 0;
-    let mut reuse_tree: Option<MctsNode> = None;
+    let mut reuse_tree: Option<MctsTree> = None;
     let mut player_time = vec![std::time::Duration::ZERO; num_players];
     let mut player_iterations_count = vec![0u64; num_players];
 
@@ -257,11 +281,11 @@ pub fn run_game(
         let is_first_pick = state.round == 1
             && matches!(&state.phase, GamePhase::Draft { draft_state } if draft_state.pick_number == 0);
 
-        let (choice, mcts_tree): (Choice, Option<MctsNode>) = if is_first_pick && config.random_first_pick {
+        let (choice, mcts_tree): (Choice, Option<MctsTree>) = if is_first_pick && config.random_first_pick {
             let choices = enumerate_choices(&state);
             (choices.choose(rng).expect("No choices available").clone(), None)
         } else {
-            let max_rollout_round = std::cmp::max(8, state.round + 2);
+            let max_rollout_round = config.max_rollout_round(state.round);
             let mcts_start = std::time::Instant::now();
             let result = ismcts(&state, player_index, config, Some(max_rollout_round), reuse_tree.take(), rng);
             player_time[player_index] += mcts_start.elapsed();
@@ -280,7 +304,7 @@ pub fn run_game(
 
         // Enable draw recording before applying the choice
         state.draw_log = Some(DrawLog::Recording(Vec::new()));
-        apply_choice_to_state(&mut state, &choice, rng);
+        apply_choice_to_state(&mut state, &choice, &mut chance_rng);
         let draws = match state.draw_log.take() {
             Some(DrawLog::Recording(events)) => events,
             _ => Vec::new(),
@@ -377,11 +401,42 @@ pub fn run_game(
         iterations: log_iterations,
         player_variants: log_player_variants,
         note,
+        deal_seed,
         variant_order,
     }
 }
 
-pub fn run_simulation(args: &SimulateArgs, threads: usize, output: &str) {
+/// Saves a finished game's log in `output` and returns its file name.
+pub fn write_game_log(output: &str, batch_id: &str, log: &GameRunOutput, rng: &mut WyRand) -> String {
+    set_card_registry(&log.initial_state.card_lookup);
+    set_sell_card_registry(&log.initial_state.sell_card_lookup);
+    let epoch_millis = now_epoch_millis();
+    let game_id: String = {
+        const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+        (0..4)
+            .map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char)
+            .collect()
+    };
+    let file_name = format!("game-{}-{}-{}.json", epoch_millis, batch_id, game_id);
+    let json = serde_json::to_string_pretty(log).unwrap();
+    write_atomic(&format!("{}/{}", output, file_name), &json);
+    file_name
+}
+
+const COMMAND: &str = "simulate";
+
+/// What a resumed simulation needs besides its arguments.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulateRun {
+    batch_id: String,
+    base_seed: u64,
+    labels: Vec<String>,
+}
+
+pub fn run_simulation(args: &SimulateArgs, threads: usize, output: &str, resume: bool) {
+    let resumed: Option<Checkpoint<SimulateArgs, SimulateRun>> = resume.then(|| checkpoint::load(output, COMMAND));
+    let args = resumed.as_ref().map_or(args, |checkpoint| &checkpoint.args);
     let player_variants = if let Some(ref v) = args.variants {
         let heuristic_params = crate::cli::load_heuristic_params(
             args.heuristic_params_file.as_ref().expect("--heuristic-params-file is required when using --variants")
@@ -393,99 +448,135 @@ pub fn run_simulation(args: &SimulateArgs, threads: usize, output: &str) {
     let num_players = player_variants.len();
     let solo = num_players == 1;
     let max_rounds = if solo { Some(args.max_rounds) } else { None };
+    if solo && args.duplicate {
+        eprintln!("Duplicate mode requires at least 2 players");
+        std::process::exit(1);
+    }
+    // In duplicate mode every deal is played once per seating
+    let (units, total_games) = if args.duplicate {
+        let deals = args.games.div_ceil(num_players);
+        (deals, deals * num_players)
+    } else {
+        (args.games, args.games)
+    };
 
     if solo {
         eprintln!(
             "Running {} solo games ({} rounds, {} MCTS iterations, {} threads)",
-            args.games, args.max_rounds, player_variants[0].ai.iterations, threads
+            total_games, args.max_rounds, player_variants[0].ai.iterations, threads
         );
     } else if has_any_difference(&player_variants) {
         let differing = compute_differing_fields(&player_variants);
         let labels: Vec<String> = player_variants.iter().map(|v| format_variant_label(v, &differing)).collect();
         eprintln!(
             "Running {} games with variants: {}, {} threads",
-            args.games,
+            total_games,
             labels.join(", "),
             threads
         );
     } else if let Some(tl) = player_variants[0].ai.time_limit_ms {
         eprintln!(
             "Running {} games with {} players, {}ms MCTS time limit, {} threads",
-            args.games, num_players, tl, threads
+            total_games, num_players, tl, threads
         );
     } else {
         eprintln!(
             "Running {} games with {} players, {} ISMCTS iterations, {} threads",
-            args.games, num_players,
+            total_games, num_players,
             player_variants[0].ai.iterations,
             threads
         );
     }
 
+    if args.duplicate {
+        eprintln!("Duplicate mode: {} deals, each played with the variants in every seat", units);
+    }
+
     std::fs::create_dir_all(output).expect("Failed to create output directory");
 
-    let batch_id = generate_batch_id();
-    let completed = AtomicUsize::new(0);
+    let labels: Vec<String> = {
+        let differing = compute_differing_fields(&player_variants);
+        player_variants.iter().map(|v| format_variant_label(v, &differing)).collect()
+    };
+    let (batch_id, base_seed, journal, records) = match &resumed {
+        Some(checkpoint) => {
+            checkpoint::check_labels(&checkpoint.state.labels, &labels);
+            let (journal, records) = Journal::resume(output, COMMAND);
+            checkpoint::remove_unrecorded_logs(output, &checkpoint.state.batch_id, &records);
+            eprintln!("{} of {} games already played", records.len(), total_games);
+            (checkpoint.state.batch_id.clone(), checkpoint.state.base_seed, journal, records)
+        }
+        None => {
+            let batch_id = generate_batch_id();
+            let base_seed = args.seed.unwrap_or_else(|| rand::rng().random());
+            let state = SimulateRun { batch_id: batch_id.clone(), base_seed, labels: labels.clone() };
+            checkpoint::save(output, COMMAND, &Checkpoint { args, state });
+            (batch_id, base_seed, Journal::create(output, COMMAND), Vec::new())
+        }
+    };
+
+    let completed = AtomicUsize::new(records.len());
     let variant_time_ms: Vec<AtomicU64> = (0..num_players).map(|_| AtomicU64::new(0)).collect();
     let variant_iterations: Vec<AtomicU64> = (0..num_players).map(|_| AtomicU64::new(0)).collect();
     let solo_wins = AtomicUsize::new(0);
     let solo_total_score = AtomicU64::new(0);
-    let total_games = args.games;
-    let num_threads = threads;
     let batch_id = batch_id.as_str();
     let note = &args.note;
+    let duplicate = args.duplicate;
+    let paired = PairedStats::new(labels);
+    // The seatings of each deal played so far, in duplicate mode
+    let seatings: Vec<Mutex<Vec<PlayerShares>>> = (0..if duplicate { units } else { 0 })
+        .map(|_| Mutex::new(Vec::new()))
+        .collect();
     let player_variants = player_variants.as_slice();
 
+    let record_game = |record: &GameRecord| {
+        for (seat, &variant) in record.variants.iter().enumerate() {
+            variant_time_ms[variant].fetch_add(record.player_time_ms[seat], Ordering::Relaxed);
+            variant_iterations[variant].fetch_add(record.player_iterations[seat], Ordering::Relaxed);
+        }
+        if solo {
+            let score = record.rankings[0].0;
+            if score >= 16 {
+                solo_wins.fetch_add(1, Ordering::Relaxed);
+            }
+            solo_total_score.fetch_add(score as u64, Ordering::Relaxed);
+        }
+        if duplicate {
+            let mut deal_seatings = seatings[record.item / num_players].lock().unwrap();
+            deal_seatings.push(record.shares());
+            if deal_seatings.len() == num_players {
+                paired.record_deal(&deal_seatings);
+            }
+        }
+    };
+
+    for record in &records {
+        record_game(record);
+    }
+    // In duplicate mode game N is seating N % players of deal N / players,
+    // dealt from the base seed + the deal number
+    let played: HashSet<usize> = records.iter().map(|r| r.item).collect();
+    let pending: Vec<usize> = (0..total_games).filter(|i| !played.contains(i)).collect();
+    let next = AtomicUsize::new(0);
+
     std::thread::scope(|s| {
-        let games_per_thread = total_games / num_threads;
-        let remainder = total_games % num_threads;
-        let mut handles = Vec::new();
-
-        for t in 0..num_threads {
-            let count = games_per_thread + if t < remainder { 1 } else { 0 };
-            let completed = &completed;
-            let variant_time_ms = &variant_time_ms;
-            let variant_iterations = &variant_iterations;
-            let solo_wins = &solo_wins;
-            let solo_total_score = &solo_total_score;
-
-            handles.push(s.spawn(move || {
+        for _ in 0..threads {
+            s.spawn(|| {
                 let mut rng = WyRand::from_rng(&mut rand::rng());
-
-                for _i in 0..count {
-                    let log = run_game(
-                        0,
-                        player_variants,
-                        note.clone(),
-                        max_rounds,
-                        &mut rng,
-                    );
-                    for (player_pos, &orig_idx) in log.variant_order.iter().enumerate() {
-                        variant_time_ms[orig_idx].fetch_add(log.player_time_ms[player_pos], Ordering::Relaxed);
-                        variant_iterations[orig_idx].fetch_add(log.player_iterations[player_pos], Ordering::Relaxed);
-                    }
-                    if solo {
-                        let score = log.final_scores.as_ref()
-                            .and_then(|fs| fs.first())
-                            .map(|fs| fs.score)
-                            .unwrap_or(0);
-                        if score >= 16 {
-                            solo_wins.fetch_add(1, Ordering::Relaxed);
-                        }
-                        solo_total_score.fetch_add(score as u64, Ordering::Relaxed);
-                    }
-                    set_card_registry(&log.initial_state.card_lookup);
-                    set_sell_card_registry(&log.initial_state.sell_card_lookup);
-                    let epoch_millis = now_epoch_millis();
-                    let game_id: String = {
-                        const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
-                        (0..4)
-                            .map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char)
-                            .collect()
+                while let Some(&item) = pending.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let (seating, deal_seed) = if duplicate {
+                        let seed = base_seed.wrapping_add((item / num_players) as u64);
+                        (Seating::Rotated(item % num_players), Some(seed))
+                    } else {
+                        (Seating::Shuffled, None)
                     };
-                    let path = format!("{}/game-{}-{}-{}.json", output, epoch_millis, batch_id, game_id);
-                    let json = serde_json::to_string_pretty(&log).unwrap();
-                    std::fs::write(&path, json).unwrap();
+                    let log = run_game(0, player_variants, note.clone(), max_rounds, seating, deal_seed, &mut rng);
+                    let log_file = write_game_log(output, batch_id, &log, &mut rng);
+                    let record = GameRecord::new(item, log_file, &log, log.variant_order.clone());
+                    record_game(&record);
+                    journal.append(&record);
+
                     let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
                     if solo {
                         if done % 100 == 0 || done == total_games {
@@ -499,11 +590,7 @@ pub fn run_simulation(args: &SimulateArgs, threads: usize, output: &str) {
                         eprintln!("Game {}/{} complete", done, total_games);
                     }
                 }
-            }));
-        }
-
-        for handle in handles {
-            handle.join().unwrap();
+            });
         }
     });
 
@@ -533,5 +620,8 @@ pub fn run_simulation(args: &SimulateArgs, threads: usize, output: &str) {
         let avg_iters = total_iters as f64 / (total_games as f64 * num_players as f64);
         eprintln!("Average per player per game: {:.1}s, {:.0} iters", avg_secs, avg_iters);
     }
+    if duplicate {
+        paired.print_summary();
+    }
     eprintln!("All {} games written to {}/", total_games, output);
 }
@@ -0,0 +1,344 @@
+//! Sequential probability ratio test between two variants.
+//!
+//! Games are scored from the candidate's point of view (1 for a win, 0.5 for
+//! a draw, 0 for a loss). After every game the generalized log-likelihood
+//! ratio of `elo1` against `elo0` is computed: for each bound, the trinomial
+//! win/draw/loss distribution closest to the observed frequencies whose
+//! expected score matches that bound is found by maximum likelihood, and the
+//! LLR compares the two. This is fishtest's GSPRT without the normal
+//! approximation, so it stays well behaved when one side wins every game.
+//! The test stops once the LLR leaves
+//! `[ln(beta / (1 - alpha)), ln((1 - beta) / alpha)]`.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use wyrand::WyRand;
+
+use crate::checkpoint::{self, Checkpoint, GameRecord, Journal};
+use crate::cli::{NamedVariant, SprtArgs, load_variants_from_file};
+use crate::generate_batch_id;
+use crate::simulation::{Seating, run_game, write_game_log};
+
+#[derive(Clone, Copy)]
+enum Verdict {
+    AcceptH0,
+    AcceptH1,
+}
+
+struct SprtState {
+    wins: u64,
+    draws: u64,
+    losses: u64,
+    llr: f64,
+    verdict: Option<Verdict>,
+}
+
+impl SprtState {
+    fn games(&self) -> u64 {
+        self.wins + self.draws + self.losses
+    }
+
+    fn mean_score(&self) -> f64 {
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+}
+
+/// Expected score of a player `elo` points stronger than its opponent.
+fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+fn score_to_elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Pseudo-count added to each outcome so that outcomes not seen yet do not
+/// get probability zero.
+const RESULT_EPSILON: f64 = 1e-3;
+
+const OUTCOME_SCORES: [f64; 3] = [1.0, 0.5, 0.0];
+
+/// Maximum likelihood win/draw/loss probabilities given the observed
+/// frequencies `freqs`, constrained to an expected score of `score`.
+///
+/// The solution has the form `p_i = f_i / (1 + lambda * (a_i - score))`; the
+/// multiplier is found by bisection, which converges since the constraint is
+/// monotonic in `lambda` on the interval that keeps every `p_i` positive.
+fn constrained_mle(freqs: [f64; 3], score: f64) -> [f64; 3] {
+    let diffs = OUTCOME_SCORES.map(|a| a - score);
+    let constraint = |lambda: f64| -> f64 {
+        (0..3).map(|i| freqs[i] * diffs[i] / (1.0 + lambda * diffs[i])).sum()
+    };
+    // diffs[0] > 0 > diffs[2] for any score strictly between 0 and 1
+    let mut lo = -1.0 / diffs[0];
+    let mut hi = -1.0 / diffs[2];
+    for _ in 0..100 {
+        let mid = 0.5 * (lo + hi);
+        if constraint(mid) > 0.0 {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    let lambda = 0.5 * (lo + hi);
+    let probs: [f64; 3] = std::array::from_fn(|i| freqs[i] / (1.0 + lambda * diffs[i]));
+    let total: f64 = probs.iter().sum();
+    probs.map(|x| x / total)
+}
+
+/// Generalized log-likelihood ratio of expected score `s1` against `s0`
+/// given the game results so far.
+fn log_likelihood_ratio(wins: u64, draws: u64, losses: u64, s0: f64, s1: f64) -> f64 {
+    let counts = [wins as f64, draws as f64, losses as f64];
+    let total: f64 = counts.iter().sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let regularized_total = total + 3.0 * RESULT_EPSILON;
+    let freqs = counts.map(|c| (c + RESULT_EPSILON) / regularized_total);
+    let p0 = constrained_mle(freqs, s0);
+    let p1 = constrained_mle(freqs, s1);
+    (0..3).map(|i| counts[i] * (p1[i] / p0[i]).ln()).sum()
+}
+
+/// Score of the candidate (variant 0) in a finished 2-player game.
+fn candidate_score(record: &GameRecord) -> f64 {
+    record.shares().into_iter().find(|&(variant, _)| variant == 0).unwrap().1
+}
+
+const COMMAND: &str = "sprt";
+
+/// What a resumed test needs besides its arguments; the games themselves
+/// are in the journal.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SprtRun {
+    batch_id: String,
+    labels: Vec<String>,
+}
+
+pub fn run_sprt(args: &SprtArgs, threads: usize, output: &str, resume: bool) {
+    let resumed: Option<Checkpoint<SprtArgs, SprtRun>> = resume.then(|| checkpoint::load(output, COMMAND));
+    let args = resumed.as_ref().map_or(args, |checkpoint| &checkpoint.args);
+    let loaded_variants = load_variants_from_file(&args.variants_file);
+    if loaded_variants.len() != 2 {
+        eprintln!("SPRT mode requires exactly 2 variants (candidate, baseline)");
+        std::process::exit(1);
+    }
+    if args.elo1 <= args.elo0 {
+        eprintln!("--elo1 must be greater than --elo0");
+        std::process::exit(1);
+    }
+    if !(args.alpha > 0.0 && args.alpha < 1.0 && args.beta > 0.0 && args.beta < 1.0) {
+        eprintln!("--alpha and --beta must be between 0 and 1");
+        std::process::exit(1);
+    }
+
+    // Named variants so every log records which side was which
+    let mut labels: Vec<String> = loaded_variants
+        .iter()
+        .zip(["candidate", "baseline"])
+        .map(|(v, default)| v.name.clone().unwrap_or_else(|| default.to_string()))
+        .collect();
+    if labels[0] == labels[1] {
+        labels = labels.iter().enumerate().map(|(i, name)| format!("{} [{}]", name, i)).collect();
+    }
+    let variants: Vec<NamedVariant> = loaded_variants
+        .iter()
+        .zip(labels.iter())
+        .map(|(v, label)| NamedVariant {
+            name: Some(label.clone()),
+            ai: v.ai.clone(),
+        })
+        .collect();
+
+    let s0 = elo_to_score(args.elo0);
+    let s1 = elo_to_score(args.elo1);
+    let lower = (args.beta / (1.0 - args.alpha)).ln();
+    let upper = ((1.0 - args.beta) / args.alpha).ln();
+
+    eprintln!(
+        "SPRT: {} vs {}, elo0={}, elo1={}, alpha={}, beta={}, bounds [{:.3}, {:.3}], max {} games, {} threads",
+        labels[0], labels[1], args.elo0, args.elo1, args.alpha, args.beta, lower, upper, args.max_games, threads
+    );
+
+    std::fs::create_dir_all(output).expect("Failed to create output directory");
+
+    let (batch_id, journal, records) = match &resumed {
+        Some(checkpoint) => {
+            checkpoint::check_labels(&checkpoint.state.labels, &labels);
+            let (journal, records) = Journal::resume(output, COMMAND);
+            checkpoint::remove_unrecorded_logs(output, &checkpoint.state.batch_id, &records);
+            eprintln!("{} games already played", records.len());
+            (checkpoint.state.batch_id.clone(), journal, records)
+        }
+        None => {
+            let batch_id = generate_batch_id();
+            let state = SprtRun { batch_id: batch_id.clone(), labels: labels.clone() };
+            checkpoint::save(output, COMMAND, &Checkpoint { args, state });
+            (batch_id, Journal::create(output, COMMAND), Vec::new())
+        }
+    };
+    let batch_id_str = batch_id.as_str();
+    let note = &args.note;
+    let variants = variants.as_slice();
+    let max_games = args.max_games;
+    let state = Mutex::new(SprtState {
+        wins: 0,
+        draws: 0,
+        losses: 0,
+        llr: 0.0,
+        verdict: None,
+    });
+    let state = &state;
+    let started = AtomicUsize::new(records.len());
+    let started = &started;
+    let stop = AtomicBool::new(false);
+    let stop = &stop;
+    let journal = &journal;
+
+    let add_game = move |st: &mut SprtState, record: &GameRecord| {
+        // Games still in flight when the test stops are logged but not
+        // counted, so the totals match the final LLR.
+        if st.verdict.is_some() {
+            return;
+        }
+        let score = candidate_score(record);
+        if score == 1.0 {
+            st.wins += 1;
+        } else if score == 0.0 {
+            st.losses += 1;
+        } else {
+            st.draws += 1;
+        }
+        st.llr = log_likelihood_ratio(st.wins, st.draws, st.losses, s0, s1);
+        println!("{}\t{}\t{}\t{}\t{:.4}", st.games(), st.wins, st.draws, st.losses, st.llr);
+        if st.llr >= upper {
+            st.verdict = Some(Verdict::AcceptH1);
+        } else if st.llr <= lower {
+            st.verdict = Some(Verdict::AcceptH0);
+        }
+        if st.verdict.is_some() || st.games() as usize >= max_games {
+            stop.store(true, Ordering::Relaxed);
+        }
+    };
+
+    println!("games\twins\tdraws\tlosses\tllr");
+    for record in &records {
+        add_game(&mut state.lock().unwrap(), record);
+    }
+
+    std::thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(move || {
+                let mut rng = WyRand::from_rng(&mut rand::rng());
+
+                while !stop.load(Ordering::Relaxed) {
+                    let item = started.fetch_add(1, Ordering::Relaxed);
+                    if item >= max_games {
+                        break;
+                    }
+                    let log = run_game(0, variants, note.clone(), None, Seating::Shuffled, None, &mut rng);
+                    let log_file = write_game_log(output, batch_id_str, &log, &mut rng);
+                    let record = GameRecord::new(item, log_file, &log, log.variant_order.clone());
+
+                    // Journaled in the order the games are counted, so that
+                    // replaying the journal gives the same result
+                    let mut st = state.lock().unwrap();
+                    journal.append(&record);
+                    add_game(&mut st, &record);
+                }
+            });
+        }
+    });
+
+    let st = state.lock().unwrap();
+    eprintln!();
+    eprintln!("=== SPRT Result ===");
+    eprintln!();
+    eprintln!("Games:   {} ({}W / {}D / {}L)", st.games(), st.wins, st.draws, st.losses);
+    if st.games() > 0 {
+        let score = st.mean_score();
+        let elo = if score > 0.0 && score < 1.0 {
+            format!("{:+.1}", score_to_elo(score))
+        } else if score >= 1.0 {
+            "+inf".to_string()
+        } else {
+            "-inf".to_string()
+        };
+        eprintln!("Score:   {:.1}% ({} Elo)", score * 100.0, elo);
+    }
+    eprintln!("LLR:     {:.3} (bounds [{:.3}, {:.3}])", st.llr, lower, upper);
+    match st.verdict {
+        Some(Verdict::AcceptH1) => eprintln!(
+            "Result:  H1 accepted, {} is at least {} Elo stronger than {}",
+            labels[0], args.elo1, labels[1]
+        ),
+        Some(Verdict::AcceptH0) => eprintln!(
+            "Result:  H0 accepted, {} is at most {} Elo stronger than {}",
+            labels[0], args.elo0, labels[1]
+        ),
+        None => eprintln!("Result:  undecided after {} games", st.games()),
+    }
+    eprintln!("Game logs written to {}/", output);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elo_score_round_trip() {
+        assert_eq!(elo_to_score(0.0), 0.5);
+        assert!((elo_to_score(400.0) - 10.0 / 11.0).abs() < 1e-12);
+        for elo in [-300.0, -5.0, 0.0, 20.0, 250.0] {
+            assert!((score_to_elo(elo_to_score(elo)) - elo).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_constrained_mle_meets_score() {
+        let freqs = [0.5, 0.2, 0.3];
+        for score in [0.3, 0.5, 0.6, 0.9] {
+            let p = constrained_mle(freqs, score);
+            assert!((p.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            let expected: f64 = (0..3).map(|i| p[i] * OUTCOME_SCORES[i]).sum();
+            assert!((expected - score).abs() < 1e-9, "score {} gave {:?}", score, p);
+        }
+        // The observed score itself is matched by the observed frequencies
+        let p = constrained_mle(freqs, 0.6);
+        assert!((0..3).all(|i| (p[i] - freqs[i]).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_llr_without_draws_matches_binomial() {
+        let (s0, s1) = (elo_to_score(0.0), elo_to_score(20.0));
+        let llr = log_likelihood_ratio(60, 0, 40, s0, s1);
+        let binomial = 60.0 * (s1 / s0).ln() + 40.0 * ((1.0 - s1) / (1.0 - s0)).ln();
+        assert!((llr - binomial).abs() < 1e-3, "llr {} against {}", llr, binomial);
+    }
+
+    #[test]
+    fn test_llr_signs_and_symmetry() {
+        let (s0, s1) = (elo_to_score(0.0), elo_to_score(10.0));
+        assert_eq!(log_likelihood_ratio(0, 0, 0, s0, s1), 0.0);
+        assert_eq!(log_likelihood_ratio(30, 40, 30, s0, s0), 0.0);
+        assert!(log_likelihood_ratio(70, 20, 30, s0, s1) > 0.0);
+        assert!(log_likelihood_ratio(30, 20, 70, s0, s1) < 0.0);
+        let forward = log_likelihood_ratio(55, 10, 45, s0, s1);
+        let backward = log_likelihood_ratio(55, 10, 45, s1, s0);
+        assert!((forward + backward).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_llr_stays_finite_when_one_side_wins_every_game() {
+        let (s0, s1) = (elo_to_score(0.0), elo_to_score(10.0));
+        let all_wins = log_likelihood_ratio(200, 0, 0, s0, s1);
+        let all_losses = log_likelihood_ratio(0, 0, 200, s0, s1);
+        assert!(all_wins.is_finite() && all_wins > 0.0);
+        assert!(all_losses.is_finite() && all_losses < 0.0);
+    }
+}
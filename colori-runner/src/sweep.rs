@@ -0,0 +1,314 @@
+//! Hyperparameter sweeps: variants generated from a base variant by varying
+//! some of its settings, ranked in a tournament.
+//!
+//! A sweep spec is a JSON file holding the base variant, written like an
+//! entry of a variants file, and either a grid of values for each field, of
+//! which every combination is played, or ranges from which `samples` points
+//! are drawn at random:
+//!
+//! ```json
+//! {
+//!   "base": { "iterations": 4000, "heuristicParamsFile": "params.json" },
+//!   "grid": { "explorationConstant": [0.7, 1.4, 2.8], "heuristicLookahead": [2, 3] }
+//! }
+//! ```
+//!
+//! ```json
+//! {
+//!   "base": { "iterations": 4000, "heuristicParamsFile": "params.json" },
+//!   "samples": 12,
+//!   "random": {
+//!     "progressiveBiasWeight": { "min": 0.01, "max": 1.0, "log": true },
+//!     "maxRolloutSteps": { "min": 20, "max": 200, "integer": true }
+//!   }
+//! }
+//! ```
+//!
+//! A field is any key of a variants file entry or of the heuristic params.
+//! The base variant plays as well, as the reference of the default gauntlet.
+
+use std::collections::BTreeMap;
+
+use rand::RngExt;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wyrand::WyRand;
+
+use crate::checkpoint::{self, Checkpoint};
+use crate::cli::{SweepArgs, TournamentArgs, variant_entry, variant_from_entry};
+use crate::tournament::{self, TournamentResults, run_tournament};
+
+const COMMAND: &str = "sweep";
+const BASE_NAME: &str = "base";
+const Z_95: f64 = 1.96;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SweepSpec {
+    base: Value,
+    #[serde(default)]
+    grid: BTreeMap<String, Vec<Value>>,
+    #[serde(default)]
+    random: BTreeMap<String, SampleRange>,
+    #[serde(default)]
+    samples: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SampleRange {
+    min: f64,
+    max: f64,
+    /// Sample uniformly on a log scale.
+    #[serde(default)]
+    log: bool,
+    /// Round to the nearest integer.
+    #[serde(default)]
+    integer: bool,
+}
+
+impl SampleRange {
+    fn sample(&self, rng: &mut WyRand) -> Value {
+        let u: f64 = rng.random();
+        let x = if self.log {
+            (self.min.ln() + u * (self.max.ln() - self.min.ln())).exp()
+        } else {
+            self.min + u * (self.max - self.min)
+        };
+        if self.integer {
+            (x.round() as i64).into()
+        } else {
+            round_significant(x, 3).into()
+        }
+    }
+}
+
+fn round_significant(x: f64, digits: i32) -> f64 {
+    if x == 0.0 {
+        return 0.0;
+    }
+    let scale = 10f64.powi(digits - x.abs().log10().ceil() as i32);
+    (x * scale).round() / scale
+}
+
+/// What a resumed sweep needs besides its arguments; the tournament keeps
+/// its own checkpoint.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SweepRun {
+    variants_file: String,
+    fields: Vec<String>,
+    /// The value of each field for each variant, the base variant first.
+    values: Vec<Vec<Value>>,
+}
+
+/// Every combination of the grid's values, with the fields in key order.
+fn grid_points(grid: &BTreeMap<String, Vec<Value>>) -> Vec<Vec<Value>> {
+    grid.values().fold(vec![Vec::new()], |points, values| {
+        points
+            .iter()
+            .flat_map(|point| {
+                values.iter().map(move |v| {
+                    let mut point = point.clone();
+                    point.push(v.clone());
+                    point
+                })
+            })
+            .collect()
+    })
+}
+
+/// Sets `field` in a full variants file entry: in the heuristic params if it
+/// is one of them, otherwise on the entry itself.
+fn set_field(entry: &mut Value, field: &str, value: Value) {
+    let params = &mut entry["heuristicParams"];
+    if params.get(field).is_some() {
+        params[field] = value;
+    } else {
+        entry[field] = value;
+    }
+}
+
+fn get_field(entry: &Value, field: &str) -> Value {
+    entry["heuristicParams"]
+        .get(field)
+        .or_else(|| entry.get(field))
+        .cloned()
+        .unwrap_or(Value::Null)
+}
+
+fn point_name(fields: &[String], values: &[Value]) -> String {
+    fields
+        .iter()
+        .zip(values)
+        .map(|(field, value)| format!("{}={}", field, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Generates the variants of a spec and writes them to a variants file in
+/// `output`.
+fn generate_variants(spec_path: &str, output: &str) -> SweepRun {
+    let contents = std::fs::read_to_string(spec_path)
+        .unwrap_or_else(|_| panic!("Failed to read sweep spec: {}", spec_path));
+    let spec: SweepSpec = serde_json::from_str(&contents)
+        .unwrap_or_else(|e| panic!("Failed to parse sweep spec: {}: {}", spec_path, e));
+
+    let (fields, points): (Vec<String>, Vec<Vec<Value>>) = match (spec.grid.is_empty(), spec.random.is_empty()) {
+        (false, true) => (spec.grid.keys().cloned().collect(), grid_points(&spec.grid)),
+        (true, false) => {
+            if spec.samples == 0 {
+                eprintln!("A random sweep needs \"samples\" greater than 0");
+                std::process::exit(1);
+            }
+            let mut rng = WyRand::from_rng(&mut rand::rng());
+            let points = (0..spec.samples)
+                .map(|_| spec.random.values().map(|range| range.sample(&mut rng)).collect())
+                .collect();
+            (spec.random.keys().cloned().collect(), points)
+        }
+        _ => {
+            eprintln!("A sweep spec needs exactly one of \"grid\" and \"random\"");
+            std::process::exit(1);
+        }
+    };
+
+    let mut base = variant_entry(&variant_from_entry(spec.base));
+    base["name"] = BASE_NAME.into();
+    let mut entries = vec![base.clone()];
+    let mut values = vec![fields.iter().map(|f| get_field(&base, f)).collect()];
+    for point in points {
+        let mut entry = base.clone();
+        for (field, value) in fields.iter().zip(&point) {
+            set_field(&mut entry, field, value.clone());
+        }
+        entry["name"] = point_name(&fields, &point).into();
+        // Parse every variant now, so that a bad field fails before any game
+        variant_from_entry(entry.clone());
+        entries.push(entry);
+        values.push(point);
+    }
+
+    std::fs::create_dir_all(output).expect("Failed to create output directory");
+    let variants_file = format!("{}/sweep-variants.json", output);
+    std::fs::write(&variants_file, serde_json::to_string_pretty(&entries).unwrap())
+        .unwrap_or_else(|_| panic!("Failed to write variants file: {}", variants_file));
+    eprintln!("Sweep: {} variants written to {}", entries.len(), variants_file);
+
+    SweepRun { variants_file, fields, values }
+}
+
+pub fn run_sweep(args: &SweepArgs, threads: usize, output: &str, resume: bool) {
+    let resumed: Option<Checkpoint<SweepArgs, SweepRun>> = resume.then(|| checkpoint::load(output, COMMAND));
+    let generated;
+    let (args, run) = match &resumed {
+        Some(checkpoint) => (&checkpoint.args, &checkpoint.state),
+        None => {
+            generated = generate_variants(args.spec.as_ref().expect("--spec is required for a sweep"), output);
+            checkpoint::save(output, COMMAND, &Checkpoint { args, state: &generated });
+            (args, &generated)
+        }
+    };
+
+    let tournament_args = TournamentArgs {
+        games: args.games,
+        note: args.note.clone(),
+        variants_file: run.variants_file.clone(),
+        players: args.players,
+        schedule: args.schedule,
+        reference: Some(BASE_NAME.to_string()),
+        duplicate: args.duplicate,
+        seed: args.seed,
+        priors: None,
+        ratings_file: None,
+    };
+    // The sweep checkpoint is saved first, so a run stopped before the
+    // tournament saved its own starts the tournament afresh.
+    let resume_tournament = resume && checkpoint::exists(output, tournament::COMMAND);
+    let results = run_tournament(&tournament_args, threads, output, resume_tournament);
+    write_results(&results, run, output);
+}
+
+/// Wilson score interval for a win rate of `score` out of `games`.
+fn wilson_interval(score: f64, games: u64) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let n = games as f64;
+    let p = score / n;
+    let z2 = Z_95 * Z_95;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    (center - half, center + half)
+}
+
+/// Prints the variants ranked by win rate and writes the same table to a
+/// TSV file in `output`.
+fn write_results(results: &TournamentResults, run: &SweepRun, output: &str) {
+    let n = results.labels.len();
+    let win_rate = |i: usize| {
+        if results.games[i] > 0 { results.scores[i] / results.games[i] as f64 } else { 0.0 }
+    };
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| win_rate(b).partial_cmp(&win_rate(a)).unwrap());
+
+    let max_label = results.labels.iter().map(|l| l.len()).max().unwrap_or(7).max(7);
+    eprintln!();
+    eprintln!("=== Sweep Results ===");
+    eprintln!();
+    eprintln!(
+        "{:>4}  {:<width$}  {:>5}  {:>8}  {:>15}  {:>16}",
+        "Rank",
+        "Variant",
+        "Games",
+        "Win Rate",
+        "95% CI",
+        "Rating",
+        width = max_label,
+    );
+    eprintln!("{}", "-".repeat(max_label + 61));
+
+    let mut tsv = String::from("rank\tvariant");
+    for field in &run.fields {
+        tsv.push('\t');
+        tsv.push_str(field);
+    }
+    tsv.push_str("\tgames\twinRate\tciLow\tciHigh\trating\tratingStdDev\n");
+
+    for (rank, &i) in order.iter().enumerate() {
+        let (low, high) = wilson_interval(results.scores[i], results.games[i]);
+        let rating = results.ratings.ratings[i];
+        let std_dev = results.ratings.std_devs[i];
+        eprintln!(
+            "{:>4}  {:<width$}  {:>5}  {:>7.1}%  {:>15}  {:>16}",
+            rank + 1,
+            results.labels[i],
+            results.games[i],
+            win_rate(i) * 100.0,
+            format!("{:.1}%–{:.1}%", low * 100.0, high * 100.0),
+            format!("{:+.1} ± {:.1}", rating, Z_95 * std_dev),
+            width = max_label,
+        );
+
+        tsv.push_str(&format!("{}\t{}", rank + 1, results.labels[i]));
+        for value in &run.values[i] {
+            tsv.push('\t');
+            tsv.push_str(&value.to_string());
+        }
+        tsv.push_str(&format!(
+            "\t{}\t{:.4}\t{:.4}\t{:.4}\t{:.1}\t{:.1}\n",
+            results.games[i],
+            win_rate(i),
+            low,
+            high,
+            rating,
+            std_dev
+        ));
+    }
+
+    let path = format!("{}/sweep-{}.tsv", output, results.batch_id);
+    std::fs::write(&path, tsv).unwrap_or_else(|_| panic!("Failed to write sweep results: {}", path));
+    eprintln!();
+    eprintln!("Sweep results written to {}", path);
+}
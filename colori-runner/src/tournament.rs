@@ -1,14 +1,23 @@
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
 
-use colori_core::unordered_cards::{set_card_registry, set_sell_card_registry};
+use colori_core::types::MAX_PLAYERS;
 use rand::RngExt;
 use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
 use wyrand::WyRand;
 
-use crate::cli::{NamedVariant, TournamentArgs, load_variants_from_file};
+use crate::checkpoint::{self, Checkpoint, GameRecord, Journal};
+use crate::cli::{NamedVariant, ScheduleKind, TournamentArgs, load_variants_from_file};
+use crate::duplicate::{PairedStats, PlayerShares};
 use crate::generate_batch_id;
-use crate::simulation::{now_epoch_millis, run_game};
+use crate::ratings::{
+    Comparison, Prior, Ranking, Ratings, comparisons_from_game, fit_ratings, load_priors, print_ratings,
+    write_ratings,
+};
+use crate::schedule::{Match, balanced_cycle_games, balanced_matches, gauntlet_matches, pair_key, random_matches, swiss_round};
+use crate::simulation::{Seating, run_game, write_game_log};
 
 struct TournamentStats {
     labels: Vec<String>,
@@ -17,6 +26,8 @@ struct TournamentStats {
     games: Vec<AtomicU64>,
     time_ms: Vec<AtomicU64>,
     iterations: Vec<AtomicU64>,
+    /// Total win share and games played by seat.
+    seats: Mutex<[(f64, u64); MAX_PLAYERS]>,
 }
 
 impl TournamentStats {
@@ -29,11 +40,39 @@ impl TournamentStats {
             games: (0..n).map(|_| AtomicU64::new(0)).collect(),
             time_ms: (0..n).map(|_| AtomicU64::new(0)).collect(),
             iterations: (0..n).map(|_| AtomicU64::new(0)).collect(),
+            seats: Mutex::new([(0.0, 0); MAX_PLAYERS]),
         }
     }
 }
 
-pub fn run_tournament(args: &TournamentArgs, threads: usize, output: &str) {
+pub const COMMAND: &str = "tournament";
+
+/// What a resumed tournament needs besides its arguments.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TournamentRun {
+    batch_id: String,
+    base_seed: u64,
+    labels: Vec<String>,
+    /// Every match scheduled so far; a Swiss tournament adds each round as
+    /// it is paired.
+    matches: Vec<Match>,
+}
+
+/// Per-variant results of a finished tournament.
+pub struct TournamentResults {
+    pub batch_id: String,
+    pub labels: Vec<String>,
+    pub games: Vec<u64>,
+    /// Wins plus half the draws.
+    pub scores: Vec<f64>,
+    pub ratings: Ratings,
+}
+
+pub fn run_tournament(args: &TournamentArgs, threads: usize, output: &str, resume: bool) -> TournamentResults {
+    let resumed: Option<Checkpoint<TournamentArgs, TournamentRun>> =
+        resume.then(|| checkpoint::load(output, COMMAND));
+    let args = resumed.as_ref().map_or(args, |checkpoint| &checkpoint.args);
     let loaded_variants = load_variants_from_file(&args.variants_file);
     let num_variants = loaded_variants.len();
     if num_variants < 2 {
@@ -83,118 +122,294 @@ pub fn run_tournament(args: &TournamentArgs, threads: usize, output: &str) {
         .map(|(i, name)| (name.clone(), i))
         .collect();
 
+    let num_players = args.players;
+    if !(2..=4).contains(&num_players) || num_players > num_variants {
+        eprintln!("--players must be between 2 and 4 and at most the number of variants");
+        std::process::exit(1);
+    }
+    if matches!(args.schedule, ScheduleKind::Gauntlet | ScheduleKind::Swiss) && num_players != 2 {
+        eprintln!("Gauntlet and Swiss schedules are for 2-player games");
+        std::process::exit(1);
+    }
+    if resumed.is_none() && matches!(args.schedule, ScheduleKind::Balanced) {
+        let cycle_games = balanced_cycle_games(num_variants, num_players);
+        if cycle_games > args.games {
+            eprintln!(
+                "A balanced schedule of {} variants at {}-player tables plays at least {} games; \
+                 raise --games to {} or use the random schedule",
+                num_variants, num_players, cycle_games, cycle_games
+            );
+            std::process::exit(1);
+        }
+    }
+    let reference = match &args.reference {
+        Some(name) => *name_to_index.get(name).unwrap_or_else(|| {
+            eprintln!("Unknown reference variant: {}", name);
+            std::process::exit(1);
+        }),
+        None => 0,
+    };
+    let priors = args.priors.as_deref().map(load_priors).unwrap_or_default();
+    let variant_priors: Vec<Option<Prior>> = labels.iter().map(|label| priors.get(label).copied()).collect();
+
+    // Swiss pairings depend on the ratings so far, so only the number of
+    // rounds is fixed up front; the other schedules are built in full.
+    let mut rng = WyRand::from_rng(&mut rand::rng());
+    let swiss_rounds = match args.schedule {
+        ScheduleKind::Swiss => args.games.div_ceil(num_variants / 2 * 2),
+        _ => 0,
+    };
+    let mut matches = match &resumed {
+        Some(checkpoint) => checkpoint.state.matches.clone(),
+        None => match args.schedule {
+            ScheduleKind::Random => {
+                // In duplicate mode every table is played once per seating
+                let count = if args.duplicate { args.games.div_ceil(num_players) } else { args.games };
+                random_matches(num_variants, num_players, count, args.duplicate, &mut rng)
+            }
+            ScheduleKind::Gauntlet => gauntlet_matches(num_variants, reference, args.games),
+            ScheduleKind::Balanced => balanced_matches(num_variants, num_players, args.games),
+            ScheduleKind::Swiss => Vec::new(),
+        },
+    };
+    let total_games = if swiss_rounds > 0 {
+        swiss_rounds * (num_variants / 2) * 2
+    } else {
+        matches.iter().map(|m| m.games()).sum()
+    };
+
     eprintln!(
-        "Tournament: {} games, {} variants, {} threads",
-        args.games, num_variants, threads
+        "Tournament: {} games, {} players per game, {} variants, {} threads",
+        total_games, num_players, num_variants, threads
     );
     for (i, label) in labels.iter().enumerate() {
-        eprintln!("  [{}] {}", i, label);
+        match priors.get(label) {
+            Some(prior) => eprintln!("  [{}] {} (prior {:+.1} ± {:.1})", i, label, prior.mean, prior.std_dev),
+            None => eprintln!("  [{}] {}", i, label),
+        }
+    }
+    match args.schedule {
+        ScheduleKind::Random => {}
+        ScheduleKind::Gauntlet => eprintln!("Gauntlet against {}, both seatings of every pairing", labels[reference]),
+        ScheduleKind::Balanced => eprintln!(
+            "Balanced schedule: {} tables, each played with the variants in every seat",
+            matches.len()
+        ),
+        ScheduleKind::Swiss => eprintln!("Swiss schedule: {} rounds, both seatings of every pairing", swiss_rounds),
+    }
+    if args.duplicate {
+        eprintln!("Duplicate mode: the seatings of each table share a deal");
     }
 
     std::fs::create_dir_all(output).expect("Failed to create output directory");
 
-    let batch_id = generate_batch_id();
-    let stats = TournamentStats::new(labels.clone());
-    let total_games = args.games;
-    let num_threads = threads;
-    let output_dir = output;
-    let batch_id_str = batch_id.as_str();
-    let note = &args.note;
-    let variants = variants.as_slice();
-    let stats = &stats;
-    let name_to_index = &name_to_index;
-    let completed = AtomicU64::new(0);
-    let completed = &completed;
-
-    std::thread::scope(|s| {
-        let games_per_thread = total_games / num_threads;
-        let remainder = total_games % num_threads;
-        let mut handles = Vec::new();
-
-        for t in 0..num_threads {
-            let count = games_per_thread + if t < remainder { 1 } else { 0 };
-
-            handles.push(s.spawn(move || {
-                let mut rng = WyRand::from_rng(&mut rand::rng());
-
-                for _ in 0..count {
-                    // Pick 2 distinct random variant indices
-                    let i = rng.random_range(0..num_variants);
-                    let mut j = rng.random_range(0..num_variants - 1);
-                    if j >= i {
-                        j += 1;
-                    }
+    let (batch_id, base_seed, journal, records) = match &resumed {
+        Some(checkpoint) => {
+            checkpoint::check_labels(&checkpoint.state.labels, &labels);
+            let (journal, records) = Journal::resume(output, COMMAND);
+            checkpoint::remove_unrecorded_logs(output, &checkpoint.state.batch_id, &records);
+            eprintln!("{} of {} games already played", records.len(), total_games);
+            (checkpoint.state.batch_id.clone(), checkpoint.state.base_seed, journal, records)
+        }
+        None => {
+            let base_seed = args.seed.unwrap_or_else(|| rand::rng().random());
+            (generate_batch_id(), base_seed, Journal::create(output, COMMAND), Vec::new())
+        }
+    };
+    let save_checkpoint = |matches: &[Match]| {
+        let state = TournamentRun {
+            batch_id: batch_id.clone(),
+            base_seed,
+            labels: labels.clone(),
+            matches: matches.to_vec(),
+        };
+        checkpoint::save(output, COMMAND, &Checkpoint { args, state });
+    };
+    if resumed.is_none() {
+        save_checkpoint(&matches);
+    }
 
-                    let pair = vec![variants[i].clone(), variants[j].clone()];
-                    let log = run_game(0, &pair, note.clone(), None, &mut rng);
-
-                    set_card_registry(&log.initial_state.card_lookup);
-                    set_sell_card_registry(&log.initial_state.sell_card_lookup);
-
-                    // Determine winner from final_scores
-                    let scores = log.final_scores.as_ref().unwrap();
-                    let ranking_0 = (
-                        scores[0].score,
-                        scores[0].completed_sell_cards,
-                        scores[0].color_wheel_total,
-                    );
-                    let ranking_1 = (
-                        scores[1].score,
-                        scores[1].completed_sell_cards,
-                        scores[1].color_wheel_total,
-                    );
-
-                    // Map player positions back to original variant indices
-                    let pv = log.player_variants.as_ref().unwrap();
-                    let idx_0 = name_to_index[pv[0].name.as_ref().unwrap()];
-                    let idx_1 = name_to_index[pv[1].name.as_ref().unwrap()];
-
-                    if ranking_0 > ranking_1 {
-                        stats.wins[idx_0].fetch_add(1, Ordering::Relaxed);
-                    } else if ranking_1 > ranking_0 {
-                        stats.wins[idx_1].fetch_add(1, Ordering::Relaxed);
-                    } else {
-                        stats.draws[idx_0].fetch_add(1, Ordering::Relaxed);
-                        stats.draws[idx_1].fetch_add(1, Ordering::Relaxed);
-                    }
-                    stats.games[idx_0].fetch_add(1, Ordering::Relaxed);
-                    stats.games[idx_1].fetch_add(1, Ordering::Relaxed);
-
-                    // Accumulate per-variant MCTS time and iterations
-                    for (player_pos, player_variant) in pv.iter().enumerate() {
-                        let vi = name_to_index[player_variant.name.as_ref().unwrap()];
-                        stats.time_ms[vi].fetch_add(log.player_time_ms[player_pos], Ordering::Relaxed);
-                        stats.iterations[vi].fetch_add(log.player_iterations[player_pos], Ordering::Relaxed);
+    let tournament = Tournament {
+        variants: &variants,
+        stats: TournamentStats::new(labels.clone()),
+        paired: PairedStats::new(labels.clone()),
+        results: Mutex::new(Vec::with_capacity(total_games)),
+        seatings: Mutex::new(HashMap::new()),
+        played: records.iter().map(|r| r.item).collect(),
+        journal,
+        players: num_players,
+        duplicate: args.duplicate,
+        base_seed,
+        note: &args.note,
+        output,
+        batch_id: &batch_id,
+        completed: AtomicU64::new(records.len() as u64),
+        total_games,
+    };
+    for record in &records {
+        tournament.record(record);
+    }
+
+    if swiss_rounds > 0 {
+        let per_round = num_variants / 2;
+        let mut round = matches.len() / per_round;
+        let mut previous = HashSet::new();
+        if round > 0 {
+            // Finish the round that was being played when the run stopped
+            let first = (round - 1) * per_round;
+            previous = matches[first..].iter().map(|m| pair_key(m.table[0], m.table[1])).collect();
+            tournament.play(&matches, first, threads);
+        }
+        while round < swiss_rounds {
+            let ratings = tournament.fit_ratings(&variant_priors);
+            let pairings = swiss_round(&ratings.ratings, &previous, &mut rng);
+            eprintln!("Swiss round {}/{}:", round + 1, swiss_rounds);
+            for m in &pairings {
+                eprintln!("  {} vs {}", labels[m.table[0]], labels[m.table[1]]);
+            }
+            previous = pairings.iter().map(|m| pair_key(m.table[0], m.table[1])).collect();
+            let first = matches.len();
+            matches.extend(pairings);
+            save_checkpoint(&matches);
+            tournament.play(&matches, first, threads);
+            round += 1;
+        }
+    } else {
+        tournament.play(&matches, 0, threads);
+    }
+
+    eprintln!("All {} games written to {}/", total_games, output);
+    print_summary(&tournament.stats);
+    if tournament.duplicate {
+        tournament.paired.print_summary();
+    }
+
+    // Fit ratings over every game of the tournament
+    let ratings = tournament.fit_ratings(&variant_priors);
+    let games: Vec<u64> = tournament.stats.games.iter().map(|g| g.load(Ordering::Relaxed)).collect();
+    print_ratings(&labels, &ratings, &games);
+    let ratings_path = args
+        .ratings_file
+        .clone()
+        .unwrap_or_else(|| format!("{}/ratings-{}.json", output, batch_id));
+    write_ratings(&ratings_path, &labels, &ratings, &games, total_games as u64);
+    eprintln!("Ratings written to {}", ratings_path);
+
+    let stats = &tournament.stats;
+    let scores = (0..num_variants)
+        .map(|i| stats.wins[i].load(Ordering::Relaxed) as f64 + 0.5 * stats.draws[i].load(Ordering::Relaxed) as f64)
+        .collect();
+    TournamentResults { batch_id, labels, games, scores, ratings }
+}
+
+/// A running tournament: the variants, where logs go, and the results so far.
+struct Tournament<'a> {
+    variants: &'a [NamedVariant],
+    stats: TournamentStats,
+    paired: PairedStats,
+    results: Mutex<Vec<Vec<(usize, Ranking)>>>,
+    /// The seatings played so far of each match, by match index.
+    seatings: Mutex<HashMap<usize, Vec<PlayerShares>>>,
+    /// Games already in the journal when the run was resumed.
+    played: HashSet<usize>,
+    journal: Journal,
+    players: usize,
+    duplicate: bool,
+    base_seed: u64,
+    note: &'a Option<String>,
+    output: &'a str,
+    batch_id: &'a str,
+    completed: AtomicU64,
+    total_games: usize,
+}
+
+impl Tournament<'_> {
+    /// Plays every game of `matches[first..]` not played yet on `threads`
+    /// threads. Game `rotation` of match `i` is item `i * players + rotation`
+    /// of the journal, and in duplicate mode match `i` is dealt from the base
+    /// seed + `i`.
+    fn play(&self, matches: &[Match], first: usize, threads: usize) {
+        // One work item per game, so that a few long matches still spread
+        // over all threads
+        let items: Vec<(usize, usize)> = (first..matches.len())
+            .flat_map(|i| (0..matches[i].games()).map(move |rotation| (i, rotation)))
+            .filter(|&(i, rotation)| !self.played.contains(&(i * self.players + rotation)))
+            .collect();
+        let next = AtomicUsize::new(0);
+
+        std::thread::scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| {
+                    let mut rng = WyRand::from_rng(&mut rand::rng());
+                    while let Some(&(i, rotation)) = items.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let m = &matches[i];
+                        let table: Vec<NamedVariant> = m.table.iter().map(|&v| self.variants[v].clone()).collect();
+                        let seating = if m.rotated { Seating::Rotated(rotation) } else { Seating::Shuffled };
+                        let deal_seed = self.duplicate.then(|| self.base_seed.wrapping_add(i as u64));
+                        let log = run_game(0, &table, self.note.clone(), None, seating, deal_seed, &mut rng);
+
+                        let log_file = write_game_log(self.output, self.batch_id, &log, &mut rng);
+                        let variant_of_seat = log.variant_order.iter().map(|&t| m.table[t]).collect();
+                        let record = GameRecord::new(i * self.players + rotation, log_file, &log, variant_of_seat);
+                        self.record(&record);
+                        self.journal.append(&record);
+
+                        let done = self.completed.fetch_add(1, Ordering::Relaxed) + 1;
+                        eprintln!("Game {}/{} complete", done, self.total_games);
                     }
+                });
+            }
+        });
+    }
 
-                    // Save game log
-                    let epoch_millis = now_epoch_millis();
-                    let game_id: String = {
-                        const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
-                        (0..4)
-                            .map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char)
-                            .collect()
-                    };
-                    let path = format!(
-                        "{}/game-{}-{}-{}.json",
-                        output_dir, epoch_millis, batch_id_str, game_id
-                    );
-                    let json = serde_json::to_string_pretty(&log).unwrap();
-                    std::fs::write(&path, json).unwrap();
-
-                    let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
-                    eprintln!("Game {}/{} complete", done, total_games);
+    /// Adds the results of a finished game to the statistics.
+    fn record(&self, record: &GameRecord) {
+        let stats = &self.stats;
+
+        // A sole winner wins; players tied for first draw
+        let shares = record.shares();
+        {
+            let mut seats = stats.seats.lock().unwrap();
+            for (seat, &(vi, share)) in shares.iter().enumerate() {
+                if share == 1.0 {
+                    stats.wins[vi].fetch_add(1, Ordering::Relaxed);
+                } else if share > 0.0 {
+                    stats.draws[vi].fetch_add(1, Ordering::Relaxed);
                 }
-            }));
+                stats.games[vi].fetch_add(1, Ordering::Relaxed);
+                seats[seat].0 += share;
+                seats[seat].1 += 1;
+            }
         }
+        self.results.lock().unwrap().push(record.players());
 
-        for handle in handles {
-            handle.join().unwrap();
+        // Accumulate per-variant MCTS time and iterations
+        for (seat, &vi) in record.variants.iter().enumerate() {
+            stats.time_ms[vi].fetch_add(record.player_time_ms[seat], Ordering::Relaxed);
+            stats.iterations[vi].fetch_add(record.player_iterations[seat], Ordering::Relaxed);
         }
-    });
 
-    eprintln!("All {} games written to {}/", total_games, output);
-    print_summary(stats);
+        // Duplicate matches are played once per seating
+        if self.duplicate {
+            let mut seatings = self.seatings.lock().unwrap();
+            let match_seatings = seatings.entry(record.item / self.players).or_default();
+            match_seatings.push(shares);
+            if match_seatings.len() == self.players {
+                self.paired.record_deal(match_seatings);
+            }
+        }
+    }
+
+    fn fit_ratings(&self, priors: &[Option<Prior>]) -> Ratings {
+        let comparisons: Vec<Comparison> = self
+            .results
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|game| comparisons_from_game(game))
+            .collect();
+        fit_ratings(self.variants.len(), &comparisons, priors)
+    }
 }
 
 fn print_summary(stats: &TournamentStats) {
@@ -286,4 +501,15 @@ fn print_summary(stats: &TournamentStats) {
             width = max_label,
         );
     }
+
+    // Win share by seat shows how much position bias the schedule had to absorb
+    let seats = stats.seats.lock().unwrap();
+    let by_seat: Vec<String> = seats
+        .iter()
+        .enumerate()
+        .filter(|(_, &(_, games))| games > 0)
+        .map(|(seat, &(share, games))| format!("seat {}: {:.1}%", seat + 1, share / games as f64 * 100.0))
+        .collect();
+    eprintln!();
+    eprintln!("Win share by seat: {}", by_seat.join(", "));
 }
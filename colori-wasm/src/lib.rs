@@ -3,8 +3,8 @@ use colori_core::colori_game::enumerate_choices;
 use colori_core::draft_phase::{advance_draft, simultaneous_pick};
 use colori_core::draw_phase::execute_draw_phase;
 use colori_core::game_log::{DrawEvent, DrawLog};
-use colori_core::ismcts::{ismcts, MctsConfig};
-use colori_core::scoring::{calculate_score, HeuristicParams};
+use colori_core::ismcts::{ismcts, MctsConfig, MctsTree};
+use colori_core::scoring::{calculate_score, sell_card_reachability, HeuristicParams};
 use colori_core::setup::create_initial_game_state;
 use colori_core::types::{Card, Choice, GameState, PlayerState};
 use colori_core::unordered_cards::{
@@ -12,6 +12,7 @@ use colori_core::unordered_cards::{
 };
 use rand::SeedableRng;
 use serde::Serialize;
+use std::sync::Arc;
 use wyrand::WyRand;
 use wasm_bindgen::prelude::*;
 
@@ -20,8 +21,8 @@ const TRAINED_PARAMS_JSON: &str = include_str!("../../genetic-algorithm/batch-lk
 fn deserialize_state(json: &str) -> GameState {
     let mut state: GameState =
         serde_json::from_str(json).expect("Failed to parse game state JSON");
-    state.card_lookup = get_card_registry();
-    state.sell_card_lookup = get_sell_card_registry();
+    state.card_lookup = Arc::new(get_card_registry());
+    state.sell_card_lookup = Arc::new(get_sell_card_registry());
     for p in state.players.iter_mut() {
         p.cached_score = calculate_score(p);
     }
@@ -40,16 +41,20 @@ pub fn wasm_run_ismcts(
     player_index: u32,
     iterations: u32,
     _ai_style: &str,
+    max_nodes: Option<u32>,
 ) -> String {
     let game_state = deserialize_state(game_state_json);
 
-    let max_rollout_round = std::cmp::max(8, game_state.round + 2);
-
     let mut rng = WyRand::from_rng(&mut rand::rng());
 
     let heuristic_params: HeuristicParams = serde_json::from_str(TRAINED_PARAMS_JSON)
         .expect("Failed to parse trained heuristic params");
-    let config = MctsConfig { iterations, ..MctsConfig::new(heuristic_params) };
+    let config = MctsConfig {
+        iterations,
+        max_nodes: max_nodes.map(|n| n as usize),
+        ..MctsConfig::new(heuristic_params)
+    };
+    let max_rollout_round = config.max_rollout_round(game_state.round);
     let result = ismcts(
         &game_state,
         player_index as usize,
@@ -62,6 +67,88 @@ pub fn wasm_run_ismcts(
     serde_json::to_string(&result.choice).expect("Failed to serialize choice")
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AnalysisChild<'a> {
+    choice: Option<&'a Choice>,
+    hidden: bool,
+    visits: u32,
+    average_reward: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PlayerTreeAnalysis<'a> {
+    player_index: usize,
+    visits: u32,
+    children: Vec<AnalysisChild<'a>>,
+}
+
+fn tree_analysis(player_index: usize, tree: &MctsTree) -> PlayerTreeAnalysis<'_> {
+    let root = tree.root();
+    let mut children: Vec<AnalysisChild> = root
+        .children()
+        .map(|c| AnalysisChild {
+            choice: c.choice(),
+            hidden: c.is_hidden(),
+            visits: c.visit_count(),
+            average_reward: c.average_reward(),
+        })
+        .collect();
+    children.sort_by_key(|c| std::cmp::Reverse(c.visits));
+    PlayerTreeAnalysis { player_index, visits: root.visit_count(), children }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IsmctsAnalysis<'a> {
+    choice: &'a Choice,
+    iterations_used: u32,
+    /// The searching player's tree first, then each opponent's tree when
+    /// `multi_observer` is set.
+    trees: Vec<PlayerTreeAnalysis<'a>>,
+}
+
+/// Run ISMCTS and return the chosen move with root statistics for every
+/// search tree, as JSON.
+#[wasm_bindgen]
+pub fn wasm_analyze_ismcts(
+    game_state_json: &str,
+    player_index: u32,
+    iterations: u32,
+    multi_observer: bool,
+) -> String {
+    let game_state = deserialize_state(game_state_json);
+    let player_index = player_index as usize;
+
+    let mut rng = WyRand::from_rng(&mut rand::rng());
+
+    let heuristic_params: HeuristicParams = serde_json::from_str(TRAINED_PARAMS_JSON)
+        .expect("Failed to parse trained heuristic params");
+    let config = MctsConfig { iterations, multi_observer, ..MctsConfig::new(heuristic_params) };
+    let max_rollout_round = config.max_rollout_round(game_state.round);
+    let result = ismcts(
+        &game_state,
+        player_index,
+        &config,
+        Some(max_rollout_round),
+        None,
+        &mut rng,
+    );
+
+    let mut trees: Vec<PlayerTreeAnalysis> = result.tree.iter().map(|t| tree_analysis(player_index, t)).collect();
+    trees.extend(
+        result
+            .opponent_trees
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != player_index)
+            .map(|(i, t)| tree_analysis(i, t)),
+    );
+    let analysis = IsmctsAnalysis { choice: &result.choice, iterations_used: result.iterations_used, trees };
+    serde_json::to_string(&analysis).expect("Failed to serialize analysis")
+}
+
 #[wasm_bindgen]
 pub fn wasm_create_initial_game_state(num_players: u32, ai_players_json: &str) -> String {
     let ai_players: Vec<bool> =
@@ -148,3 +235,13 @@ pub fn wasm_get_legal_actions(game_state_json: &str) -> String {
     let choices = enumerate_choices(&state);
     serde_json::to_string(&choices).expect("Failed to serialize choices")
 }
+
+/// For each displayed sell card, the mixes and missing primaries or material
+/// between the player and buying it, as JSON.
+#[wasm_bindgen]
+pub fn wasm_sell_card_reachability(game_state_json: &str, player_index: u32) -> String {
+    let state = deserialize_state(game_state_json);
+    let player = &state.players[player_index as usize];
+    let reach = sell_card_reachability(&player.color_wheel, &player.materials, &state.sell_card_display);
+    serde_json::to_string(&reach).expect("Failed to serialize sell card reachability")
+}
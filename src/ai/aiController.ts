@@ -19,6 +19,7 @@ interface PrecomputeEntry {
 export class AIController {
   private worker: Worker;
   aiStyle: string = 'ga';
+  maxNodes?: number;
 
   private precomputeMap = new Map<string, PrecomputeEntry>();
   private generationId = 0;
@@ -45,6 +46,7 @@ export class AIController {
         playerIndex,
         iterations,
         aiStyle: this.aiStyle,
+        maxNodes: this.maxNodes,
       }));
       this.worker.postMessage(plain);
     });
@@ -84,6 +86,7 @@ export class AIController {
         playerIndex: req.playerIndex,
         iterations: req.iterations,
         aiStyle: this.aiStyle,
+        maxNodes: this.maxNodes,
       }));
       worker.postMessage(plain);
       this.precomputeMap.set(key, entry);
@@ -6,6 +6,7 @@ export interface AIWorkerRequest {
   playerIndex: number;
   iterations: number;
   aiStyle?: string;
+  maxNodes?: number;
 }
 
 export interface AIWorkerSuccess {
@@ -32,10 +33,10 @@ function ensureInit(): Promise<unknown> {
 self.onmessage = async (event: MessageEvent<AIWorkerRequest>) => {
   try {
     await ensureInit();
-    const { gameState, playerIndex, iterations, aiStyle } = event.data;
+    const { gameState, playerIndex, iterations, aiStyle, maxNodes } = event.data;
     const gameStateJson = JSON.stringify(gameState);
 
-    const resultJson = wasm_run_ismcts(gameStateJson, playerIndex, iterations, aiStyle ?? 'ga');
+    const resultJson = wasm_run_ismcts(gameStateJson, playerIndex, iterations, aiStyle ?? 'ga', maxNodes);
 
     const choice = JSON.parse(resultJson);
     self.postMessage({ type: 'success', choice } satisfies AIWorkerSuccess);
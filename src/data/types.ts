@@ -205,6 +205,8 @@ export interface StructuredGameLog {
   iterations?: number;
   playerVariants?: PlayerVariant[];
   note?: string;
+  // u64 written as a string so it survives JSON number parsing
+  dealSeed?: string;
 }
 
 export interface FinalPlayerStats {
@@ -1,4 +1,4 @@
-import type { GameState, Choice, PlayerState, Color, Card, DrawEvent } from '../data/types';
+import type { GameState, Choice, PlayerState, Color, Card, DrawEvent, MaterialType } from '../data/types';
 import { mixResult } from '../data/colors';
 import { getCardData, getSellCardData, getAnyCardData } from '../data/cards';
 import init, {
@@ -8,6 +8,7 @@ import init, {
   wasm_simultaneous_pick,
   wasm_advance_draft,
   wasm_calculate_scores,
+  wasm_sell_card_reachability,
 } from '../wasm-pkg/colori_wasm.js';
 
 let initialized = false;
@@ -67,6 +68,17 @@ export function calculateScores(players: PlayerState[], playerNames: string[]):
   return scores.map((score, i) => ({ name: playerNames[i], score }));
 }
 
+export interface SellCardReach {
+  sellCardInstanceId: number;
+  mixes: [Color, Color][];
+  missingPrimaries: Color[];
+  missingMaterial: MaterialType | null;
+}
+
+export function getSellCardReachability(state: GameState, playerIndex: number): SellCardReach[] {
+  return JSON.parse(wasm_sell_card_reachability(JSON.stringify(state), playerIndex));
+}
+
 export function cloneGameState(state: GameState): GameState {
   return JSON.parse(JSON.stringify(state));
 }